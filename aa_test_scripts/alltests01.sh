@@ -5,21 +5,44 @@ cargo run allcplcode/cpltests/cpltest_basic_assign.cpl
 cargo run allcplcode/cpltests/cpltest_basic_break.cpl
 cargo run allcplcode/cpltests/cpltest_basic_continue.cpl
 cargo run allcplcode/cpltests/cpltest_basic_dict.cpl
+cargo run allcplcode/cpltests/cpltest_basic_do_until.cpl
+cargo run allcplcode/cpltests/cpltest_basic_elseif.cpl
 cargo run allcplcode/cpltests/cpltest_basic_eq_ne.cpl -w
 cargo run allcplcode/cpltests/cpltest_basic_eval.cpl
 cargo run allcplcode/cpltests/cpltest_basic_exit.cpl
 cargo run allcplcode/cpltests/cpltest_basic_exp.cpl
 cargo run allcplcode/cpltests/cpltest_basic_fc.cpl
+cargo run allcplcode/cpltests/cpltest_basic_for.cpl
 cargo run allcplcode/cpltests/cpltest_basic_foreach.cpl
+cargo run allcplcode/cpltests/cpltest_basic_function_values.cpl
 cargo run allcplcode/cpltests/cpltest_basic_if.cpl
+cargo run allcplcode/cpltests/cpltest_basic_in.cpl
 cargo run allcplcode/cpltests/cpltest_basic_literal.cpl
 cargo run allcplcode/cpltests/cpltest_basic_loop.cpl
 cargo run allcplcode/cpltests/cpltest_basic_lvalue.cpl
 cargo run allcplcode/cpltests/cpltest_basic_matrix.cpl
 cargo run allcplcode/cpltests/cpltest_basic_multi_level_struct.cpl
 cargo run allcplcode/cpltests/cpltest_basic_return.cpl
+cargo run allcplcode/cpltests/cpltest_basic_short_circuit.cpl
 cargo run allcplcode/cpltests/cpltest_basic_string.cpl
+cargo run allcplcode/cpltests/cpltest_basic_string_interpolation.cpl
+cargo run allcplcode/cpltests/cpltest_basic_string_raw_multiline.cpl
 cargo run allcplcode/cpltests/cpltest_basic_struct.cpl
+cargo run allcplcode/cpltests/cpltest_basic_ternary.cpl
+cargo run allcplcode/cpltests/cpltest_basic_try_catch.cpl
+cargo run allcplcode/cpltests/cpltest_basic_global.cpl
+cargo run allcplcode/cpltests/cpltest_basic_default_named.cpl
+cargo run allcplcode/cpltests/cpltest_basic_variadic.cpl
+cargo run allcplcode/cpltests/cpltest_basic_destructuring.cpl
+cargo run allcplcode/cpltests/cpltest_basic_methods.cpl
+cargo run allcplcode/cpltests/cpltest_basic_constructors.cpl
+cargo run allcplcode/cpltests/cpltest_basic_embedding.cpl
+cargo run allcplcode/cpltests/cpltest_basic_eval_patterns.cpl
+cargo run allcplcode/cpltests/cpltest_basic_labeled_loops.cpl
+cargo run allcplcode/cpltests/cpltest_basic_integers.cpl
+cargo run allcplcode/cpltests/cpltest_basic_decimals.cpl
+cargo run allcplcode/cpltests/cpltest_basic_dates.cpl
+cargo run allcplcode/cpltests/cpltest_basic_operators.cpl
 cargo run allcplcode/cpltests/cpltest_basic_unary.cpl
 cargo run allcplcode/cpltests/cpltest_basic_while_break.cpl
 cargo run allcplcode/cpltests/cpltest_basic_while_continue.cpl
@@ -62,6 +85,7 @@ cargo run allcplcode/cpltests/cpltest_fc08.cpl
 cargo run allcplcode/cpltests/cpltest_foreach00.cpl
 cargo run allcplcode/cpltests/cpltest_foreach01.cpl
 cargo run allcplcode/cpltests/cpltest_if00.cpl
+cargo run allcplcode/cpltests/cpltest_import.cpl
 cargo run allcplcode/cpltests/cpltest_include.cpl
 cargo run allcplcode/cpltests/cpltest_literal00.cpl
 cargo run allcplcode/cpltests/cpltest_op_overload.cpl
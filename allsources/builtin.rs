@@ -2,6 +2,7 @@ use cplvar::*;
 use regex::Regex;	
 use regex::Match;
 use macrolib::*;
+use std::collections::HashMap;
 
 
 //****************************************************************
@@ -32,6 +33,11 @@ pub struct BuiltinFunctions{
 	pub builtin_function_list : Vec<BuiltinFunctionDetail>,
 	pub opens : Vec<CplVar>,
 	pub compiled_regex_list : Vec<Regex>,
+	when_regexes : HashMap<String, Regex>,		// the regexes of when patterns, compiled once
+
+	//	The message left by a builtin function that failed.  The executor
+	//	throws it as an error that can be caught
+	pub error : Option<String>,
 }
 
 impl BuiltinFunctions {
@@ -39,6 +45,8 @@ impl BuiltinFunctions {
 		BuiltinFunctions{
 			opens : Vec::new(),
 			compiled_regex_list : Vec::new(),
+			when_regexes : HashMap::new(),
+			error : None,
 			builtin_function_list : vec!
 				[
 					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_dump_operands, "Dump".to_string(), 
@@ -64,11 +72,45 @@ impl BuiltinFunctions {
 					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_length, "Length".to_string(), 
 						["collection".to_string()].to_vec()),
 
+					//	Decimal Functions
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_decimal, "Decimal".to_string(), 
+						["value".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_divide, "Divide".to_string(), 
+						["dividend".to_string(), "divisor".to_string(), "places".to_string(), "rounding".to_string()].to_vec()),
+
+					//	Date Functions
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_now, "Now".to_string(), 
+						Vec::new()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_parse_date, "ParseDate".to_string(), 
+						["text".to_string(), "format".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_format_date, "FormatDate".to_string(), 
+						["date".to_string(), "format".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_year, "Year".to_string(), 
+						["date".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_month, "Month".to_string(), 
+						["date".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_day, "Day".to_string(), 
+						["date".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_hour, "Hour".to_string(), 
+						["date".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_minute, "Minute".to_string(), 
+						["date".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_second, "Second".to_string(), 
+						["date".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_weekday, "Weekday".to_string(), 
+						["date".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_add_days, "AddDays".to_string(), 
+						["date".to_string(), "days".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_add_months, "AddMonths".to_string(), 
+						["date".to_string(), "months".to_string()].to_vec()),
+					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_at_offset, "AtOffset".to_string(), 
+						["date".to_string(), "offset".to_string()].to_vec()),
+
 					//	Collection Functions
 					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_keys, "Keys".to_string(), 
 						["collection".to_string()].to_vec()),
 					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_sort, "Sort".to_string(), 
-						["collection".to_string()].to_vec()),
+						["collection".to_string(), "compare=".to_string()].to_vec()),
 					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_push, "Push".to_string(), 
 						["collection".to_string(),"item_to_push".to_string()].to_vec()),
 					BuiltinFunctionDetail::new(BuiltinFunctions::builtin_pop, "Pop".to_string(), 
@@ -110,9 +152,27 @@ impl BuiltinFunctions {
 		at end - 1 and so on.
 
 		Each of these functions edits the arguments list for length
-		and data type, crashing if anything is wrong
+		and data type, crashing if anything is wrong.  Bad values
+		(e.g. a start past the end of the string) are reported with
+		fail() so that they can be caught
 	*********************************************************/
 
+	fn fail(&mut self, message : String) -> CplVar{
+		self.error = Some(message);
+		CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new()))
+	}
+
+	//	Only numbers, strings and bools can be used as dictionary keys
+	fn check_key(&mut self, key : &CplVar) -> bool{
+		match key.var{
+			CplDataType::CplNumber(_) | CplDataType::CplString(_) | CplDataType::CplBool(_) => true,
+			_ => {
+				self.fail(format!("a dictionary key must be a number, string or bool.  Found: {}", key.var));
+				false
+			},
+		}
+	}
+
 	pub fn builtin_dump_operands(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
 		if let CplDataType::CplString(ref s) = arguments[0].var{
 			operand_stack.dump_operands_with_message(&s.cpl_string);
@@ -146,8 +206,8 @@ impl BuiltinFunctions {
 		let start = operand_stack.dereference(&arguments[argslen-2]).as_number();
 		let len = operand_stack.dereference(&arguments[argslen-3]).as_number();
 
-		if (start+len) > haystack.len() as f64{
-			panic!("from Builtin Function 'substr' - start + len > length of haystack");
+		if start < 0.0 || len < 0.0 || (start+len) > haystack.len() as f64{
+			return self.fail(format!("start ({}) + length ({}) is past the end of the string (length {})", start, len, haystack.len()));
 		}
 		let ss: String = haystack.chars().skip(start as usize).take(len as usize).collect();
 		
@@ -158,7 +218,10 @@ impl BuiltinFunctions {
 		let haystack_suffix: String = haystack.chars().skip(start as usize).collect();
 		let haystack_prefix: String = haystack.chars().take(start).collect();
 		
-		let re = Regex::new(needle).unwrap();
+		let re = match Regex::new(needle){
+			Ok(re) => re,
+			Err(e) => return self.fail(format!("invalid regular expression: {}", e)),
+		};
 		let temp = re.replace_all(&haystack_suffix, replacement);
 		let updated = format!("{}{}", haystack_prefix, temp);
 		CplVar::new(CplDataType::CplString(CplString::new(updated)))
@@ -194,10 +257,8 @@ impl BuiltinFunctions {
 			}
 		}
 
-		let start_limit = haystack.len() - needle.len();
-		if start > start_limit{
-			// abend!(format!("from Builtin Function 'Replace' - start is > haystack length - match length"));
-			panic!("from Builtin Function 'Replace' - start is > haystack length - match length: start={} haystack={} needle={} limit={}", start, haystack.len(), needle.len(), start_limit);
+		if needle.len() > haystack.len() || start > haystack.len() - needle.len(){
+			return self.fail(format!("start ({}) is past the last place the needle ({}) can be found in a string of length {}", start, needle, haystack.len()));
 		}
 		let rslt = haystack.get(start..).unwrap().replace(&needle, &replacement);
 		
@@ -223,7 +284,10 @@ impl BuiltinFunctions {
 		if delimiter.len() > 2 && delimiter.as_bytes()[0] as char == '/' && delimiter.as_bytes()[delimiter.len()-1] as char == '/'{
 			if delimiter.len() > 3 && delimiter.as_bytes()[delimiter.len()-2] as char != '\\'{
 				let regex_delimiter : String = delimiter.chars().skip(1).take(delimiter.len()-2).collect();
-				let re = Regex::new(&regex_delimiter).unwrap();
+				let re = match Regex::new(&regex_delimiter){
+					Ok(re) => re,
+					Err(e) => return self.fail(format!("invalid regular expression: {}", e)),
+				};
 				rslt = re.split(&haystack).map(|m| m.to_string()).collect();
 				//println!(".... {:?}",rslt);
 			}else{
@@ -257,22 +321,24 @@ impl BuiltinFunctions {
 		let start = operand_stack.dereference(&arguments[argslen-3]).as_number() as usize;
 
 		if start >= haystack.len() {
-			abend!(format!("from Builtin Function 'Match' - start is > haystack length"));
+			return self.fail(format!("start ({}) is past the end of the string (length {})", start, haystack.len()));
 		}
 
 		let haystack_suffix: String = haystack.chars().skip(start as usize).collect();
 		
 		let re : Regex;
 		if let CplDataType::CplString(regex) = needle.var{
-			re = Regex::new(&regex.cpl_string).unwrap();
+			re = match Regex::new(&regex.cpl_string){
+				Ok(re) => re,
+				Err(e) => return self.fail(format!("invalid regular expression: {}", e)),
+			};
 		}else if let CplDataType::CplNumber(n) = needle.var{
-			re = self.compiled_regex_list[n.cpl_number as usize].clone();
-		}else{
-			if match_or_cap == 'm'{
-				abend!(format!("from Builtin Function 'Match' - needle must be either regex string or the number returned from Regex)"));
-			}else{
-				abend!(format!("from Builtin Function 'Capture' - needle must be either regex string or the number returned from Regex)"));
+			match self.compiled_regex_list.get(n.cpl_number as usize){
+				Some(compiled) => re = compiled.clone(),
+				None => return self.fail(format!("{} is not a number returned from Regex", n.cpl_number)),
 			}
+		}else{
+			return self.fail("needle must be either a regex string or the number returned from Regex".to_string());
 		}
 		
 		let mut rtn_array = CplArray::new();
@@ -312,6 +378,19 @@ impl BuiltinFunctions {
 		return self.match_or_capture_help('m', arguments, operand_stack);
 	}
 
+	//	The regex match of a when pattern (when /<regex>/).  The regex is compiled the first
+	//	time it's used.  Returns the compile error if it isn't a valid regex
+	pub fn regex_is_match(&mut self, haystack : &str, pattern : &str) -> Result<bool, String>{
+		if !self.when_regexes.contains_key(pattern){
+			let re = match Regex::new(pattern){
+				Ok(re) => re,
+				Err(e) => return Err(format!("invalid regular expression: {}", e)),
+			};
+			self.when_regexes.insert(pattern.to_string(), re);
+		}
+		Ok(self.when_regexes[pattern].is_match(haystack))
+	}
+
 	//	Compile a regex and put in the compiled_regex_list
 	pub fn builtin_regex(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
 		if arguments.len() != 1 {
@@ -319,7 +398,10 @@ impl BuiltinFunctions {
 		}
 
 		let needle = operand_stack.dereference(&arguments[0]).as_string();
-		let re = Regex::new(&needle).unwrap();
+		let re = match Regex::new(&needle){
+			Ok(re) => re,
+			Err(e) => return self.fail(format!("invalid regular expression: {}", e)),
+		};
 		self.compiled_regex_list.push(re);
 		let rtn = self.compiled_regex_list.len() - 1;
 		return CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Int, rtn as f64)));
@@ -366,6 +448,236 @@ impl BuiltinFunctions {
 	}
 
 
+	//*******************************************************
+	//***************  Decimal Functions ********************
+	//*******************************************************
+
+	//	the value of a number or numeric string as a decimal
+	fn to_decimal(&mut self, value : &CplVar) -> Option<CplDecimal>{
+		match value.var{
+			CplDataType::CplNumber(ref n) => n.as_decimal(),
+			CplDataType::CplString(ref s) => CplDecimal::parse(&s.cpl_string),
+			_ => None,
+		}
+	}
+
+	//	Decimal("12.34") or Decimal(n)
+	pub fn builtin_decimal(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		if arguments.len() != 1 {
+			abend!(format!("from Builtin Function 'Decimal' - Expecing 1 argument, found {}", arguments.len()));
+		}
+
+		let value = operand_stack.dereference(&arguments[0]);
+		match self.to_decimal(&value){
+			Some(d) => CplVar::new(CplDataType::CplNumber(CplNumber::new_decimal(d))),
+			None => self.fail(format!("{} is not a decimal number", value)),
+		}
+	}
+
+	//	Divide(dividend, divisor, places, rounding) divides as decimals keeping places digits
+	//	after the decimal point.  rounding is one of half_even, half_up, down, up, floor or ceiling
+	pub fn builtin_divide(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		if arguments.len() != 4 {
+			abend!(format!("from Builtin Function 'Divide' - Expecing 4 arguments, found {}", arguments.len()));
+		}
+
+		let argslen = arguments.len();
+		let dividend = operand_stack.dereference(&arguments[argslen-1]);
+		let divisor = operand_stack.dereference(&arguments[argslen-2]);
+		let places = operand_stack.dereference(&arguments[argslen-3]).as_number();
+		let rounding_name = operand_stack.dereference(&arguments[argslen-4]).as_string();
+
+		if places < 0.0 || places > MAX_DECIMAL_PLACES as f64{
+			return self.fail(format!("the places ({}) must be from 0 to {}", places, MAX_DECIMAL_PLACES));
+		}
+		let rounding = match DecimalRounding::from_name(&rounding_name){
+			Some(rounding) => rounding,
+			None => return self.fail(format!("unknown rounding '{}'.  Use half_even, half_up, down, up, floor or ceiling", rounding_name)),
+		};
+
+		let (a, b) = match (self.to_decimal(&dividend), self.to_decimal(&divisor)){
+			(Some(a), Some(b)) => (a, b),
+			_ => return self.fail(format!("Divide({}, {}) needs two numbers", dividend, divisor)),
+		};
+		if b.units == 0{
+			return self.fail(format!("decimal division by zero: Divide({}, {})", a, b));
+		}
+		match a.div(&b, places as u32, rounding){
+			Some(d) => CplVar::new(CplDataType::CplNumber(CplNumber::new_decimal(d))),
+			None => self.fail(format!("decimal overflow: Divide({}, {})", a, b)),
+		}
+	}
+
+	//*******************************************************
+	//***************  Date Functions ***********************
+	//*******************************************************
+
+	//	the date in an argument or None (after failing) if it isn't a date
+	fn to_datetime(&mut self, value : &CplVar) -> Option<CplDateTime>{
+		if let CplDataType::CplDateTime(d) = value.var{
+			return Some(d);
+		}
+		self.fail(format!("expecting a date.  Found: {}", value));
+		None
+	}
+
+	//	the current date and time in UTC
+	pub fn builtin_now(&mut self, arguments : &Vec<CplVar>, _operand_stack : &mut OperandStack) -> CplVar{
+		if arguments.len() != 0 {
+			abend!(format!("from Builtin Function 'Now' - Expecing 0 arguments, found {}", arguments.len()));
+		}
+		CplVar::new(CplDataType::CplDateTime(CplDateTime::now()))
+	}
+
+	//	ParseDate("2024-03-05 14:30", "%Y-%m-%d %H:%M")
+	pub fn builtin_parse_date(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		if arguments.len() != 2 {
+			abend!(format!("from Builtin Function 'ParseDate' - Expecing 2 arguments, found {}", arguments.len()));
+		}
+
+		let argslen = arguments.len();
+		let text = operand_stack.dereference(&arguments[argslen-1]).as_string();
+		let format = operand_stack.dereference(&arguments[argslen-2]).as_string();
+		match CplDateTime::parse(&text, &format){
+			Ok(d) => CplVar::new(CplDataType::CplDateTime(d)),
+			Err(message) => self.fail(message),
+		}
+	}
+
+	//	FormatDate(d, "%d %B %Y")
+	pub fn builtin_format_date(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		if arguments.len() != 2 {
+			abend!(format!("from Builtin Function 'FormatDate' - Expecing 2 arguments, found {}", arguments.len()));
+		}
+
+		let argslen = arguments.len();
+		let value = operand_stack.dereference(&arguments[argslen-1]);
+		let format = operand_stack.dereference(&arguments[argslen-2]).as_string();
+		let date = match self.to_datetime(&value){
+			Some(d) => d,
+			None => return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())),
+		};
+		match date.format(&format){
+			Ok(text) => CplVar::new(CplDataType::CplString(CplString::new(text))),
+			Err(message) => self.fail(message),
+		}
+	}
+
+	//	one component of a date (at the date's offset).  component is 0 for the year,
+	//	1 the month ... 5 the second and 6 the weekday (0 is Sunday)
+	fn date_component(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack, function_name : &str, component : usize) -> CplVar{
+		if arguments.len() != 1 {
+			abend!(format!("from Builtin Function '{}' - Expecing 1 argument, found {}", function_name, arguments.len()));
+		}
+
+		let value = operand_stack.dereference(&arguments[0]);
+		let date = match self.to_datetime(&value){
+			Some(d) => d,
+			None => return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())),
+		};
+		let (year, month, day, hour, minute, second) = date.components();
+		let rslt = match component{
+			0 => year,
+			1 => month as i64,
+			2 => day as i64,
+			3 => hour as i64,
+			4 => minute as i64,
+			5 => second as i64,
+			_ => date.weekday() as i64,
+		};
+		CplVar::new(CplDataType::CplNumber(CplNumber::new_int(rslt)))
+	}
+
+	pub fn builtin_year(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		self.date_component(arguments, operand_stack, "Year", 0)
+	}
+
+	pub fn builtin_month(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		self.date_component(arguments, operand_stack, "Month", 1)
+	}
+
+	pub fn builtin_day(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		self.date_component(arguments, operand_stack, "Day", 2)
+	}
+
+	pub fn builtin_hour(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		self.date_component(arguments, operand_stack, "Hour", 3)
+	}
+
+	pub fn builtin_minute(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		self.date_component(arguments, operand_stack, "Minute", 4)
+	}
+
+	pub fn builtin_second(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		self.date_component(arguments, operand_stack, "Second", 5)
+	}
+
+	pub fn builtin_weekday(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		self.date_component(arguments, operand_stack, "Weekday", 6)
+	}
+
+	//	the date and the whole number in the arguments of AddDays and AddMonths
+	fn date_and_count(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack, function_name : &str) -> Option<(CplDateTime, i64)>{
+		if arguments.len() != 2 {
+			abend!(format!("from Builtin Function '{}' - Expecing 2 arguments, found {}", function_name, arguments.len()));
+		}
+
+		let argslen = arguments.len();
+		let value = operand_stack.dereference(&arguments[argslen-1]);
+		let count = operand_stack.dereference(&arguments[argslen-2]);
+		let date = self.to_datetime(&value)?;
+		match count.var{
+			CplDataType::CplNumber(ref n) if n.is_int() || n.as_real().fract() == 0.0 => Some((date, n.as_int())),
+			_ => {
+				self.fail(format!("expecting a whole number.  Found: {}", count));
+				None
+			},
+		}
+	}
+
+	//	AddDays(d, -7) is a week earlier
+	pub fn builtin_add_days(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		let (date, days) = match self.date_and_count(arguments, operand_stack, "AddDays"){
+			Some(date_and_days) => date_and_days,
+			None => return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())),
+		};
+		match date.add_days(days){
+			Some(d) => CplVar::new(CplDataType::CplDateTime(d)),
+			None => self.fail(format!("date overflow: AddDays({}, {})", date, days)),
+		}
+	}
+
+	//	AddMonths(d, 1) keeps the day unless the next month is shorter
+	pub fn builtin_add_months(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		let (date, months) = match self.date_and_count(arguments, operand_stack, "AddMonths"){
+			Some(date_and_months) => date_and_months,
+			None => return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())),
+		};
+		match date.add_months(months){
+			Some(d) => CplVar::new(CplDataType::CplDateTime(d)),
+			None => self.fail(format!("date overflow: AddMonths({}, {})", date, months)),
+		}
+	}
+
+	//	AtOffset(d, "+05:30") is the same moment shown at another offset from UTC
+	pub fn builtin_at_offset(&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
+		if arguments.len() != 2 {
+			abend!(format!("from Builtin Function 'AtOffset' - Expecing 2 arguments, found {}", arguments.len()));
+		}
+
+		let argslen = arguments.len();
+		let value = operand_stack.dereference(&arguments[argslen-1]);
+		let offset_text = operand_stack.dereference(&arguments[argslen-2]).as_string();
+		let date = match self.to_datetime(&value){
+			Some(d) => d,
+			None => return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())),
+		};
+		match CplDateTime::parse_offset(&offset_text){
+			Some(offset) => CplVar::new(CplDataType::CplDateTime(CplDateTime::new(date.seconds, offset))),
+			None => self.fail(format!("'{}' is not an offset.  Use +hh:mm, -hh:mm or Z", offset_text)),
+		}
+	}
+
 	//*******************************************************
 	//***************  Collection Functions *****************
 	//*******************************************************
@@ -378,17 +690,17 @@ impl BuiltinFunctions {
 		let var = operand_stack.dereference(&arguments[0]);
 
 		if let CplDataType::CplVarRef(var_ref) = &arguments[1].var{
-			let operand_frames = operand_stack.operand_frames.last_mut().unwrap();
+			let operand_frames = &mut operand_stack.operand_frames[var_ref.frame_num];
 			if let CplDataType::CplArray(array) = &mut operand_frames.operand_blocks[var_ref.block_num].operand_block[var_ref.address].var{
 				array.append(&var);
 			}else if let CplDataType::CplDict(dict) = &mut operand_frames.operand_blocks[var_ref.block_num].operand_block[var_ref.address].var{
 				dict.append(&var);
 			}else{
-				panic!("From:  builtin_append - Append:  Expecting parameter 1 to be a reference to an array or dictionary (e.g. append(&array,item_to_append))")
+				return self.fail("Expecting parameter 1 to be a reference to an array or dictionary (e.g. Append(&array,item_to_append))".to_string());
 			}
 			return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new()));
 		}else{
-			panic!("From:  builtin_append - Append:  Expecting parameter 1 to be a reference to an array or dictionary (e.g. Append(&array,item_to_append))")
+			return self.fail("Expecting parameter 1 to be a reference to an array or dictionary (e.g. Append(&array,item_to_append))".to_string());
 		}
 	}
 
@@ -402,27 +714,33 @@ impl BuiltinFunctions {
 			if let CplDataType::CplDict(dict) = &mut operand_stack.operand_frames[var_ref.frame_num].operand_blocks[var_ref.block_num].operand_block[var_ref.address].var{
 				return dict.keys();
 			}else{
-				panic!("From:  builtin_keys - Keys:  Expecting parameter 1 to be a reference to an array (e.g. Keys(&array))")
+				return self.fail("Expecting parameter 1 to be a reference to a dictionary (e.g. Keys(&dictionary))".to_string());
 			}
 		}else{
-			panic!("From:  builtin_keys - Keys:  Expecting parameter 1 to be a reference to an array (e.g. Keys(&array))")
+			return self.fail("Expecting parameter 1 to be a reference to a dictionary (e.g. Keys(&dictionary))".to_string());
 		}
 	}
 
 	pub fn builtin_sort (&mut self, arguments : &Vec<CplVar>, operand_stack : &mut OperandStack) -> CplVar{
-		if (arguments.len()) != 1{
-			abend!(format!("from Builtin Function 'Sort' - Expecting 1 argument, found {}", arguments.len()));
+		if (arguments.len()) != 2{
+			abend!(format!("from Builtin Function 'Sort' - Expecting 2 arguments, found {}", arguments.len()));
 		}
 
-		if let CplDataType::CplVarRef(var_ref) = &arguments[0].var{
+		//	a comparator function is called by the executor, which does the sort itself
+		let compare = operand_stack.dereference(&arguments[0]);
+		if !matches!(&compare.var, CplDataType::CplString(s) if s.cpl_string.is_empty()){
+			return self.fail("Expecting parameter 2 to be a function (e.g. Sort(&array, fn(a, b){return a - b;}))".to_string());
+		}
+
+		if let CplDataType::CplVarRef(var_ref) = &arguments[1].var{
 			if let CplDataType::CplArray(array) = &mut operand_stack.operand_frames[var_ref.frame_num].operand_blocks[var_ref.block_num].operand_block[var_ref.address].var{
 				array.sort();
 				return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new()));
 			}else{
-				panic!("From:  builtin_sort - Sort:  Expecting parameter 1 to be a reference to an array (e.g. Sort(&array))")
+				return self.fail("Expecting parameter 1 to be a reference to an array (e.g. Sort(&array))".to_string());
 			}
 		}else{
-			panic!("From:  builtin_sort - Sort:  Expecting parameter 1 to be a reference to an array (e.g. Sort(&array))")
+			return self.fail("Expecting parameter 1 to be a reference to an array (e.g. Sort(&array))".to_string());
 		}
 	}
 
@@ -435,15 +753,15 @@ impl BuiltinFunctions {
 		let new_value = operand_stack.dereference(&arguments[0]);
 
 		if let CplDataType::CplVarRef(var_ref) = &arguments[1].var{
-			let operand_frames = operand_stack.operand_frames.last_mut().unwrap();
+			let operand_frames = &mut operand_stack.operand_frames[var_ref.frame_num];
 			if let CplDataType::CplArray(array) = &mut operand_frames.operand_blocks[var_ref.block_num].operand_block[var_ref.address].var{
 				array.push(&new_value);
 				return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new()));
 			}else{
-				panic!("From builtin_push - Push:  Expecting parameter 1 to be a reference to an array (e.g. Push(&array,item_to_push))")	
+				return self.fail("Expecting parameter 1 to be a reference to an array (e.g. Push(&array,item_to_push))".to_string());
 			}
 		}else{
-			panic!("From builtin_push - Push:  Expecting parameter 1 to be a reference to an array (e.g. Push(&array,item_to_push))")
+			return self.fail("Expecting parameter 1 to be a reference to an array (e.g. Push(&array,item_to_push))".to_string());
 		}
 	}
 
@@ -453,14 +771,14 @@ impl BuiltinFunctions {
 		}
 
 		if let CplDataType::CplVarRef(var_ref) = &arguments[0].var{
-			let operand_frames = operand_stack.operand_frames.last_mut().unwrap();
+			let operand_frames = &mut operand_stack.operand_frames[var_ref.frame_num];
 			if let CplDataType::CplArray(array) = &mut operand_frames.operand_blocks[var_ref.block_num].operand_block[var_ref.address].var{
 				return array.pop();
 			}else{
-				abend!(format!("From builtin_pop Pop().  Expecting a reference to an array (e.g. Pop(&array))"));
+				return self.fail("Expecting a reference to an array (e.g. Pop(&array))".to_string());
 			}
 		}else{
-			abend!(format!("From builtin_pop Pop().  Expecting a reference to an array (e.g. Pop(&array))"));
+			return self.fail("Expecting a reference to an array (e.g. Pop(&array))".to_string());
 		}
 	}
 
@@ -474,15 +792,26 @@ impl BuiltinFunctions {
 		let new_value = operand_stack.dereference(&arguments[1]);
 		let update_flag = operand_stack.dereference(&arguments[0]);
 
+		if !self.check_key(&key){
+			return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new()));
+		}
+		let update = match update_flag.var{
+			CplDataType::CplBool(ref b) => b.cpl_bool,
+			_ => return self.fail(format!("Expecting parameter 4 to be a boolean.  Found: {}", update_flag.var)),
+		};
+
 		if let CplDataType::CplVarRef(var_ref) = &arguments[3].var{
 			if let CplDataType::CplDict(dict) = &mut operand_stack.operand_frames[var_ref.frame_num].operand_blocks[var_ref.block_num].operand_block[var_ref.address].var{
+				if !update && dict.contains_key(&CplKey::to_key(&key.var)){
+					return self.fail(format!("key {} already exists and update_flag is false", key.var));
+				}
 				dict.insert_builtin(&key, &new_value, &update_flag);
 			}else{
-				panic!("From:  builtin_insert - Insert:  Expecting parameter 1 to be a reference to a dictionary (e.g. Insert(&dict,key,value)")
+				return self.fail("Expecting parameter 1 to be a reference to a dictionary (e.g. Insert(&dict,key,value,update_flag))".to_string());
 			}
 			return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new()));
 		}else{
-			panic!("From:  builtin_insert - Insert:  Expecting parameter 1 to be a reference to a dictionary (e.g. Insert(&dict,key,value)")
+			return self.fail("Expecting parameter 1 to be a reference to a dictionary (e.g. Insert(&dict,key,value,update_flag))".to_string());
 		}
 	}
 
@@ -492,6 +821,9 @@ impl BuiltinFunctions {
 		}
 
 		let key = operand_stack.dereference(&arguments[0]);
+		if !self.check_key(&key){
+			return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new()));
+		}
 
 		if let CplDataType::CplVarRef(var_ref) = &arguments[1].var{
 			// let operand_frames = operand_stack.operand_frames.last_mut().unwrap();
@@ -499,10 +831,10 @@ impl BuiltinFunctions {
 				dict.delete(&key);
 				return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new()));
 			}else{
-				panic!("From builtin_push - Delete:  Expecting parameter 1 to be a reference to a dictionary(e.g. Delete(&dictionary,key))")	
+				return self.fail("Expecting parameter 1 to be a reference to a dictionary (e.g. Delete(&dictionary,key))".to_string());
 			}
 		}else{
-			panic!("From builtin_push - Delete:  Expecting parameter 1 to be a reference to a dictionary(e.g. Delete(&dictionary,key))")	
+			return self.fail("Expecting parameter 1 to be a reference to a dictionary (e.g. Delete(&dictionary,key))".to_string());
 		}
 	}
 
@@ -511,18 +843,20 @@ impl BuiltinFunctions {
 			abend!(format!("from Builtin Function 'Push' - Expecting 2 arguments, found {}", arguments.len()));
 		}
 
-		// let container = ;
-		// let key = ;
+		if !self.check_key(&arguments[0]){
+			return CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new()));
+		}
 
 		if let CplDataType::CplVarRef(ref var_ref) = &arguments[1].var{
 			// let operand_frames = operand_stack.operand_frames.last_mut().unwrap();
 			if let CplDataType::CplDict(dict) = &mut operand_stack.operand_frames[var_ref.frame_num].operand_blocks[var_ref.block_num].operand_block[var_ref.address].var{
 				return dict.contains(&arguments[0]);
 			}else{
-				panic!("From builtin:Contains:  Parameter 1 is a reference but doesn't appear to be pointing a dictionary. It is {}",&mut operand_stack.operand_frames[var_ref.frame_num].operand_blocks[var_ref.block_num].operand_block[var_ref.address].var);
+				let found = format!("{}", operand_stack.operand_frames[var_ref.frame_num].operand_blocks[var_ref.block_num].operand_block[var_ref.address].var);
+				return self.fail(format!("Parameter 1 is a reference but doesn't point to a dictionary.  It is {}", found));
 			}
 		}else{
-			panic!("From builtin:Contains:  Expecting parameter 1 to be a reference to a dictionary(e.g. Contains(&dictionary,key))")	
+			return self.fail("Expecting parameter 1 to be a reference to a dictionary (e.g. Contains(&dictionary,key))".to_string());
 		}
 	}
 
@@ -541,8 +875,15 @@ impl BuiltinFunctions {
 		let file_name = arguments[argslen-1].as_string();
 		let open_mode = arguments[argslen-2].as_string();
 		
+		if open_mode.is_empty(){
+			return self.fail(format!("Unknown open mode '{}'",open_mode));
+		}
+
 		match open_mode.as_bytes()[0] as char{
 			'<' =>{
+				if !std::path::Path::new(&file_name).is_file(){
+					return self.fail(format!("Unable to open {}", file_name));
+				}
 				self.opens.push(CplVar::new(CplDataType::CplFileReader(CplFileReader::new(&file_name, open_mode.as_str()))));
 				return CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Int, (self.opens.len()-1) as f64)));
 			},
@@ -555,7 +896,7 @@ impl BuiltinFunctions {
 					return CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Int, (self.opens.len()-1) as f64)));	
 				}
 			},
-			_ => abend!(format!("Unknown open mode '{}'",open_mode)),
+			_ => return self.fail(format!("Unknown open mode '{}'",open_mode)),
 		}
 	}
 
@@ -632,7 +973,7 @@ impl BuiltinFunctions {
 			if let CplDataType::CplFileWriter(ref mut w) = self.opens[file_num as usize].var{
 				match &arguments[argslen-2].var{
 					CplDataType::CplString(s) => w.write(&s.cpl_string, writeln),
-					CplDataType::CplNumber(n) => w.write(&n.cpl_number.to_string(), writeln),
+					CplDataType::CplNumber(n) => w.write(&n.to_string(), writeln),
 					CplDataType::CplBool(b) => w.write(&b.cpl_bool.to_string(), writeln),
 					CplDataType::CplVarRef(vr) => {
 						let array = operand_stack.operand_frames.get(vr.frame_num).unwrap().operand_blocks.get(vr.block_num).unwrap().operand_block.get(vr.address).unwrap();
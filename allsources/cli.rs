@@ -68,6 +68,7 @@ fn usage_all(){
 	eprintln!("                   | 'h' (help/usage))");
 	eprintln!("                   | 'o'<file> (output file)");
 	eprintln!("                   | 'p'<file> (performance stats csv file)");
+	eprintln!("                   | 'I'<dir> (directory to search for include and import files)");
 	eprintln!("   <arguments>    ::= a space separated list of arguments passed to ENTRY function");
 	
 	usage_d_swiches();
@@ -75,6 +76,9 @@ fn usage_all(){
 	eprintln!("\n");
 	eprintln!("        -d values can be 'added' together to specify multiple switches; e.g. -d1+4 will set bits 1 and 4");
 	eprintln!("        -w switch tells the executor to println a warning message instead of abend if possible");
+	eprintln!("        -I may be repeated.  Include and import files are searched for relative to the including file,");
+	eprintln!("           then in the -I directories, then in the directories listed in CPL_PATH and finally");
+	eprintln!("           relative to the working directory");
 	eprintln!("\n");
 	eprintln!("        NOTE: the source file may appear anywhere in the argument list but must be the first non-switch");
 	eprintln!("        <switches can appear anywhere on the command line");
@@ -95,6 +99,7 @@ pub struct CLI<'a>{
 	cl : & 'a Vec<String>,
 	pub cl_args : Vec<String>,
 	runtime_warnings : i32,
+	include_dirs : Vec<String>,
 }
 
 impl<'a> CLI<'a>{
@@ -107,6 +112,7 @@ impl<'a> CLI<'a>{
 			cl : cl,
 			cl_args : Vec::new(),
 			runtime_warnings : 0,
+			include_dirs : Vec::new(),
 		};
 
 		if cli.cl.len() < 2 {
@@ -128,6 +134,7 @@ impl<'a> CLI<'a>{
 					'o' | 'O' => {let ls = cli.get_switch_parameter(false); cli.switch_insert('o',&ls)},
 					'w' | 'W' => cli.runtime_warnings += 1,
 					'p' | 'P' => {let ls = cli.get_switch_parameter(false); cli.switch_insert('p',&ls)}
+					'I' | 'i' => {let ls = cli.get_switch_parameter(false); cli.include_dirs.push(ls)}
 					'-' => {},
 					_   =>	{
 								eprintln!("Switch {} ignored",cl[cli.cl_index].as_bytes()[1] as char);
@@ -203,6 +210,11 @@ impl<'a> CLI<'a>{
 		&self.switches.get(&'s').unwrap()
 	}
 
+	//	the -I directories in the order they were given
+	pub fn include_dirs(&self) -> &Vec<String>{
+		&self.include_dirs
+	}
+
 	pub fn debug(&self) -> u32{
 		if self.switches.contains_key(&'d'){
 			let p = self.switches.get(&'d').unwrap();
@@ -2,6 +2,7 @@
 use std::collections::HashMap;
 use builtin::*;
 use machineinstruction::*;
+use tokenizer::*;
 use macrolib::*;
 
 
@@ -35,7 +36,22 @@ impl FrameMap{
 		let mut builtin_function_num = 0;
 		for builtin_function in &fm.builtin_function_table.builtin_function_list{
 			fm.frame_names.insert(builtin_function.name.clone(), (builtin_function_num, true) );
-			fm.frames_list.push(CodeFrame::new(&builtin_function.name, false, true, builtin_function.arguments.clone()));
+			//	a builtin parameter written as "name=value" is optional and defaults to the string value
+			let mut parameters : Vec<String> = Vec::new();
+			let mut parameter_defaults : Vec<Option<Token>> = Vec::new();
+			for argument in &builtin_function.arguments{
+				match argument.split_once('='){
+					Some((name, default)) => {
+						parameters.push(name.to_string());
+						parameter_defaults.push(Some(Token::make_string(default)));
+					},
+					None => {
+						parameters.push(argument.clone());
+						parameter_defaults.push(None);
+					},
+				}
+			}
+			fm.frames_list.push(CodeFrame::new(&builtin_function.name, false, true, parameters, parameter_defaults));
 			builtin_function_num += 1;
 		}
 		fm
@@ -142,18 +158,20 @@ pub struct CodeFrame{
 	pub is_entry : bool,				// if true then this frame holds the entry function
 	pub is_builtin : bool,
 	pub parameters : Vec<String>,					// parameters for the function
+	pub parameter_defaults : Vec<Option<Token>>,	// the default value (a literal) of each parameter, if it has one
 	pub code_block_list : Vec<CodeBlock>,			// The list intermediate code blocks
 	pub current_code_block_num : Vec<usize>,		// Which code block are we adding instructions to
 }
 
 impl CodeFrame {
-	pub fn new(function_name : &str, is_entry : bool, is_builtin : bool, parameters : Vec<String>) -> CodeFrame{
+	pub fn new(function_name : &str, is_entry : bool, is_builtin : bool, parameters : Vec<String>, parameter_defaults : Vec<Option<Token>>) -> CodeFrame{
 		CodeFrame{
 			//cli : cli,
 			function_name : function_name.to_string(),
 			is_entry : is_entry,
 			is_builtin : is_builtin,
 			parameters : parameters,
+			parameter_defaults : parameter_defaults,
 			code_block_list : Vec::new(),
 			current_code_block_num : Vec::new(),
 		}
@@ -173,6 +173,11 @@ pub struct CodeGen<'a>{
 	//	and this is where to go when continue instruction executed
 	continue_address : Vec<(usize,usize)>,
 
+	//	A label waiting for the loop that follows it and, once the loop's
+	//	block has been added, the labeled loop blocks of the current function
+	loop_label : Option<Token>,
+	loop_labels : HashMap<usize, String>,
+
 	block_begin_counter : usize,
 
 	index_expression_comma_counter : usize,
@@ -187,6 +192,36 @@ pub struct CodeGen<'a>{
 	//	inside an Eval
 	eval_data : Vec<EvalData>,
 
+	//	When a do is generated, the block and address of its Bl instruction is
+	//	saved here so that the until can fill in the break address (which isn't
+	//	known until after the until condition has been generated).  It's a
+	//	stack because there might be a do inside a do
+	do_data : Vec<(usize,usize)>,
+
+	//	When a try is generated, the block and address of its Try instruction is
+	//	saved here so that the catch can fill in the catch block number.  It's a
+	//	stack because there might be a try inside a try
+	try_data : Vec<(usize,usize)>,
+
+	//	The address of the Jf/Jt generated at the end of the left operand of '&&'
+	//	or '||'.  It's patched when the operator itself is generated.  It's a stack
+	//	because the right operand can contain another '&&' or '||'
+	short_circuit : Vec<usize>,
+
+	//	The address of the Jf at the end of the condition of '?', replaced by the address
+	//	of the J at the end of the first choice.  A stack because the choices can nest
+	ternary : Vec<usize>,
+
+	//	Function literals (see Tokenizer::lift_function_literals) mapped to the names
+	//	they might capture and, once the literal has been generated, the names they did
+	//	capture.  The captured names become extra parameters of the lifted function
+	function_literals : HashMap<String, Vec<String>>,
+	closure_captures : HashMap<String, Vec<String>>,
+
+	//	Global variables and their (postfix) initializers.  The initializers are
+	//	generated at the start of the entry function
+	global_variables : Vec<(Token, GlobalEntry, Vec<Token>)>,
+
 	//	we can't use the symbol table for structs so we construct a mini
 	//	symbol table here
 	struct_list : Vec<Struct>,
@@ -205,8 +240,17 @@ impl<'a> CodeGen<'a>{
 			index_expression_comma_counter : 0,
 			break_address : Vec::new(),
 			continue_address : Vec::new(),
+			loop_label : None,
+			loop_labels : HashMap::new(),
 			//function_call_hold : Vec::new(),
 			eval_data : Vec::new(),
+			do_data : Vec::new(),
+			try_data : Vec::new(),
+			short_circuit : Vec::new(),
+			ternary : Vec::new(),
+			function_literals : HashMap::new(),
+			closure_captures : HashMap::new(),
+			global_variables : Vec::new(),
 			struct_list : Vec::new(),
 			struct_map : HashMap::new(),
 		}
@@ -278,6 +322,19 @@ impl<'a> CodeGen<'a>{
 		}
 	}
 
+	//	Add the global variables to the symbol table in the order they were declared
+	//	and hang on to their initializers for the entry function
+	pub fn add_global_variables(&mut self, global_variables : &Vec<GlobalStatement>){
+		for global_variable in global_variables{
+			let entry = self.symbol_table.add_global_variable(&global_variable.global_id.token_value);
+			self.global_variables.push((global_variable.global_id.clone(), entry, global_variable.global_value.clone()));
+		}
+	}
+
+	pub fn add_function_literals(&mut self, function_literals : &HashMap<String, Vec<String>>){
+		self.function_literals = function_literals.clone();
+	}
+
 	fn get_random_id(&self) -> String{
 		let temp : u16 = random();
 		let rtn = format!("$temp{}", temp);
@@ -341,6 +398,13 @@ impl<'a> CodeGen<'a>{
 		//	Add a new block to the block list
 		self.frames.frames_list.get_mut(function_num).unwrap().code_block_list.push(CodeBlock::new(breakable));
 
+		//	a loop's block is breakable.  If the loop is labeled, remember the label
+		if breakable{
+			if let Some(label) = self.loop_label.take(){
+				self.loop_labels.insert(block_num, label.token_value);
+			}
+		}
+
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    add_code_block: added {}", block_num);}
 
 		//	return the block number of the block just created
@@ -383,15 +447,11 @@ impl<'a> CodeGen<'a>{
 		*self.frames.frame_names.get(function_name).unwrap()
 	}
 
-	//	look up the parameter count from the frame
-	fn get_function_parameter_count(&self, function_num : usize) -> usize{
-		self.frames.frames_list[function_num].parameters.len()
-	}
 
 
 	//	We add all of the functions to the Frame Map before we generate op codes.  I think this will obviate the
 	//	need for forward functions.  We also add SymbolTableFunctions in the symbol table.
-	pub fn add_function_to_frame(&mut self, name : &String, function_parameters : &Vec<String>, function_entry_flag : bool){
+	pub fn add_function_to_frame(&mut self, name : &String, function_parameters : &Vec<String>, function_parameter_defaults : &Vec<Option<Token>>, function_entry_flag : bool){
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::ADD_FUNCTION_TO_FRAME function name={}", name);}
 
 		//	Make sure that the function doesn't already exist
@@ -399,8 +459,20 @@ impl<'a> CodeGen<'a>{
 			abend!(format!("Function {} already defined", name));
 		}
 
+		//	A default that names a lit is replaced by the lit's value so that a call through
+		//	a function value (which is bound at run time) can use it
+		let mut parameter_defaults = function_parameter_defaults.clone();
+		for default in parameter_defaults.iter_mut().flatten(){
+			if default.token_type != TokenType::ID{
+				continue;
+			}
+			if let Some(SymbolTableEntryType::LiteralEntry(literal_detail)) = self.symbol_table.get_global_symbol_entry(&default.token_value){
+				*default = self.literal_token(default, &literal_detail.literal_type);
+			}
+		}
+
 		//	Add a new frame to the frame map
-		self.frames.add_new_frame(name.to_string(), CodeFrame::new(name, function_entry_flag, false, function_parameters.clone()));
+		self.frames.add_new_frame(name.to_string(), CodeFrame::new(name, function_entry_flag, false, function_parameters.clone(), parameter_defaults));
 	}
 
 	/**
@@ -532,6 +604,34 @@ impl<'a> CodeGen<'a>{
 			
 			//eprintln!("======= Returning to: {} {:?}", member_stack_entry.current_struct.name, member_indices);
 		}
+
+		self.add_struct_methods_to_symbol_table(local_var, struct_index);
+	}
+
+	//	Each method of the struct goes into the symbol table as <local>:<method> so
+	//	that a call like p:shift(1) can find the function implementing it
+	fn add_struct_methods_to_symbol_table(&mut self, local_var : &str, struct_index : usize){
+		let methods = self.struct_list.get(struct_index).unwrap().methods.clone();
+		for (method, function_name) in methods{
+			self.symbol_table.add_struct_method(format!("{}:{}", local_var, method), &function_name);
+		}
+	}
+
+	//	If the call is a method call (e.g. p:shift) return the call with the name
+	//	of the function that implements the method.  Anything else is returned as is
+	fn resolve_method_call(&mut self, call : &Token) -> Token{
+		if call.token_value.replace("::", "").find(':') == None{
+			return call.clone();
+		}
+
+		match self.symbol_table.get_symbol_entry(&call.token_value){
+			Some(SymbolTableEntryType::StructMethodEntry(method)) => {
+				let mut resolved = call.clone();
+				resolved.token_value = method.function_name;
+				resolved
+			}
+			_ => abend!(format!("Error from resolve_method_call: at {}, {} isn't a method of a struct (or the struct was never instantiated)", call.location(), call.token_value)),
+		}
 	}
 
 
@@ -542,12 +642,28 @@ impl<'a> CodeGen<'a>{
 		//  add a frame to the symbol table so that block numbers start at 0
 		self.symbol_table.add_frame(name);
 
+		//	block numbers start over so do the loop labels
+		self.loop_labels.clear();
+
 		//	add code block and the symbol table block for it
 		let current_block = self.add_code_block(false, function_num);
 		
 		//	and make the block current
 		self.make_block_current(current_block, function_num);
 
+		//	The globals are initialized before anything else happens
+		if function_entry_flag{
+			self.gen_global_initializers(function_num);
+		}
+
+		//	A function literal also receives the values it captured, after its declared
+		//	parameters (see gen_expression_function_literal)
+		let mut all_parameters = function_parameters.clone();
+		if let Some(captures) = self.closure_captures.get(name){
+			all_parameters.extend(captures.iter().cloned());
+		}
+		let function_parameters = &all_parameters;
+
 		//  if this isn't an entry function and there are no parameters, then we're done
 		if function_parameters.len() == 0{
 			return;
@@ -608,7 +724,8 @@ impl<'a> CodeGen<'a>{
 			//	the instanstiated struct in memory, we are only making sure that the
 			//	symbol table is constructed so references can be found.
 
-			let parts: Vec<&str> = function_parameters[parameter_index].split(':').collect();
+			//	(the rest parameter, ...<var>, receives an array of the extra arguments)
+			let parts: Vec<&str> = function_parameters[parameter_index].trim_start_matches("...").splitn(2, ':').collect();
 
 			//	If this is a qualified ID then we need to add the struct information
 			//	to the symbol table
@@ -653,6 +770,44 @@ impl<'a> CodeGen<'a>{
 		}
 	}
 
+	//	Allocate each global in the global operand frame and, if it has one, evaluate its
+	//	initializer.  An initializer can use the globals declared before it
+	fn gen_global_initializers(&mut self, function_num : usize){
+		let global_variables = self.global_variables.clone();
+		for (global_id, entry, global_value) in &global_variables{
+			self.add_machine_instruction(
+				MachineInstruction::new(
+					Opcode::Alloc
+					, OpcodeMode::GlobalVar
+					, self.symbol_table.current_frame()
+					, 0
+					, entry.index
+					, Vec::new()
+					, entry.interner
+					, global_id.clone()
+				),function_num
+			);
+
+			if global_value.is_empty(){
+				continue;
+			}
+
+			self.gen_expression(global_value, function_num);
+			self.add_machine_instruction(
+				MachineInstruction::new(
+					Opcode::Update
+					, OpcodeMode::UpdateGlobal
+					, self.symbol_table.current_frame()
+					, 0
+					, entry.index
+					, Vec::new()
+					, entry.interner
+					, global_id.clone()
+				),function_num
+			);
+		}
+	}
+
 	/****************************************
 	***		Command Line Interface        ***
 	*****************************************/
@@ -777,7 +932,8 @@ impl<'a> CodeGen<'a>{
 
 		match token.token_type{
 			TokenType::ID | TokenType::INDEXED_ID | TokenType::IDADDR | TokenType::QUALIFIED_ID => self.gen_expression_id(token, function_num),
-			TokenType::INTEGER | TokenType::FLOAT | TokenType::STRING  | TokenType::BOOL => self.gen_expression_scalar(token, function_num),
+			TokenType::INTEGER | TokenType::FLOAT | TokenType::DECIMAL | TokenType::STRING  | TokenType::BOOL => self.gen_expression_scalar(token, function_num),
+			TokenType::FUNCTION_LITERAL => self.gen_expression_function_literal(token, function_num),
 			_ => abend!(format!("from gen_expression_factor:  Houston we have a problem.  Factors not mapped correctly.{}", token.token_type)),			
 		}
 	}
@@ -904,6 +1060,30 @@ impl<'a> CodeGen<'a>{
 		);
 	}
 
+	//	Same as gen_expression_id_detail except the variable is in the global frame
+	fn gen_expression_global(&mut self, token : &Token, global_detail : &GlobalEntry, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_expression_global: {} {}", token, global_detail);}
+		let mode : OpcodeMode;
+		if token.token_type == TokenType::IDADDR{
+			mode = OpcodeMode::GlobalVarRef;
+		}else{
+			mode = OpcodeMode::GlobalVar;
+		}
+
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Push
+				, mode
+				, self.symbol_table.current_frame()
+				, 0
+				, global_detail.index 				// address in the global frame
+				, Vec::new()
+				, global_detail.interner
+				, token.clone()
+			),function_num
+		);
+	}
+
 	//	If the ID was the name of an instantiated struct, we want to push a VarRef
 	//	pointing to it onto the stack.
 	fn gen_expression_struct(&mut self, token : &Token, symbol_block_num : usize, symbol_index : usize, function_num : usize){
@@ -934,7 +1114,12 @@ impl<'a> CodeGen<'a>{
 		let entry = self.symbol_table.get_symbol_entry(&token.token_value);
 	
 		match entry {
-			None => abend!(&format!("From gen_expression_id: {} Not in symbol table",token.token_value)),
+			//	a function name used as a value
+			None if token.token_type == TokenType::ID && self.frames.frame_names.contains_key(&token.token_value) => {
+				let target_frame_info = self.get_function_frame_info(&token.token_value);
+				self.gen_closure(token, target_frame_info.0, 0, function_num);
+			}
+			None => abend!(&format!("From gen_expression_id: at {}, {} Not in symbol table", token.location(), token.token_value)),
 			Some(ref e) => match e{
 				SymbolTableEntryType::NormalSymbolEntry(normal_symbol_detail) => {
 					self.gen_expression_id_detail(token, &normal_symbol_detail, function_num);
@@ -944,6 +1129,10 @@ impl<'a> CodeGen<'a>{
 					self.gen_expression_struct_member(&token, function_num);
 				}
 
+				SymbolTableEntryType::StructMethodEntry(_) => {
+					abend!(format!("From gen_expression_id: at {}, {} is a method.  It can only be called, e.g. {}(...)", token.location(), token.token_value, token.token_value));
+				}
+
 				SymbolTableEntryType::StructEntry(struct_detail) => {
 					self.gen_expression_struct(token, struct_detail.block_num, struct_detail.index, function_num);
 				}
@@ -951,6 +1140,10 @@ impl<'a> CodeGen<'a>{
 				SymbolTableEntryType::StructChildEntry (_struct_detail) => {
 				}
 
+				SymbolTableEntryType::GlobalEntry(global_detail) => {
+					self.gen_expression_global(token, &global_detail, function_num);
+				}
+
 				//	If it turns out the id is actually a literal, just push it's value onto
 				//	the stack
 				SymbolTableEntryType::LiteralEntry(literal_detail) => {
@@ -960,12 +1153,88 @@ impl<'a> CodeGen<'a>{
 		}
 	}
 
-	fn gen_expression_literal(&mut self, token : &Token, literal_type : &LiteralType, function_num : usize){
-		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_expression_literal: {}:{}", token, literal_type);}
+	//	The tokenizer lifted the function literal into a function of its own named by the token.
+	//	Any of the names used in its body that are variables here are captured: their
+	//	values are pushed and become extra parameters of the lifted function.
+	fn gen_expression_function_literal(&mut self, token : &Token, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_expression_function_literal: {}", token);}
+
+		let candidates = self.function_literals.get(&token.token_value).cloned().unwrap_or_default();
+		let mut captures : Vec<String> = Vec::new();
+		for name in candidates{
+			if let Some(SymbolTableEntryType::NormalSymbolEntry(detail)) = self.symbol_table.get_symbol_entry(&name){
+				let mut capture_token = token.clone();
+				capture_token.token_type = TokenType::ID;
+				capture_token.token_value = name.clone();
+				self.gen_expression_id_detail(&capture_token, &detail, function_num);
+				captures.push(name);
+			}
+		}
+
+		let target_frame_info = self.get_function_frame_info(&token.token_value);
+		self.frames.frames_list[target_frame_info.0].parameters.extend(captures.iter().cloned());
+		let capture_count = captures.len();
+		self.closure_captures.insert(token.token_value.clone(), captures);
+
+		self.gen_closure(token, target_frame_info.0, capture_count, function_num);
+	}
+
+	//	Push a function value for the frame, taking its captured values off the operand stack
+	fn gen_closure(&mut self, token : &Token, target_frame_num : usize, capture_count : usize, function_num : usize){
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Closure
+				, OpcodeMode::NONE
+				, self.symbol_table.current_frame()
+				, target_frame_num				// frame number of the function
+				, 0
+				, vec!(capture_count)			// number of captured values on the stack
+				, 0
+				, token.clone()
+			),function_num
+		);
+	}
+
+	//	Call through a variable holding a function value.  The value is pushed after
+	//	the arguments and the parameter count is checked when the call is made.
+	fn gen_indirect_function_call(&mut self, token : &Token, detail : &NormalSymbolEntry, arg_count : usize, is_statement : usize, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_indirect_function_call: {}", token);}
+
+		//	the parameters aren't known until the call is made so the arguments are all positional,
+		//	though arrays can be spread into them
+		let mut argument_order : Vec<usize> = Vec::new();
+		if !token.arg_names.is_empty(){
+			for name in &token.arg_names{
+				match name.as_str(){
+					"" => argument_order.push(ARG_POSITIONAL),
+					"..." => argument_order.push(ARG_SPREAD),
+					_ => abend!(format!("From gen_indirect_function_call: at {}, arguments can't be named when calling through {} which holds a function value", token.location(), token.token_value)),
+				}
+			}
+		}
+
+		let mut callee = token.clone();
+		callee.token_type = TokenType::ID;
+		self.gen_expression_id_detail(&callee, detail, function_num);
+
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::FunctionCall
+				, OpcodeMode::Indirect
+				, self.symbol_table.current_frame()
+				, 0
+				, 0
+				, [vec!(arg_count, is_statement), argument_order].concat()
+				, 0
+				, token.clone()
+			),function_num
+		);
+	}
 
+	//	The value of a lit as a literal token
+	fn literal_token(&self, token : &Token, literal_type : &LiteralType) -> Token{
 		let mut lit_token = token.clone();
 		lit_token.token_category = TokenCategory::Factor;
-		
 
 		match literal_type{
 			LiteralType::LiteralNumber(ref v) 	=>{
@@ -984,6 +1253,13 @@ impl<'a> CodeGen<'a>{
 				panic! ("from gen_expression_literal_detail: Array and Dictionary Literals not supported yet");
 			}
 		}
+		lit_token
+	}
+
+	fn gen_expression_literal(&mut self, token : &Token, literal_type : &LiteralType, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_expression_literal: {}:{}", token, literal_type);}
+
+		let lit_token = self.literal_token(token, literal_type);
 
 		self.add_machine_instruction(
 			MachineInstruction::new(
@@ -1019,6 +1295,8 @@ impl<'a> CodeGen<'a>{
 	fn gen_expression_function_call(&mut self, token : &Token, function_num : usize){
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_expression_function_call: {}", token);}
 
+		let token = &self.resolve_method_call(token);
+
 		//  For function calls in an expression, we build machine instructions
 		//	in the order they appear in the postfix express.
 		
@@ -1027,17 +1305,25 @@ impl<'a> CodeGen<'a>{
 		//	queried along with an item counter.  When the item counter has gone to zero, the
 		//	function call instruction is added to the opcode list.
 
+		//	a variable holding a function value
+		if !self.frames.frame_names.contains_key(&token.token_value){
+			if let Some(SymbolTableEntryType::NormalSymbolEntry(detail)) = self.symbol_table.get_symbol_entry(&token.token_value){
+				if let TokenType::FUNCTION_CALL(arg_count) = token.token_type{
+					self.gen_indirect_function_call(token, &detail, arg_count, 0, function_num);
+					return;
+				}
+			}
+		}
+
 		//	get the info from the function frame (constructed by the model)
 		let target_frame_info = self.get_function_frame_info(&token.token_value);
-		//	get the number of parameters defined by the called function
-		let target_parameter_count = self.get_function_parameter_count(target_frame_info.0);
-		//	extract the number of arguments defined by the function call and if it
-		//	doesn't match the number of parameters of the called routine, die with an error
-		if let TokenType::FUNCTION_CALL(arg_count) = token.token_type{
-			if arg_count != target_parameter_count{
-				abend!(format!("From gen_expression_function_call:  Argument count ({}) doesn't match parameter count ({}) for function {}", arg_count, target_parameter_count, token.token_value));
-			}
+		//	extract the number of arguments defined by the function call and match them up with
+		//	the parameters of the called routine (dying with an error if they don't)
+		let mut arg_count = 0;
+		if let TokenType::FUNCTION_CALL(count) = token.token_type{
+			arg_count = count;
 		}
+		let (pushed_count, argument_order) = self.gen_argument_binding(token, target_frame_info.0, arg_count, function_num);
 
 		//	define whether or not the function is internal or builtin
 		let opcode_mode : OpcodeMode;
@@ -1054,7 +1340,7 @@ impl<'a> CodeGen<'a>{
 			, self.symbol_table.current_frame()
 			, target_frame_info.0				// frame number of called function
 			, function_num							// for function calls this is the frame where the instruction needs to go
-			, vec!(target_parameter_count, 0)	// number of parameters declared, is_statement = false
+			, [vec!(pushed_count, 0), argument_order].concat()	// number of values pushed, is_statement = false, argument order
 			, 0
 			, token.clone()						// function name
 		);
@@ -1062,6 +1348,107 @@ impl<'a> CodeGen<'a>{
 		self.add_machine_instruction(fc, function_num);
 	}
 
+	//	Match the arguments of a call with the parameters of the called function.  Positional
+	//	arguments come first and are followed by named ones (f(1, c: "y")).  Any parameter
+	//	that isn't given an argument gets its default, which is pushed after the arguments,
+	//	and positional arguments past the last parameter go to the rest parameter (...rest).
+	//	Returns the number of values pushed and, if they aren't simply the arguments in
+	//	parameter order, what each of them is (see ARG_POSITIONAL) so that the call can
+	//	put them in order.  When an array is spread into the arguments (f(...a)) the number
+	//	of arguments isn't known until the call so the matching is left to the call.
+	fn gen_argument_binding(&mut self, call : &Token, target_frame_num : usize, arg_count : usize, function_num : usize) -> (usize, Vec<usize>){
+		let parameters = self.frames.frames_list[target_frame_num].parameters.clone();
+		let parameter_defaults = self.frames.frames_list[target_frame_num].parameter_defaults.clone();
+
+		//	a struct parameter is named by its variable (e.g. p of p:point)
+		let parameter_names : Vec<String> = parameters.iter().map(|p| p.trim_start_matches("...").splitn(2, ':').next().unwrap().to_string()).collect();
+		let mut rest_num : Option<usize> = None;
+		if parameters.last().map_or(false, |p| p.starts_with("...")){
+			rest_num = Some(parameters.len() - 1);
+		}
+		let positional_count = parameters.len() - rest_num.map_or(0, |_| 1);
+		let has_spread = call.arg_names.iter().any(|name| name == "...");
+
+		let mut argument_order : Vec<usize> = Vec::new();
+		let mut is_bound = vec!(false; parameters.len());
+		let mut has_named = false;
+		for argument_num in 0..arg_count{
+			let name = call.arg_names.get(argument_num).cloned().unwrap_or_default();
+			if name.is_empty() || name == "..."{
+				if has_named{
+					abend!(format!("From gen_argument_binding: at {}, positional argument {} of {} follows a named argument", call.location(), argument_num + 1, call.token_value));
+				}
+				if name == "..."{
+					argument_order.push(ARG_SPREAD);
+				}else if has_spread{
+					argument_order.push(ARG_POSITIONAL);
+				}else if argument_num < positional_count{
+					is_bound[argument_num] = true;
+					argument_order.push(argument_num);
+				}else if let Some(rest_num) = rest_num{
+					argument_order.push(rest_num);
+				}else{
+					abend!(format!("From gen_argument_binding: at {}, argument count ({}) is more than the parameter count ({}) for function {}", call.location(), arg_count, parameters.len(), call.token_value));
+				}
+				continue;
+			}
+
+			has_named = true;
+			let parameter_num : usize;
+			match parameter_names.iter().position(|p| *p == name){
+				Some(p) if Some(p) != rest_num => parameter_num = p,
+				Some(_) => abend!(format!("From gen_argument_binding: at {}, the rest parameter {} of {} can't be named", call.location(), name, call.token_value)),
+				None => abend!(format!("From gen_argument_binding: at {}, function {} has no parameter named {}", call.location(), call.token_value, name)),
+			}
+
+			if is_bound[parameter_num]{
+				abend!(format!("From gen_argument_binding: at {}, parameter {} of {} is given more than one argument", call.location(), parameter_names[parameter_num], call.token_value));
+			}
+			is_bound[parameter_num] = true;
+			argument_order.push(parameter_num);
+		}
+
+		//	Now the defaults of the parameters that weren't given an argument.  With a
+		//	spread we don't know which those are so all of the defaults are pushed
+		for parameter_num in 0..positional_count{
+			if is_bound[parameter_num]{
+				continue;
+			}
+
+			match parameter_defaults.get(parameter_num).cloned().flatten(){
+				Some(default) => self.gen_parameter_default(call, &parameter_names[parameter_num], &default, function_num),
+				None if has_spread => continue,
+				None => abend!(format!("From gen_argument_binding: at {}, argument count ({}) doesn't match parameter count ({}) for function {}:  there's no argument for {}", call.location(), arg_count, parameters.len(), call.token_value, parameter_names[parameter_num])),
+			}
+
+			if has_spread{
+				argument_order.push(ARG_DEFAULT + parameter_num);
+			}else{
+				argument_order.push(parameter_num);
+			}
+		}
+
+		//	the values are already in parameter order (the call always collects a rest parameter)
+		let pushed_count = argument_order.len();
+		if rest_num.is_none() && argument_order.iter().enumerate().all(|(i, p)| i == *p){
+			argument_order.clear();
+		}
+		(pushed_count, argument_order)
+	}
+
+	//	Push the default value of a parameter.  It's either a literal or the name of a lit
+	fn gen_parameter_default(&mut self, call : &Token, parameter : &str, default : &Token, function_num : usize){
+		if default.token_type != TokenType::ID{
+			self.gen_expression_scalar(default, function_num);
+			return;
+		}
+
+		match self.symbol_table.get_symbol_entry(&default.token_value){
+			Some(SymbolTableEntryType::LiteralEntry(literal_detail)) => self.gen_expression_literal(default, &literal_detail.literal_type, function_num),
+			_ => abend!(format!("From gen_parameter_default: at {}, the default value of {} ({}) for function {} isn't a lit", call.location(), parameter, default.token_value, call.token_value)),
+		}
+	}
+
 	//	When the token in the expression is a "list separator" type comma, we
 	//	are constructing an array via a literal array (e.g. [1,2,3]) and this
 	//	causes the update of the array.  The array is at the top of the stack
@@ -1213,6 +1600,134 @@ impl<'a> CodeGen<'a>{
 		self.collection_context.pop();
 	}
 
+	//	The left operand of '&&' or '||' has been generated.  Check that it's a bool and,
+	//	if it decides the result, jump around the right operand.  The jump address
+	//	isn't known until the operator is seen so it's patched in gen_expression_short_circuit
+	//
+	//		<left operand>
+	//		Land|Lor			check the left operand is a bool
+	//		Jf|Jt  result		'&&' jumps when false, '||' jumps when true
+	//		<right operand>
+	//		Land|Lor			check the right operand is a bool (it's the result)
+	//		J      exit
+	//	result:
+	//		Push false|true
+	//	exit:
+	fn gen_expression_short_circuit_test(&mut self, token : &Token, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_expression_short_circuit_test: {}", token);}
+
+		let (check, jump) = if token.token_type == TokenType::LAND_TEST {(Opcode::Land, Opcode::Jf)} else {(Opcode::Lor, Opcode::Jt)};
+
+		self.add_machine_instruction(MachineInstruction::new(
+			check
+			, OpcodeMode::NONE
+			, self.symbol_table.current_frame()
+			, 0
+			, 0
+			, Vec::new()
+			, 0
+			, token.clone()),function_num
+		);
+
+		self.short_circuit.push(self.get_current_address(function_num));
+		self.add_machine_instruction(MachineInstruction::new(
+			jump
+			, OpcodeMode::Jump
+			, self.symbol_table.current_frame()
+			, 0
+			, 0
+			, Vec::new()
+			, 0
+			, Token::new()),function_num
+		);
+	}
+
+	//	The right operand of '&&' or '||' has been generated.  Finish the sequence
+	//	started by gen_expression_short_circuit_test
+	fn gen_expression_short_circuit(&mut self, token : &Token, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_expression_short_circuit: {}", token);}
+
+		let jump_address = match self.short_circuit.pop(){
+			Some(a) => a,
+			None => abend!(format!("from gen_expression_short_circuit:  Houston, we have a problem.  {} without a left operand", token.token_value)),
+		};
+
+		self.add_machine_instruction(MachineInstruction::new(
+			token_type_to_opcode(token.token_type)
+			, OpcodeMode::NONE
+			, self.symbol_table.current_frame()
+			, 0
+			, 0
+			, Vec::new()
+			, 0
+			, token.clone()),function_num
+		);
+
+		//	+0: skip the short circuit result, +1: the short circuit result, +2: exit
+		let result_address = self.get_current_address(function_num) + 1;
+		self.add_machine_instruction(MachineInstruction::new(
+			Opcode::J
+			, OpcodeMode::Jump
+			, self.symbol_table.current_frame()
+			, 0
+			, result_address + 1
+			, Vec::new()
+			, 0
+			, Token::new()),function_num
+		);
+
+		let mut result = Token::new();
+		result.token_type = TokenType::BOOL;
+		result.token_category = TokenCategory::Factor;
+		result.token_value = if token.token_type == TokenType::LAND {"false".to_string()} else {"true".to_string()};
+		result.line_number = token.line_number;
+		self.gen_expression_scalar(&result, function_num);
+
+		let block_num = self.get_current_block_num(function_num);
+		self.frames.frames_list[function_num].code_block_list[block_num].code_block[jump_address].address = result_address;
+	}
+
+	//	cond ? a : b
+	//
+	//		<cond>
+	//		Jf     second		(TERNARY_TEST)
+	//		<a>
+	//		J      exit			(TERNARY_ELSE)
+	//	second:
+	//		<b>
+	//	exit:					(COLON)
+	fn gen_expression_ternary(&mut self, token : &Token, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_expression_ternary: {}", token);}
+
+		let block_num = self.get_current_block_num(function_num);
+
+		//	patch the jump that skips what was just generated
+		if token.token_type != TokenType::TERNARY_TEST{
+			let jump_address = match self.ternary.pop(){
+				Some(a) => a,
+				None => abend!(format!("from gen_expression_ternary:  Houston, we have a problem.  {} without a '?'", token.token_type)),
+			};
+			let target = if token.token_type == TokenType::TERNARY_ELSE {self.get_current_address(function_num) + 1} else {self.get_current_address(function_num)};
+			self.frames.frames_list[function_num].code_block_list[block_num].code_block[jump_address].address = target;
+		}
+
+		if token.token_type == TokenType::COLON{
+			return;
+		}
+
+		self.ternary.push(self.get_current_address(function_num));
+		self.add_machine_instruction(MachineInstruction::new(
+			if token.token_type == TokenType::TERNARY_TEST {Opcode::Jf} else {Opcode::J}
+			, OpcodeMode::Jump
+			, self.symbol_table.current_frame()
+			, 0
+			, 0
+			, Vec::new()
+			, 0
+			, Token::new()),function_num
+		);
+	}
+
 	fn gen_expression(&mut self, expression_list : &Vec<Token>, function_num : usize){
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen.gen_expression {}",token_list_text(expression_list));}
 
@@ -1255,6 +1770,17 @@ impl<'a> CodeGen<'a>{
 					, t.clone()),function_num
 				);
 				continue;
+			}else if t.token_type == TokenType::LAND_TEST || t.token_type == TokenType::LOR_TEST{
+				self.gen_expression_short_circuit_test(t, function_num);
+				continue;
+			}else if t.token_type == TokenType::LAND || t.token_type == TokenType::LOR{
+				self.gen_expression_short_circuit(t, function_num);
+				continue;
+			}else if t.token_type == TokenType::TERNARY_TEST || t.token_type == TokenType::TERNARY_ELSE || t.token_type == TokenType::COLON{
+				self.gen_expression_ternary(t, function_num);
+				continue;
+			}else if t.token_type == TokenType::QUESTION{
+				abend!(format!("Found '?' without a ':' at {}", t.location()));
 			}
 
 			match t.token_category{
@@ -1298,6 +1824,33 @@ impl<'a> CodeGen<'a>{
 		}
 	}
 
+	//	The label applies to the loop generated next.  A loop can't have the same label
+	//	as a loop it's in
+	pub fn gen_loop_label(&mut self, label : &Token, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("gen_loop_label {}", label.token_value);}
+
+		if self.find_loop_label(&label.token_value, function_num) != None{
+			abend!(format!("Error at {}: the loop label '{}' is already used by an enclosing loop", label.location(), label.token_value));
+		}
+		self.loop_label = Some(label.clone());
+	}
+
+	//	The break/continue depth of the enclosing loop with the label:  the number of loops
+	//	between it and the current block
+	fn find_loop_label(&self, label : &str, function_num : usize) -> Option<usize>{
+		let frame = &self.frames.frames_list[function_num];
+		let mut depth = 0;
+		for block_num in frame.current_code_block_num.iter().rev(){
+			if self.loop_labels.get(block_num).map(|l| l.as_str()) == Some(label){
+				return Some(depth);
+			}
+			if frame.code_block_list[*block_num].breakable{
+				depth += 1;
+			}
+		}
+		None
+	}
+
 	pub fn gen_loop(&mut self, function_num : usize){
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("gen_loop");}
 
@@ -1358,6 +1911,26 @@ impl<'a> CodeGen<'a>{
 	pub fn gen_simple(&mut self, token : &Token, expression_list : &Vec<Token>, function_num : usize){
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen.gen_simple: {} {}",token.token_value, token_list_text(expression_list));}
 		
+		//	break <label>; and continue <label>; become break <depth>; and continue <depth>;
+		let depth_list : Vec<Token>;
+		let mut expression_list = expression_list;
+		if (token.token_type == TokenType::BREAK || token.token_type == TokenType::CONTINUE)
+				&& expression_list.len() == 1 && expression_list[0].token_type == TokenType::ID{
+			let label = &expression_list[0];
+			match self.find_loop_label(&label.token_value, function_num){
+				Some(depth) => {
+					let mut depth_token = label.clone();
+					depth_token.token_type = TokenType::INTEGER;
+					depth_token.token_value = depth.to_string();
+					depth_list = vec![depth_token];
+					expression_list = &depth_list;
+				},
+				//	a variable holding the depth
+				None if self.symbol_table.get_symbol_entry(&label.token_value).is_some() => {},
+				None => abend!(format!("Error at {}: {} {}: there is no enclosing loop with the label '{}'", token.location(), token.token_value, label.token_value, label.token_value)),
+			}
+		}
+
 		//	If the expression list is empty then the statement better be either BREAK or CONTINUE
 		if expression_list.is_empty() 
 			&& token.token_type != TokenType::BREAK
@@ -1378,7 +1951,7 @@ impl<'a> CodeGen<'a>{
 			loop{
 				if block < 0{
 					self.error_abend(
-						&format!("FROM gen_simple. {} Not allowed in this context: PCL Line {}", token.token_type, token.line_number)
+						&format!("FROM gen_simple. {} Not allowed in this context: at {}", token.token_type, token.location())
 					);		
 				}
 				if self.frames.frames_list[function_num].code_block_list[block as usize].breakable{
@@ -1485,6 +2058,10 @@ impl<'a> CodeGen<'a>{
 			TokenType::ASG_APPEND_EQ  	=> Opcode::AppendEq,
 			TokenType::ASG_OR_EQ 		=> Opcode::OrEq,
 			TokenType::ASG_AND_EQ  		=> Opcode::AndEq,
+			TokenType::ASG_XOR_EQ  		=> Opcode::XorEq,
+			TokenType::ASG_POWER_EQ  	=> Opcode::PowEq,
+			TokenType::ASG_SHIFT_LEFT_EQ	=> Opcode::ShlEq,
+			TokenType::ASG_SHIFT_RIGHT_EQ	=> Opcode::ShrEq,
 			TokenType::ASG_EQ 			=> Opcode::Update,		// always means update scalar
 
 			_=> abend!(format!("From op_to_opcde:  {} is an unknown assignment operator", op.token_type)),
@@ -1558,9 +2135,9 @@ impl<'a> CodeGen<'a>{
 		);
 	}			
 
-	pub fn gen_struct_instantiate(&mut self, instantiated_struct : &Token, struct_name : &Token, function_num : usize){
+	pub fn gen_struct_instantiate(&mut self, instantiated_struct : &Token, struct_name : &Token, has_arguments : bool, function_num : usize){
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){
-			eprintln!("CodeGen:GEN_STRUCT_INSTANTIATE target={} struct={}", instantiated_struct.token_value, struct_name.token_value);
+			eprintln!("CodeGen:GEN_STRUCT_INSTANTIATE target={} struct={} has_arguments={}", instantiated_struct.token_value, struct_name.token_value, has_arguments);
 		}
 
 		// eprintln!("======= CodeGen:gen_struct_instantiate target={} struct={}", instantiated_struct.token_value, struct_name.token_value);
@@ -1746,6 +2323,7 @@ impl<'a> CodeGen<'a>{
 			//eprintln!("======= Returning to: {} {:?}", member_stack_entry.current_struct.name, member_indices);
 		}
 
+		self.add_struct_methods_to_symbol_table(&instantiated_struct_symbol, struct_index);
 
 		//	Turn the allocated variable into the array; update from
 		//	the operand stack
@@ -1762,9 +2340,37 @@ impl<'a> CodeGen<'a>{
 			),function_num
 		);
 
+		self.gen_struct_init(instantiated_struct, struct_name, struct_index, has_arguments, function_num);
+
 		//self.symbol_table.symbol_table_dump_diag("======= symbols at end of struct_instantiate");
 	}
 
+	//	If the struct has an init method, new foo calls it without any arguments.  The
+	//	call made by new foo(...) is a method call statement of its own (see Parser::struct_instantiate)
+	//	so all we do is make sure there's something for it to call
+	fn gen_struct_init(&mut self, instantiated_struct : &Token, struct_name : &Token, struct_index : usize, has_arguments : bool, function_num : usize){
+		let has_init = self.struct_list.get(struct_index).unwrap().methods.iter().any(|(method, _)| method == "init");
+		if has_arguments{
+			if !has_init{
+				abend!(format!("Error from gen_struct_instantiate at {}: struct {} doesn't have an init method so new {}(...) can't have arguments", instantiated_struct.location(), struct_name.token_value, struct_name.token_value));
+			}
+			return;
+		}
+		if !has_init{
+			return;
+		}
+
+		let mut init_call = instantiated_struct.clone();
+		init_call.token_type = TokenType::QUALIFIED_ID;
+		init_call.token_value = format!("{}:init", instantiated_struct.token_value);
+
+		let mut instance_address = instantiated_struct.clone();
+		instance_address.token_type = TokenType::IDADDR;
+		instance_address.token_category = TokenCategory::Factor;
+
+		self.gen_function_call_statement(&init_call, &vec!(instance_address), function_num, 1);
+	}
+
 	fn _gen_inc_dec_collection(&mut self, _target : &Token, _op : &Token, _target_index_expression : &Vec<Token>, _expression_list : &Vec<Token>, _function_num : usize){
 		panic!("Conversion of collection[index] += 1 to inc not implemented yet")
 	}
@@ -1792,6 +2398,23 @@ impl<'a> CodeGen<'a>{
 		if target.token_type == TokenType::QUALIFIED_ID{
 			return false;
 		}else if target_index_expression.len() == 0{
+			if let Some(SymbolTableEntryType::GlobalEntry(global_detail)) = self.symbol_table.get_symbol_entry(&target.token_value){
+				let opcode = if op.token_type == TokenType::ASG_ADD_EQ {Opcode::Inc} else {Opcode::Dec};
+				self.add_machine_instruction(
+					MachineInstruction::new(
+						opcode
+						, OpcodeMode::GlobalVar
+						, self.symbol_table.current_frame()
+						, 0
+						, global_detail.index
+						, Vec::new()
+						, 0
+						, target.clone()
+					),function_num
+				);
+				return true;
+			}
+
 			(block_num, index) = self.get_address_scalar(target);
 		}else{
 			return false;
@@ -1851,7 +2474,7 @@ impl<'a> CodeGen<'a>{
 					SymbolTableEntryType::StructEntry(m) => {
 						detail = NormalSymbolEntry::new(m.block_num, m.index, m.interner);
 					}
-					_ => abend!(format!("From gen_assignment_to_scalar: Symbol {} Exists but is not the correct type", target.token_value)),
+					_ => abend!(format!("From gen_assignment_to_scalar: at {}, Symbol {} Exists but is not the correct type", target.location(), target.token_value)),
 				}
 			},
 		}
@@ -1911,6 +2534,38 @@ impl<'a> CodeGen<'a>{
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_assignment_to_collection target={} detail={}", target.token_value, detail);}
 	}
 
+	//	Assign to a global or, if there's an index expression, to an element of the
+	//	collection it holds
+	fn gen_assignment_to_global(&mut self, target : &Token, op : &Token, global_detail : &GlobalEntry, target_index_expression : &Vec<Token>, target_index_count : usize, expression_list : &Vec<Token>, function_num : usize){
+		self.gen_expression(expression_list, function_num);
+
+		let mode : OpcodeMode;
+		let qualifier : Vec<usize>;
+		if target_index_expression.len() == 0{
+			mode = OpcodeMode::UpdateGlobal;
+			qualifier = Vec::new();
+		}else{
+			self.gen_expression(target_index_expression, function_num);
+			mode = OpcodeMode::UpdateGlobalIndexed;
+			qualifier = vec!(target_index_count);
+		}
+
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				self.op_to_opcode(op)
+				, mode
+				, self.symbol_table.current_frame()
+				, 0
+				, global_detail.index
+				, qualifier
+				, global_detail.interner
+				, target.clone()
+			),function_num
+		);
+
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("    gen_assignment_to_global target={} detail={}", target.token_value, global_detail);}
+	}
+
 	//	Compute an expression and then assign this value to a member of a struct.  
 	
 	//	TODO:  This code is remarkably similar to the gen_assignment_to_collection that we should be able
@@ -1980,8 +2635,14 @@ impl<'a> CodeGen<'a>{
 	// pub fn gen_assignment_to_literal (&mut self, target : &Token, op : &Token, expression_list : &Vec<Token>, function_num : Option<usize>){
 	// }
 
-	pub fn gen_assignment(&mut self, target : &Token, op : &Token, target_index_expression : &Vec<Token>, target_index_count : usize, expression_list : &Vec<Token>, function_num : usize){		
-		//	Here we test for x+=1 or x-=1 which we convert to x++ or x-- respectively
+	pub fn gen_assignment(&mut self, target : &Token, targets : &Vec<Token>, op : &Token, target_index_expression : &Vec<Token>, target_index_count : usize, expression_list : &Vec<Token>, function_num : usize){		
+		//	x, y = <expression> unpacks the array and then assigns each of the elements
+		if targets.len() > 0{
+			self.gen_destructuring_assignment(targets, op, expression_list, function_num);
+			return;
+		}
+
+		//	Here we test for x+=1 or x-=1 which we convert to x++ or x-- respectively
 		if op.token_type == TokenType::ASG_ADD_EQ || op.token_type == TokenType::ASG_SUB_EQ{
 			if expression_list.len() == 1 {
 				if expression_list[0].token_type == TokenType::INTEGER && expression_list[0].token_value == "1"{
@@ -1999,6 +2660,12 @@ impl<'a> CodeGen<'a>{
 			return;
 		}
 
+		//	A global is updated in the global frame whether or not it's indexed
+		if let Some(SymbolTableEntryType::GlobalEntry(global_detail)) = self.symbol_table.get_symbol_entry(&target.token_value){
+			self.gen_assignment_to_global(target, op, &global_detail, target_index_expression, target_index_count, expression_list, function_num);
+			return;
+		}
+
 		//	target is not a struct member.  Is it a indexed expression (i.e. target is an array
 		//	or dictionary element)?
 		if target_index_expression.len() == 0{
@@ -2008,14 +2675,68 @@ impl<'a> CodeGen<'a>{
 		}
 	}
 
+	//	The array is unpacked onto the operand stack with its first element on top and then
+	//	each target is assigned as if its expression were empty (i.e. the value is already
+	//	on the stack)
+	fn gen_destructuring_assignment(&mut self, targets : &Vec<Token>, op : &Token, expression_list : &Vec<Token>, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::GEN_DESTRUCTURING_ASSIGNMENT: targets={}", token_list_text(targets));}
+
+		//	New variables have to be allocated before the values are pushed or they
+		//	would be allocated on top of them
+		for target in targets{
+			if target.token_type == TokenType::ID && self.symbol_table.get_symbol_entry(&target.token_value).is_none(){
+				let detail = self.symbol_table.add_normal_symbol(&target.token_value);
+				self.gen_alloc(target, detail.block_num, detail.index, detail.interner, function_num);
+			}
+		}
+
+		self.gen_expression(expression_list, function_num);
+
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Unpack
+				, OpcodeMode::NONE
+				, self.symbol_table.current_frame()
+				, 0
+				, 0
+				, vec!(targets.len())
+				, 0
+				, targets[0].clone()
+			),function_num
+		);
+
+		for target in targets{
+			self.gen_assignment(target, &Vec::new(), op, &Vec::new(), 0, &Vec::new(), function_num);
+		}
+	}
+
 	pub fn gen_function_call_statement(&mut self, function_name : &Token, expression_list : &Vec<Token>, function_num : usize, argument_count : usize){
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::GEN_FUNCTION_CALL_STATEMENT:  target={}",function_name.token_value);}
 
+		let function_name = &self.resolve_method_call(function_name);
+
 		//	find the function name in the frame map
 		if self.frames.frame_names.contains_key(&function_name.token_value){
 			if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen:gen_function_call_statement: {} block {}",function_name.token_value, self.frames.frame_names.get(&function_name.token_value).unwrap().0);}
+		}else if let Some(SymbolTableEntryType::NormalSymbolEntry(detail)) = self.symbol_table.get_symbol_entry(&function_name.token_value){
+			//	a variable holding a function value
+			self.gen_expression(expression_list, function_num);
+			self.gen_indirect_function_call(function_name, &detail, argument_count, 1, function_num);
+			self.add_machine_instruction(
+				MachineInstruction::new(
+					Opcode::Pop
+					, OpcodeMode::NONE
+					, self.symbol_table.current_frame()
+					, 0
+					, 0
+					, Vec::new()
+					, 0
+					, Token::new()
+				),function_num
+			);
+			return;
 		}else{
-			abend!(format!("From gen_function_call_statement: function name {} not found", function_name.token_value));
+			abend!(format!("From gen_function_call_statement: at {}, function name {} not found", function_name.location(), function_name.token_value));
 		}
 
 		//	Generate the arguments expression, which, when executed will
@@ -2027,11 +2748,8 @@ impl<'a> CodeGen<'a>{
 		//	Generate the actual call
 
 		let target_frame_info = self.get_function_frame_info(&function_name.token_value);
-		let target_parameter_count = self.get_function_parameter_count(target_frame_info.0);
 
-		if argument_count != target_parameter_count{
-			abend!(format!("From gen_function_call_statement: Argument count ({}) does't match parameter count ({}) for function {}", argument_count, target_parameter_count, function_name.token_value));
-		}
+		let (pushed_count, argument_order) = self.gen_argument_binding(function_name, target_frame_info.0, argument_count, function_num);
 
 		let opcode_mode : OpcodeMode;
 		if target_frame_info.1 {
@@ -2046,7 +2764,7 @@ impl<'a> CodeGen<'a>{
 				, self.symbol_table.current_frame()
 				, target_frame_info.0			// frame number of called function
 				, 0
-				, vec!(target_parameter_count, 1) // number of parameters declared and is_statement=true
+				, [vec!(pushed_count, 1), argument_order].concat() // number of values pushed, is_statement=true and argument order
 				, 0
 				, function_name.clone()			// function name
 			),function_num
@@ -2190,8 +2908,11 @@ impl<'a> CodeGen<'a>{
 		//	Now all of the machine instructions will go here
 		self.make_block_current(eval_block_num, function_num);
 	}
-	pub fn gen_when(&mut self, when_expression : &Vec<Token>, function_num : usize){
-		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::GEN_WHEN: {}", token_list_text(&when_expression));}
+	pub fn gen_when(&mut self, patterns : &Vec<Vec<Token>>, guard : &Vec<Token>, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){
+			let pattern_text : Vec<String> = patterns.iter().map(|p| token_list_text(p)).collect();
+			eprintln!("CodeGen::GEN_WHEN: {} guard: {}", pattern_text.join(" , "), token_list_text(guard));
+		}
 		if self.cli.is_debug_bit(INSERT_DIAG_COMMENTS){
 			self.add_machine_instruction(
 				MachineInstruction::new(
@@ -2202,7 +2923,7 @@ impl<'a> CodeGen<'a>{
 					, 0
 					, Vec::new()
 					, 0
-					, Token::make_string(&format!("=== gen_when === {}",when_expression[0].line_text))
+					, Token::make_string(&format!("=== gen_when === {}",patterns[0][0].line_text))
 				),function_num
 			);
 		}
@@ -2217,66 +2938,66 @@ impl<'a> CodeGen<'a>{
 		//	change this to current_block + 1 -- let's wait and see...
 		let when_block_num = self.add_code_block(false, function_num);
 
-		//	generate the when expression, it's at TOS-1
-		self.gen_expression(when_expression, function_num);
-
-		//	This is the random name created when eval was generated
-		let target_var = Token::new2(
-			TokenType::ID
-			, self.eval_data[eval_data_index].target_var_name.clone()
-			, 0
-			, "ha ha".to_string()
-			, TokenCategory::Factor
-		);
-
+		//	Each pattern leaves a boolean on the operand stack.  The first one that's
+		//	true jumps to the guard (or the branch and link if there's no guard).  If
+		//	the last one is false then none of them matched so jump around the branch and link.
+		//	We don't know either address yet so save the jumps to patch them later
+		let mut matched_jumps = Vec::new();
+		let mut no_match_jumps = Vec::new();
+		for (i, pattern) in patterns.iter().enumerate(){
+			self.gen_when_pattern(pattern, eval_data_index, function_num);
+
+			let is_last = i == patterns.len() - 1;
+			if is_last{
+				no_match_jumps.push(self.get_current_address(function_num));
+			}else{
+				matched_jumps.push(self.get_current_address(function_num));
+			}
+			self.add_machine_instruction(
+				MachineInstruction::new(
+					if is_last {Opcode::Jf} else {Opcode::Jt}
+					, OpcodeMode::Jump
+					, self.symbol_table.current_frame()
+					, 0
+					, 0
+					, Vec::new()
+					, 0
+					, Token::new()
+				),function_num
+			);
+		}
 
-		//  Get the Eval target (it's at TOS)
-		self.add_machine_instruction(
-			MachineInstruction::new(
-				Opcode::Push
-				, OpcodeMode::Var
-				, self.symbol_table.current_frame()
-				, self.eval_data[eval_data_index].target_detail.block_num
-				, self.eval_data[eval_data_index].target_detail.index
-				, Vec::new()
-				, self.eval_data[eval_data_index].target_detail.interner
-				, target_var.clone()
-			),function_num
-		);
+		for jump in matched_jumps{
+			let target = self.get_current_address(function_num);
+			self.patch_jump_address(jump, target, function_num);
+		}
 
-		//	The top two operands are the expression and the target.  Compare
-		//	them for equality. Leave the boolean result on the operand stack
-		self.add_machine_instruction(
-			MachineInstruction::new(
-				Opcode::Eq
-				, OpcodeMode::Var
-				, self.symbol_table.current_frame()
-				, 0
-				, 0
-				, Vec::new()
-				, 0
-				, Token::new()
-			),function_num
-		);	
+		//	when <patterns> if <guard>:  a pattern matched but the guard has to be true too
+		if guard.len() > 0{
+			self.gen_expression(guard, function_num);
+			no_match_jumps.push(self.get_current_address(function_num));
+			self.add_machine_instruction(
+				MachineInstruction::new(
+					Opcode::Jf
+					, OpcodeMode::Jump
+					, self.symbol_table.current_frame()
+					, 0
+					, 0
+					, Vec::new()
+					, 0
+					, Token::new()
+				),function_num
+			);
+		}
 
 		//	get the current instruction address
 		let current_code_address = self.get_current_address(function_num);
 
-		//	if the two operands were not equal. jump around
-		//	the branch and link
-		self.add_machine_instruction(
-			MachineInstruction::new(
-				Opcode::Jf
-				, OpcodeMode::Jump
-				, self.symbol_table.current_frame()
-				, 0
-				, current_code_address + 3   
-				, Vec::new()
-				, 0
-				, Token::new()
-			),function_num
-		);
-		
+		//	if there was no match, jump around the branch and link
+		for jump in no_match_jumps{
+			self.patch_jump_address(jump, current_code_address + 2, function_num);
+		}
+
 		//	branch and link to the eval block
 		//	current address + 1:  call the if block return to the next instruction
 		self.add_machine_instruction(
@@ -2285,7 +3006,7 @@ impl<'a> CodeGen<'a>{
 				, OpcodeMode::Bl
 				, self.symbol_table.current_frame()
 				, current_block_num
-				, current_code_address + 2
+				, current_code_address + 1
 				, vec!(0, when_block_num, 0, 0)	// don't break out of when
 				, 0
 				, Token::new()
@@ -2327,7 +3048,7 @@ impl<'a> CodeGen<'a>{
 						, OpcodeMode::Bl
 						, self.symbol_table.current_frame()
 						, current_block_num
-						, current_code_address + 2
+						, current_code_address + 1
 						, vec!(0,self.eval_data[eval_data_index].otherwise_block_num,0,0) // dont' break out of when
 						, 0
 						, Token::new()
@@ -2344,6 +3065,112 @@ impl<'a> CodeGen<'a>{
 		self.make_block_current(when_block_num, function_num);
 	}
 
+	//	Generate one when pattern.  It leaves true on the operand stack if the eval
+	//	target matches:
+	//
+	//		_				always
+	//		/<regex>/		the regex matches the target
+	//		<low>..<high>	low <= target <= high
+	//		<expression>	the expression equals the target
+	fn gen_when_pattern(&mut self, pattern : &Vec<Token>, eval_data_index : usize, function_num : usize){
+		if pattern.len() == 1 && pattern[0].token_type == TokenType::WILDCARD{
+			let mut true_token = pattern[0].clone();
+			true_token.token_type = TokenType::BOOL;
+			true_token.token_value = "true".to_string();
+			self.gen_expression_scalar(&true_token, function_num);
+		}else if pattern.len() == 1 && pattern[0].token_type == TokenType::REGEX{
+			self.gen_when_target(eval_data_index, function_num);
+			let mut regex = pattern[0].clone();
+			regex.token_type = TokenType::STRING;
+			self.gen_expression_scalar(&regex, function_num);
+			self.add_machine_instruction(
+				MachineInstruction::new(
+					Opcode::RegexMatch
+					, OpcodeMode::NONE
+					, self.symbol_table.current_frame()
+					, 0
+					, 0
+					, Vec::new()
+					, 0
+					, pattern[0].clone()
+				),function_num
+			);
+		}else if let Some(range_ix) = pattern.iter().position(|t| t.token_type == TokenType::RANGE){
+			//	target >= low && target <= high
+			let range = &pattern[range_ix];
+			let mut land = range.clone();
+			land.token_type = TokenType::LAND_TEST;
+			land.token_value = "&&".to_string();
+
+			self.gen_when_target(eval_data_index, function_num);
+			self.gen_expression(&pattern[..range_ix].to_vec(), function_num);
+			self.gen_when_compare(Opcode::Ge, function_num);
+			self.gen_expression_short_circuit_test(&land, function_num);
+
+			self.gen_when_target(eval_data_index, function_num);
+			self.gen_expression(&pattern[range_ix + 1..].to_vec(), function_num);
+			self.gen_when_compare(Opcode::Le, function_num);
+			land.token_type = TokenType::LAND;
+			self.gen_expression_short_circuit(&land, function_num);
+		}else{
+			//	generate the when expression, it's at TOS-1
+			self.gen_expression(pattern, function_num);
+
+			//  Get the Eval target (it's at TOS)
+			self.gen_when_target(eval_data_index, function_num);
+
+			//	The top two operands are the expression and the target.  Compare
+			//	them for equality. Leave the boolean result on the operand stack
+			self.gen_when_compare(Opcode::Eq, function_num);
+		}
+	}
+
+	//	Push the eval target
+	fn gen_when_target(&mut self, eval_data_index : usize, function_num : usize){
+		//	This is the random name created when eval was generated
+		let target_var = Token::new2(
+			TokenType::ID
+			, self.eval_data[eval_data_index].target_var_name.clone()
+			, 0
+			, "ha ha".to_string()
+			, TokenCategory::Factor
+		);
+
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Push
+				, OpcodeMode::Var
+				, self.symbol_table.current_frame()
+				, self.eval_data[eval_data_index].target_detail.block_num
+				, self.eval_data[eval_data_index].target_detail.index
+				, Vec::new()
+				, self.eval_data[eval_data_index].target_detail.interner
+				, target_var
+			),function_num
+		);
+	}
+
+	fn gen_when_compare(&mut self, opcode : Opcode, function_num : usize){
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				opcode
+				, OpcodeMode::Var
+				, self.symbol_table.current_frame()
+				, 0
+				, 0
+				, Vec::new()
+				, 0
+				, Token::new()
+			),function_num
+		);
+	}
+
+	//	Point an already generated jump at its target
+	fn patch_jump_address(&mut self, jump_address : usize, target : usize, function_num : usize){
+		let block_num = self.get_current_block_num(function_num);
+		self.frames.frames_list[function_num].code_block_list[block_num].code_block[jump_address].address = target;
+	}
+
 
 	pub fn gen_otherwise(&mut self, function_num : usize){
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::GEN_OTHERWISE");}
@@ -2424,9 +3251,9 @@ impl<'a> CodeGen<'a>{
 		// //	Now all of the machine instructions will go here
 	}
 
-	pub fn gen_if(&mut self, condition : &Vec<Token>, has_else:bool, else_block_num : usize, function_num : usize){
+	pub fn gen_if(&mut self, condition : &Vec<Token>, elseif_list : &Vec<(Vec<Token>, usize)>, has_else:bool, else_block_num : usize, function_num : usize){
 		if self.cli.is_debug_bit(TRACE_CODE_GEN){
-			eprintln!("CodeGen::GEN_IF has_else={} else_block: {} cond: {}", has_else, else_block_num, token_list_text(&condition));
+			eprintln!("CodeGen::GEN_IF elseif count={} has_else={} else_block: {} cond: {}", elseif_list.len(), has_else, else_block_num, token_list_text(&condition));
 		}
 
 		let current_block_num = self.get_current_block_num(function_num);
@@ -2434,112 +3261,92 @@ impl<'a> CodeGen<'a>{
 		//	add a block to the block list.  save its number.  We probably can
 		//	change this to current_block + 1 -- let's wait and see...
 		let if_block_num = self.add_code_block(false, function_num);
-		
-		//	generate the condition evaluation code
-		self.gen_expression(condition, function_num);
-
-		//	get an address that we'll be using as a return address (with some modifications)
-		//  as well as the basis for jumping around
-		let current_code_address = self.get_current_address(function_num);
 
 		/*
-				   <condition eval>
-			n:     jf around if		target: n+3
-			n+1:   call if			return to n+2
-			if has_else
-				n+2    j around else    target: n+4
-			else
-				n+2    j to continue	target: n+3
-			
-			if has_else
-				n+3 call else		return to n+4
-				n+4 continue
-			else
-				n+3 continue
+			Each arm (the if and each elseif) is tested in turn and the first
+			one that's true is called.  Every arm returns to the same place:
+
+					   <condition eval>
+				n:     jf to next arm		target: n+2
+				n+1:   call if				return to exit
+					   <elseif condition eval>
+				m:     jf to next arm		target: m+2
+				m+1:   call elseif			return to exit
+						:
+				if has_else
+					x:	call else			return to exit
+				exit:	continue
+
+			The exit address isn't known until all of the arms have been
+			generated so the return address of each call is updated at the end
 		*/
 
-		let jf_offset : usize;
-		if has_else{
-			jf_offset = 3;
-		}else{
-			jf_offset = 2;
+		let mut arms : Vec<(&Vec<Token>, usize)> = vec!((condition, if_block_num));
+		for elseif in elseif_list{
+			arms.push((&elseif.0, elseif.1));
 		}
 
-		//	current address:   jump around IF block
-		self.add_machine_instruction(
-			MachineInstruction::new(
-				Opcode::Jf
-				, OpcodeMode::Jump
-				, self.symbol_table.current_frame()
-				, 0
-				, current_code_address + jf_offset
-				, Vec::new()
-				, 0
-				, Token::new()
-			),function_num
-		);
+		//	the addresses of the calls whose return address is the exit
+		let mut call_addresses : Vec<usize> = Vec::new();
 
-		//	current address + 1:  call the if block return to the next instruction
-		self.add_machine_instruction(
-			MachineInstruction::new(
-				Opcode::Bl
-				, OpcodeMode::Bl
-				, self.symbol_table.current_frame()
-				, current_block_num
-				, current_code_address + 2
-				, vec!(0,if_block_num, 0, 0)
-				, 0
-				, Token::new()
-			),function_num
-		);
+		for (arm_condition, arm_block_num) in arms{
+			//	generate the condition evaluation code
+			self.gen_expression(arm_condition, function_num);
 
-		
-		//	When control returns to here, and there is an ELSE then jump around it
-		if has_else{
-			//  current addresss + 2
+			let current_code_address = self.get_current_address(function_num);
+
+			//	current address:   jump around the call to this arm
 			self.add_machine_instruction(
 				MachineInstruction::new(
-					Opcode::J
+					Opcode::Jf
 					, OpcodeMode::Jump
 					, self.symbol_table.current_frame()
 					, 0
-					, current_code_address + 4					// this will be modified at block end
+					, current_code_address + 2
 					, Vec::new()
 					, 0
 					, Token::new()
 				),function_num
-			);	
-		}else{
-			//  current address + 2 jump back into normal instruction stream
+			);
+
+			//	current address + 1:  call the arm, return to the exit
 			self.add_machine_instruction(
 				MachineInstruction::new(
-					Opcode::J
-					, OpcodeMode::Jump
+					Opcode::Bl
+					, OpcodeMode::Bl
 					, self.symbol_table.current_frame()
-					, 0
-					, current_code_address + 3					// this will be modified at block end
-					, Vec::new()
+					, current_block_num
+					, 0										// this will be modified below
+					, vec!(0,arm_block_num, 0, 0)
 					, 0
 					, Token::new()
 				),function_num
-			);	
+			);
+			call_addresses.push(current_code_address + 1);
 		}
 
-
 		if has_else{
+			call_addresses.push(self.get_current_address(function_num));
 			self.add_machine_instruction(
 				MachineInstruction::new(
 					Opcode::Bl
 					, OpcodeMode::Bl
 					, self.symbol_table.current_frame()
 					, current_block_num
-					, current_code_address + 4
+					, 0										// this will be modified below
 					, vec!(0,else_block_num, 0, 0) // don't break out of if block
 					, 0
 					, Token::new()
 				),function_num
 			);
 		}
+
+		//	Now we know where the exit is
+		let exit_address = self.get_current_address(function_num);
+		for call_address in call_addresses{
+			self.frames.frames_list[function_num].code_block_list[current_block_num].code_block[call_address].address = exit_address;
+		}
+
 		self.make_block_current(if_block_num, function_num);
 	}
 
@@ -2629,7 +3436,319 @@ impl<'a> CodeGen<'a>{
 
 		self.make_block_current(while_block_num, function_num);
 	}
-	
+
+	/*
+		The init has already been generated (it's just an assignment statement).  The
+		step goes in its own (non-breakable) block so that it can be called both at
+		the bottom of the loop and by continue (which returns to n+i+2):
+
+		n:		j around step			target: n+2
+		n+1:	call step				return to n+2
+		n+2
+				<condition eval>
+		n+i		jf to end				target: n+i+3
+		n+i+1:	call for				return to n+i+2
+		n+i+2:	j (step)				target: n+1
+
+		where i = number of instructions generated for eval.  If there
+		isn't a step, this is the same as a while loop
+	*/
+	pub fn gen_for(&mut self, condition : &Vec<Token>, has_step : bool, step_target : &Token, step_op : &Token, step_target_index_expression : &Vec<Token>, step_target_index_count : usize, step_expression : &Vec<Token>, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::GEN_FOR has_step={} cond: {}", has_step, token_list_text(condition));}
+
+		//	This is the block where the return instruction goes to
+		let return_block_num = self.get_current_block_num(function_num);
+
+		//	the address we jump to at the bottom of the loop
+		let mut loop_address = self.get_current_address(function_num);
+
+		if has_step{
+			let step_block_num = self.add_code_block(false, function_num);
+
+			//	n:  jump around the step call into the condition
+			self.add_machine_instruction(
+				MachineInstruction::new(
+					Opcode::J
+					, OpcodeMode::Jump
+					, self.symbol_table.current_frame()
+					, 0
+					, loop_address + 2
+					, Vec::new()
+					, 0
+					, Token::new()
+				),function_num
+			);
+
+			//	n+1:  call the step block and return to the condition
+			self.add_machine_instruction(
+				MachineInstruction::new(
+					Opcode::Bl
+					, OpcodeMode::Bl
+					, self.symbol_table.current_frame()
+					, return_block_num
+					, loop_address + 2
+					, vec!(0,step_block_num, 0, 0)	// don't break out of the step
+					, 0
+					, Token::new()
+				),function_num
+			);
+
+			//	generate the step in its own block and then return to this one
+			self.make_block_current(step_block_num, function_num);
+			self.gen_assignment(step_target, &Vec::new(), step_op, step_target_index_expression, step_target_index_count, step_expression, function_num);
+			self.gen_block_end(function_num);
+
+			loop_address += 1;
+		}
+
+		//	this is the target block for the for block
+		let for_block_num = self.add_code_block(true, function_num);
+
+		//	generate the condition evaluation code
+		self.gen_expression(condition, function_num);
+
+		//  get the address of the after the condition
+		let post_condition_address = self.get_current_address(function_num);
+
+		//	post_condition_address + 0:   if condition false, exit the loop
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Jf
+				, OpcodeMode::Jump
+				, self.symbol_table.current_frame()
+				, 0
+				, post_condition_address + 3
+				, Vec::new()
+				, 0
+				, Token::new()
+			),function_num
+		);
+
+		//	post_condition_address + 1 call the for block, return to the next location
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Bl
+				, OpcodeMode::Bl
+				, self.symbol_table.current_frame()
+				, return_block_num
+				, post_condition_address + 2
+				, vec!(1,for_block_num, return_block_num, post_condition_address + 3)
+				, 0
+				, Token::new()
+			),function_num
+		);
+
+		//	post_condition_address + 2:   jump to the step (or the condition if there isn't one)
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::J
+				, OpcodeMode::Jump
+				, self.symbol_table.current_frame()
+				, 0
+				, loop_address
+				, Vec::new()
+				, 0
+				, Token::new()
+			),function_num
+		);
+
+		self.make_block_current(for_block_num, function_num);
+	}
+
+	/*
+		do {...} until <condition>;
+
+		n:		call do					return to n+1
+		n+1
+				<condition eval>
+		n+i+1	jf (loop)				target: n
+		n+i+2	continue
+
+		The break address in the Bl at n is n+i+2 which we don't know until
+		the until is generated (see gen_until).  Note that continue returns
+		to n+1 so that the condition is evaluated.
+	*/
+	pub fn gen_do(&mut self, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::GEN_DO");}
+
+		//	This is the block where the return instruction goes to
+		let return_block_num = self.get_current_block_num(function_num);
+
+		//	this is the target block for the do block
+		let do_block_num = self.add_code_block(true, function_num);
+
+		let do_address = self.get_current_address(function_num);
+
+		//	n:  call the do block, return to the condition.  The break address will be
+		//	modified by gen_until
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Bl
+				, OpcodeMode::Bl
+				, self.symbol_table.current_frame()
+				, return_block_num
+				, do_address + 1
+				, vec!(1,do_block_num, return_block_num, 0)
+				, 0
+				, Token::new()
+			),function_num
+		);
+
+		self.do_data.push((return_block_num, do_address));
+
+		self.make_block_current(do_block_num, function_num);
+	}
+
+	//	The do block has ended and we're back in the block that called it.  Generate
+	//	the condition and jump back to the do if it's false.
+	pub fn gen_until(&mut self, condition : &Vec<Token>, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::GEN_UNTIL cond: {}", token_list_text(condition));}
+
+		let (do_block_num, do_address) = match self.do_data.pop(){
+			Some(d) => d,
+			None => abend!(format!("from gen_until:  Houston, we have a problem.  Until without a do")),
+		};
+
+		//	generate the condition evaluation code
+		self.gen_expression(condition, function_num);
+
+		let post_condition_address = self.get_current_address(function_num);
+
+		//	post_condition_address + 0:  if the condition is false go around again
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Jf
+				, OpcodeMode::Jump
+				, self.symbol_table.current_frame()
+				, 0
+				, do_address
+				, Vec::new()
+				, 0
+				, Token::new()
+			),function_num
+		);
+
+		//	and now we know where break goes
+		self.frames.frames_list[function_num].code_block_list[do_block_num].code_block[do_address].qualifier[3] = post_condition_address + 1;
+	}
+
+	//	try {...} catch <id> {...} generates:
+	//
+	//		n:		Try		register the catch block, which returns to n+3
+	//		n+1:	Bl		the try block, returning to n+2
+	//		n+2:	EndTry	the try block finished without a throw so drop the catch block
+	//
+	//	The catch block is entered only by a throw.  The catch block number isn't known
+	//	until gen_catch creates it
+	pub fn gen_try(&mut self, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::GEN_TRY");}
+
+		//	This is the block where the try and catch blocks return to
+		let return_block_num = self.get_current_block_num(function_num);
+
+		//	this is the target block for the try block
+		let try_block_num = self.add_code_block(false, function_num);
+
+		let try_address = self.get_current_address(function_num);
+
+		//	n:  the catch block is patched by gen_catch
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Try
+				, OpcodeMode::NONE
+				, self.symbol_table.current_frame()
+				, return_block_num
+				, try_address + 3
+				, vec!(0)
+				, 0
+				, Token::new()
+			),function_num
+		);
+
+		//	n+1:  call the try block
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Bl
+				, OpcodeMode::Bl
+				, self.symbol_table.current_frame()
+				, return_block_num
+				, try_address + 2
+				, vec!(0,try_block_num, 0, 0)
+				, 0
+				, Token::new()
+			),function_num
+		);
+
+		self.try_data.push((return_block_num, try_address));
+
+		self.make_block_current(try_block_num, function_num);
+	}
+
+	//	The try block has ended and we're back in the block that called it.  Generate
+	//	the EndTry and then the catch block.  The error value is the first variable
+	//	of the catch block
+	pub fn gen_catch(&mut self, variable : &Token, function_num : usize){
+		if self.cli.is_debug_bit(TRACE_CODE_GEN){eprintln!("CodeGen::GEN_CATCH variable: {}", variable.token_value);}
+
+		let (return_block_num, try_address) = match self.try_data.pop(){
+			Some(d) => d,
+			None => abend!(format!("from gen_catch:  Houston, we have a problem.  Catch without a try")),
+		};
+
+		//	n+2:
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::EndTry
+				, OpcodeMode::NONE
+				, self.symbol_table.current_frame()
+				, 0
+				, 0
+				, Vec::new()
+				, 0
+				, Token::new()
+			),function_num
+		);
+
+		let catch_block_num = self.add_code_block(false, function_num);
+		self.frames.frames_list[function_num].code_block_list[return_block_num].code_block[try_address].qualifier[0] = catch_block_num;
+
+		self.make_block_current(catch_block_num, function_num);
+
+		if variable.token_value.is_empty(){
+			return;
+		}
+
+		//	The error variable is always local to the catch block
+		let detail = self.symbol_table.add_normal_symbol(&variable.token_value);
+		self.gen_alloc(variable, detail.block_num, detail.index, detail.interner, function_num);
+
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Catch
+				, OpcodeMode::NONE
+				, self.symbol_table.current_frame()
+				, 0
+				, 0
+				, Vec::new()
+				, 0
+				, variable.clone()
+			),function_num
+		);
+
+		self.add_machine_instruction(
+			MachineInstruction::new(
+				Opcode::Update
+				, OpcodeMode::Update
+				, self.symbol_table.current_frame()
+				, detail.block_num
+				, detail.index
+				, Vec::new()
+				, detail.interner
+				, variable.clone()
+			),function_num
+		);
+	}
+
 	//	We've added a foreach pseudo machine instruction which encodes all of the relative infomration
 	//	required for the foreach header to work
 	pub fn gen_foreach (&mut self, target : &Token, source_type : &TokenType, source : &Vec<Token>, function_num : usize){
@@ -2656,9 +3775,17 @@ impl<'a> CodeGen<'a>{
 		//
 		//	NOTE:  we are effectively ignoring "source_type" for now.  That is, we don't support
 		//	foreach on literal arrays or struct members.
-		let source_detail = self.symbol_table.get_normal_address(&source[0].token_value);
-		foreach_data.foreach_source_block_num = source_detail.block_num;
-		foreach_data.foreach_source_address = source_detail.index;
+		//	A global collection is in the global frame.  The Foreach's mode tells exec where to look
+		let mut source_mode = OpcodeMode::NONE;
+		if let Some(SymbolTableEntryType::GlobalEntry(global_detail)) = self.symbol_table.get_symbol_entry(&source[0].token_value){
+			source_mode = OpcodeMode::GlobalVar;
+			foreach_data.foreach_source_block_num = 0;
+			foreach_data.foreach_source_address = global_detail.index;
+		}else{
+			let source_detail = self.symbol_table.get_normal_address(&source[0].token_value);
+			foreach_data.foreach_source_block_num = source_detail.block_num;
+			foreach_data.foreach_source_address = source_detail.index;
+		}
 
 		// foreach_data.foreach_source_detail = Some(SymbolTableEntryType::NormalSymbolEntry(source_detail));
 
@@ -2674,8 +3801,10 @@ impl<'a> CodeGen<'a>{
 			},
 			//	the symbol exists so just get its detail	
 			Some (entry) 	=> {
-				if let SymbolTableEntryType::NormalSymbolEntry(d) = entry{
-					foreach_data.foreach_target_detail = d;
+				match entry{
+					SymbolTableEntryType::NormalSymbolEntry(d) => foreach_data.foreach_target_detail = d,
+					SymbolTableEntryType::GlobalEntry(_) => abend!(format!("foreach at {}: the target {} is a global.  Use a local variable", target.location(), target.token_value)),
+					_ => {},
 				}
 			}
 		}
@@ -2753,7 +3882,7 @@ impl<'a> CodeGen<'a>{
 		self.add_machine_instruction(
 			MachineInstruction::new(
 				Opcode::Foreach
-				, OpcodeMode::NONE
+				, source_mode
 				, self.symbol_table.current_frame()
 				, foreach_data.foreach_target_detail.block_num
 				, foreach_data.foreach_target_detail.index
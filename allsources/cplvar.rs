@@ -27,6 +27,7 @@ pub enum RustDataType{
 	Uninitialized,
 	Int,
 	Real,
+	Decimal,
 	String,
 	Bool,
 	Char,
@@ -41,6 +42,7 @@ impl fmt::Display for RustDataType{
 			RustDataType::Uninitialized => write!(f, "Uninitialized"),
 			RustDataType::Int => write!(f, "int"),
 			RustDataType::Real => write!(f, "real"),
+			RustDataType::Decimal => write!(f, "decimal"),
 			RustDataType::String => write!(f, "string"),
 			RustDataType::Bool => write!(f, "bool"),
 			RustDataType::Char => write!(f, "char"),
@@ -64,13 +66,15 @@ pub enum CplDataType{
 	CplFileWriter(CplFileWriter),
 	CplUninitialized(CplUninitialized),
 	CplUndefined(CplUndefined),
+	CplFunction(CplFunction),				// a function value (named function, builtin or closure)
+	CplDateTime(CplDateTime),
 }
 impl fmt::Display for CplDataType{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     	match self {
 			CplDataType::CplUninitialized(_) => write!(f,"CplUninitialized"),
 			CplDataType::CplUndefined(_) => write!(f, "CplUndefined"),
-			CplDataType::CplNumber(s) => write!(f,"CplNumber({})", s.cpl_number),
+			CplDataType::CplNumber(s) => write!(f,"CplNumber({})", s),
 			CplDataType::CplBool(b) => write!(f,"CplBool: {}", b.cpl_bool),
 			CplDataType::CplVarRef(r) => write!(f,"CplVarRef: {},{},{}",r.frame_num, r.block_num, r.address),
 			CplDataType::CplArray(a) => write!(f,"CplArray: {}",a.array_text()),
@@ -79,6 +83,8 @@ impl fmt::Display for CplDataType{
 			CplDataType::CplFileReader(s) => write!(f,"CplFileReader: \"{}\"", s.file_name),
 			CplDataType::CplFileWriter(s) => write!(f,"CplFileWriter: \"{}\"", s.file_name),
 			CplDataType::CplStruct(_) => write!(f,"CplStruct"),
+			CplDataType::CplFunction(func) => write!(f,"CplFunction: {} ({})", func.name, func.frame_num),
+			CplDataType::CplDateTime(d) => write!(f,"CplDateTime: {}", d),
 	   }
 	}
 }
@@ -88,7 +94,7 @@ impl Clone for CplDataType{
     	match self {
 			CplDataType::CplUninitialized(_) => CplDataType::CplUninitialized(CplUninitialized::new()),
 			CplDataType::CplUndefined(_) => CplDataType::CplUndefined(CplUndefined::new()),
-			CplDataType::CplNumber(v) => CplDataType::CplNumber(CplNumber::new(v.rust_data_type, v.cpl_number)),
+			CplDataType::CplNumber(v) => CplDataType::CplNumber(v.copy()),
 			CplDataType::CplBool(b) => CplDataType::CplBool(CplBool::new(b.cpl_bool)),
 			CplDataType::CplVarRef(r) => CplDataType::CplVarRef(CplVarRef::new(r.frame_num, r.block_num, r.address)),
 			CplDataType::CplArray(a) => CplDataType::CplArray(a.clone()),
@@ -97,6 +103,8 @@ impl Clone for CplDataType{
 			CplDataType::CplFileReader(_) => abend!(format!("Unable to clone CplFileReader")),
 			CplDataType::CplFileWriter(_) => abend!(format!("Unable to clone CplFileWriter")),
 			CplDataType::CplStruct(a) => CplDataType::CplStruct(a.clone()),
+			CplDataType::CplFunction(func) => CplDataType::CplFunction(func.clone()),
+			CplDataType::CplDateTime(d) => CplDataType::CplDateTime(*d),
 	   }
 	}
 }
@@ -156,6 +164,10 @@ impl fmt::Display for CplDataTypeInspected{
 //	The operand stack is created once by main() and a reference to it is passed
 //	to all subsequent executors.  In other words, the operand stack is global to
 //	an executing program.
+//	The first frame is the global frame.  It has a single block holding the
+//	program's global variables and is never popped.
+pub const GLOBAL_FRAME : usize = 0;
+
 pub struct OperandStack{
 	pub operand_frames : Vec<OperandFrame>,
 	cli_warnings : bool,
@@ -163,9 +175,12 @@ pub struct OperandStack{
 }
 impl OperandStack{
 	pub fn new(cli_warnings : bool, namelist : Vec<String>) -> OperandStack{
+		//  create the global frame when the operand stack is instantiated
+		let mut global_frame = OperandFrame::new();
+		global_frame.operand_blocks.push(OperandBlock::new());
+
 		OperandStack{
-			//  create the first frame when the operand stack is instantiated
-			operand_frames : Vec::new(),
+			operand_frames : vec!(global_frame),
 			cli_warnings : cli_warnings,
 			namelist : namelist,
 		}
@@ -215,6 +230,15 @@ impl OperandStack{
 		frame.operand_blocks.pop();
 	}
 
+	//	remove everything above len in the current block of the current
+	//	frame
+	pub fn truncate_block(&mut self, len : usize){
+		let frame = self.operand_frames.last_mut().unwrap();
+		if let Some(block) = frame.operand_blocks.last_mut(){
+			block.operand_block.truncate(len);
+		}
+	}
+
 	//	get the number of variables in the current block of the
 	//	current frame
 	pub fn variable_count (&self) -> usize{
@@ -276,6 +300,42 @@ impl OperandStack{
 		}
 	}
 
+	//	fetch a global variable.  Like fetch_local_var, a collection is returned
+	//	as a VarRef pointing at it
+	pub fn fetch_global_var(&self, address : usize) -> CplVar{
+		let block = &self.operand_frames[GLOBAL_FRAME].operand_blocks[0];
+		if address >= block.operand_block.len(){
+			abend!(format!("from CplVar.fetch_global_var: global {} has not been allocated", address));
+		}
+
+		match block.operand_block[address].var{
+			CplDataType::CplArray(_) |
+			CplDataType::CplDict(_) => CplVar::new(CplDataType::CplVarRef(CplVarRef::new(GLOBAL_FRAME, 0, address))),
+			_ => block.operand_block[address].clone(),
+		}
+	}
+
+	//	Create an uninitialized global variable unless it already exists
+	pub fn global_alloc(&mut self, address : usize, interner : usize){
+		let block = &mut self.operand_frames[GLOBAL_FRAME].operand_blocks[0];
+		if address < block.operand_block.len(){
+			return;
+		}
+
+		block.operand_block.push(CplVar::inew(CplDataType::CplUninitialized(CplUninitialized::new()), interner));
+		if address != block.operand_block.len() - 1{
+			panic!("from CplVar.global_alloc:  address of the new global is not what was requested {} != {}", address, block.operand_block.len() - 1);
+		}
+	}
+
+	//	Update a global variable with the value at the top of the stack.  A VarRef is
+	//	dereferenced first:  it may point into a function's frame which goes away
+	//	when the function returns.
+	pub fn update_global_from_tos(&mut self, address : usize){
+		let tos = self.dereference_tos();
+		self.update_from_val(&tos, GLOBAL_FRAME, 0, address);
+	}
+
 	pub fn get_type(&self, data : &CplVar) -> CplDataTypeInspected{
 		match data.var{
 			CplDataType::CplUninitialized(_) => CplDataTypeInspected::CplUninitialized,
@@ -469,7 +529,7 @@ impl OperandStack{
 			let ix_var = self.pop();
 			match ix_var.var{
 				CplDataType::CplString(s) => indices.push(s.cpl_string),
-				CplDataType::CplNumber(n) => indices.push(n.cpl_number.to_string()),
+				CplDataType::CplNumber(n) => indices.push(n.to_string()),
 				CplDataType::CplBool(n) => indices.push(n.cpl_bool.to_string()),
 				_=> panic!("from fetch_dict_indexed_from_operand_stack: {} is not a valid key",ix_var),
 			}
@@ -626,13 +686,14 @@ impl OperandStack{
 		self.push(&dict);		
 	}
 
-	pub fn apply_binary_operator_scalar_local (&mut self, block_num : usize, address : usize, opcode : Opcode){
+	pub fn apply_binary_operator_scalar_local (&mut self, block_num : usize, address : usize, opcode : Opcode) -> Result<(), String>{
 		let frame_num = self.operand_frames.len() - 1;
-		self.apply_binary_operator_scalar_global(frame_num, block_num, address, opcode);
+		self.apply_binary_operator_scalar_global(frame_num, block_num, address, opcode)
 	}
 
-	//	Perform assignment opperator on a scalar value (e.g. x += 1);
-	pub fn apply_binary_operator_scalar_global (&mut self, frame_num : usize, block_num : usize, address : usize, opcode : Opcode){
+	//	Perform assignment opperator on a scalar value (e.g. x += 1).  An arithmetic error (e.g.
+	//	overflow) is returned for the executor to throw
+	pub fn apply_binary_operator_scalar_global (&mut self, frame_num : usize, block_num : usize, address : usize, opcode : Opcode) -> Result<(), String>{
 		//	get the new value from the top of the stack	
 		let tos = self.dereference_tos();
 
@@ -650,14 +711,18 @@ impl OperandStack{
 		//	a number, the result is still a string but with its "numberic" value changed
 		match var.var{
 			CplDataType::CplNumber (ref mut n) => n.apply_binary_operator_to_number(&tos, opcode),
-			CplDataType::CplString (ref mut s) => s.apply_binary_operator_to_string(&tos, opcode),
+			CplDataType::CplString (ref mut s) => {
+				s.apply_binary_operator_to_string(&tos, opcode);
+				Ok(())
+			},
+			CplDataType::CplDateTime (ref mut d) => d.apply_binary_operator_to_datetime(&tos, opcode),
 			_=> abend!(format!("from CplVar.apply_binary_operator_scalar:  unable to perform {} on type {}", opcode, var.var)),
 		}		
 	}
 
 	//	Perform assignment opperator on an element of a collection (e.g. x[0] += 1);
 	//	NOTE this is for direct access to the collection (i.e. not via a VarRef)
-	pub fn apply_binary_operator_indexed (&mut self, block_num : usize, address : usize, opcode : Opcode){
+	pub fn apply_binary_operator_indexed (&mut self, block_num : usize, address : usize, opcode : Opcode) -> Result<(), String>{
 		//	get the new value from the top of the stack
 		let rvalue = self.dereference_tos();
 
@@ -668,11 +733,11 @@ impl OperandStack{
 		//	case we are updating an element of a collection)
 		match self.operand_frames.last_mut().unwrap().operand_blocks.get_mut(block_num).unwrap().operand_block.get_mut(address).unwrap().var{
 			CplDataType::CplArray (ref mut a) =>{
-				a.update_indexed_op(&index, &rvalue, opcode);
+				a.update_indexed_op(&index, &rvalue, opcode)
 			}
 
 			CplDataType::CplDict (ref mut d) => {
-				d.update_indexed_op(&index, &rvalue, opcode);
+				d.update_indexed_op(&index, &rvalue, opcode)
 			}
 						
 			_=> abend!(format!("from CplVar.apply_binary_operator_indexed:  unable to perform {}", opcode)),
@@ -684,7 +749,7 @@ impl OperandStack{
 	//	updates the value of a local array element.  The element is found by traversing the
 	//	the index_list.  That is, index_list[0] is the element of the array to start at
 	//	the rvalue at tos	
-	fn update_array(&mut self, frame_num : usize, block_num : usize, address: usize, index_list : &Vec<usize>, op : Opcode){
+	fn update_array(&mut self, frame_num : usize, block_num : usize, address: usize, index_list : &Vec<usize>, op : Opcode) -> Result<(), String>{
 		//eprintln!("======= update_array frame{} block={} address={} index list={:?}", frame_num, block_num, address, index_list);
 
 		//	get the rvalue
@@ -714,7 +779,7 @@ impl OperandStack{
 		//eprintln!("======= update_array: last_index={}",last_index);
 
 		if let CplDataType::CplArray(ref mut a) = array.var{
-			a.update_indexed(last_index, &value, op);
+			a.update_indexed(last_index, &value, op)
 		}else{
 			panic!("from OperandStack.update_array: Unexpected data type: {}",array.var);
 		}
@@ -723,7 +788,7 @@ impl OperandStack{
 	//	update the value of a local collection element.  The collection may be either an array
 	//	or a dictionary. The indices are on the stack (above the value).  This is a "dispatcher"
 	//	function depending on the type of collection.
-	fn update_collection_from_stack (&mut self, frame_num : usize, block_num : usize, address : usize, index_count : usize, op : Opcode){
+	fn update_collection_from_stack (&mut self, frame_num : usize, block_num : usize, address : usize, index_count : usize, op : Opcode) -> Result<(), String>{
 		if let CplDataType::CplArray(_) = self.operand_frames.get(frame_num).unwrap().operand_blocks.get(block_num).unwrap().operand_block.get(address).unwrap().var{
 			let mut array_index_list : Vec<usize> = Vec::new();
 			let mut i=0;
@@ -734,8 +799,7 @@ impl OperandStack{
 				i+=1;
 			}
 
-			self.update_array(frame_num, block_num, address, &array_index_list, op);
-			return;
+			return self.update_array(frame_num, block_num, address, &array_index_list, op);
 		}else if let CplDataType::CplDict(_) = self.operand_frames.get(frame_num).unwrap().operand_blocks.get(block_num).unwrap().operand_block.get(address).unwrap().var{
 			let mut i=0;
 			let mut dict_index_list : Vec<CplVar> = Vec::new();
@@ -747,13 +811,13 @@ impl OperandStack{
 			//	get the rvalue
 			let value = self.dereference_tos();
 	
-			self.update_dictionary(frame_num, block_num, address, &dict_index_list, &value, op);
+			self.update_dictionary(frame_num, block_num, address, &dict_index_list, &value, op)
 		}else{
 			panic!("from update_collection_from_stack:  Expecting either an array or dictionary");
 		}		
 	}
 
-	fn update_dictionary (&mut self, frame_num : usize, block_num : usize, address : usize, indices : &Vec<CplVar>, value : &CplVar, op : Opcode){
+	fn update_dictionary (&mut self, frame_num : usize, block_num : usize, address : usize, indices : &Vec<CplVar>, value : &CplVar, op : Opcode) -> Result<(), String>{
 		// eprintln!("======= update_dictionary frame={} block={} address={} index={} value={} op={}", frame_num, block_num, address, indices[0].var, value, op);
 
 		//	get the current frame
@@ -763,9 +827,10 @@ impl OperandStack{
 		if let CplDataType::CplDict(ref mut d) = frame.operand_blocks.get_mut(block_num).unwrap().operand_block.get_mut(address).unwrap().var{
 			match op{
 				Opcode::Update => d.update_indexed(&indices[0], &value),
-				_=> d.update_indexed_op(&indices[0], &value, op),
+				_=> return d.update_indexed_op(&indices[0], &value, op),
 			}
 		}
+		Ok(())
 	}
 
 	//	If the mode is UpdateIndexed, the indicies are on the stack and the number of
@@ -774,16 +839,18 @@ impl OperandStack{
 	//	If the mode is UpdateStructElement, the indices are in the qualfifier.  Structs
 	//	are always arrays so the indicies are always numbers.
 	//
-	pub fn update_local_collection(&mut self, block_num : usize, address: usize, qualifier : &Vec<usize>, op : Opcode, mode : OpcodeMode){
+	pub fn update_local_collection(&mut self, block_num : usize, address: usize, qualifier : &Vec<usize>, op : Opcode, mode : OpcodeMode) -> Result<(), String>{
 		let frame_num = self.operand_frames.len() - 1;
-		self.update_global_collection(frame_num, block_num, address, qualifier, op, mode);
+		self.update_global_collection(frame_num, block_num, address, qualifier, op, mode)
 	}
 
-	pub fn update_global_collection(&mut self, frame_num : usize, block_num : usize, address: usize, qualifier : &Vec<usize>, op : Opcode, mode : OpcodeMode){
+	pub fn update_global_collection(&mut self, frame_num : usize, block_num : usize, address: usize, qualifier : &Vec<usize>, op : Opcode, mode : OpcodeMode) -> Result<(), String>{
 		if mode == OpcodeMode::UpdateStructElement{
-			self.update_array(frame_num, block_num, address, qualifier, op);
+			self.update_array(frame_num, block_num, address, qualifier, op)
 		}else if mode == OpcodeMode::UpdateIndexed{
-			self.update_collection_from_stack(frame_num, block_num, address, qualifier[0], op);
+			self.update_collection_from_stack(frame_num, block_num, address, qualifier[0], op)
+		}else{
+			Ok(())
 		}
 	}
 
@@ -792,6 +859,7 @@ impl OperandStack{
 		let rtn : &str;
 
 		match var.var{
+			CplDataType::CplNumber(ref n) if n.is_decimal() => rtn = "CplDecimal",
 			CplDataType::CplNumber(_) 				=> rtn = "CplNumber",
 			CplDataType::CplString(_)				=> rtn = "CplString",
 			CplDataType::CplBool(_)					=> rtn = "CplBool",
@@ -803,6 +871,8 @@ impl OperandStack{
 			CplDataType::CplFileWriter(_) 			=> rtn = "CplFileHandle",
 			CplDataType::CplDict(_) 				=> rtn = "CplDict",
 			CplDataType::CplStruct(_)				=> rtn = "CplStruct",
+			CplDataType::CplFunction(_)				=> rtn = "CplFunction",
+			CplDataType::CplDateTime(_)				=> rtn = "CplDateTime",
 		}
 
 		return rtn.to_string();
@@ -811,7 +881,7 @@ impl OperandStack{
 	//	Does a unary operatorion on the local variable specified by the address
 	//	But if the variable is a VarRef, the actual location can be anywhere
 	//	in the operand stack.
-	pub fn perform_unary_op(&mut self, _address: usize, opcode : Opcode){
+	pub fn perform_unary_op(&mut self, _address: usize, opcode : Opcode) -> Result<(), String>{
 		
 		//	get the frame and block
 		// let frame = self.operand_frames.last_mut().unwrap();
@@ -820,12 +890,13 @@ impl OperandStack{
 
 		let mut var = self.pop();
 		match var.var{
-			CplDataType::CplNumber (ref mut n) => n.apply_unary_operator(opcode),
+			CplDataType::CplNumber (ref mut n) => n.apply_unary_operator(opcode)?,
 			CplDataType::CplBool(ref mut b) => b.apply_daminit(),
 			_=> abend!(format!("from CplVar.perform_unary_op:  unable to perform {} on type {}", opcode, var.var)),
 		}
 
 		self.push(&var);
+		Ok(())
 	}
 
 	//	Requested the length of a variable pointed to by a VarRef
@@ -1094,6 +1165,8 @@ impl CplVar{
 			CplDataType::CplFileReader(_) => if let CplDataType::CplFileReader(_) = self.var {return true}else{return false},
 			CplDataType::CplFileWriter(_) => if let CplDataType::CplFileWriter(_) = self.var {return true}else{return false},
 			CplDataType::CplStruct(_) => if let CplDataType::CplStruct(_) = self.var {return true}else{return false},
+			CplDataType::CplFunction(_) => if let CplDataType::CplFunction(_) = self.var {return true}else{return false},
+			CplDataType::CplDateTime(_) => if let CplDataType::CplDateTime(_) = self.var {return true}else{return false},
 		}
 	}
 
@@ -1105,7 +1178,8 @@ impl CplVar{
 			CplDataType::CplArray(_) |
 			CplDataType::CplDict(_) |
 			CplDataType::CplFileReader(_) |
-			CplDataType::CplFileWriter(_) => {
+			CplDataType::CplFileWriter(_) |
+			CplDataType::CplFunction(_) => {
 				if warn{
 					eprintln!("Warning: unable compare {} with {}", self.var, v.var);
 				}
@@ -1135,9 +1209,10 @@ impl CplVar{
 		//	If the types are equal, just go ahead and compare their values
 		if self.is_type_equal(v){
 			match &v.var{
-				CplDataType::CplNumber(n1) => if let CplDataType::CplNumber(ref n2) = self.var {return n1.cpl_number == n2.cpl_number}else{return false},
+				CplDataType::CplNumber(n1) => if let CplDataType::CplNumber(ref n2) = self.var {return n1 == n2}else{return false},
 				CplDataType::CplBool(b1) => if let CplDataType::CplBool(ref b2) = self.var {return b1.cpl_bool == b2.cpl_bool}else{return false},
 				CplDataType::CplString(s1) => if let CplDataType::CplString(ref s2) = self.var {return s1.cpl_string == s2.cpl_string}else{return false},
+				CplDataType::CplDateTime(d1) => if let CplDataType::CplDateTime(ref d2) = self.var {return d1 == d2}else{return false},
 				_ => if warn {
 					eprintln!("Warning from CplVar.is_equal:  I don't know what this is {}", v.var);
 				},
@@ -1155,9 +1230,10 @@ impl CplVar{
 		//	If the types are equal, just go ahead and compare their values
 		if self.is_type_equal(v){
 			match &v.var{
-				CplDataType::CplNumber(n1) => if let CplDataType::CplNumber(ref n2) = self.var {return n1.cpl_number != n2.cpl_number}else{return false},
+				CplDataType::CplNumber(n1) => if let CplDataType::CplNumber(ref n2) = self.var {return n1 != n2}else{return false},
 				CplDataType::CplBool(b1) => if let CplDataType::CplBool(ref b2) = self.var {return b1.cpl_bool != b2.cpl_bool}else{return false},
 				CplDataType::CplString(s1) => if let CplDataType::CplString(ref s2) = self.var {return s1.cpl_string != s2.cpl_string}else{return false},
+				CplDataType::CplDateTime(d1) => if let CplDataType::CplDateTime(ref d2) = self.var {return d1 != d2}else{return false},
 				_ => if warn {
 					eprintln!("Warning from CplVar.is_equal:  I don't know what this is {}", v.var);
 				},
@@ -1170,8 +1246,9 @@ impl CplVar{
 	pub fn as_string(&self) -> String{
 		match &self.var{
 			CplDataType::CplString(s) => return s.cpl_string.clone(),
-			CplDataType::CplNumber(n) => return n.cpl_number.to_string(),
+			CplDataType::CplNumber(n) => return n.to_string(),
 			CplDataType::CplBool(b) => return b.cpl_bool.to_string(),
+			CplDataType::CplDateTime(d) => return d.to_string(),
 			_ => panic!("From CplVar.as_string: Var isn't a string, number or boolean it's a {}", self.var),
 		}
 	}
@@ -1206,7 +1283,7 @@ impl CplVar{
 
 	pub fn print(&self){
 		match &self.var{
-			CplDataType::CplNumber(n) 				=> eprintln!("{}",n.cpl_number),
+			CplDataType::CplNumber(n) 				=> eprintln!("{}",n),
 			CplDataType::CplString(s)				=> eprintln!("{}",s.cpl_string),
 			CplDataType::CplBool(b)					=> eprintln!("{}",b.cpl_bool),
 			CplDataType::CplVarRef(v)				=> eprintln!("VarRef: {},{},{}", v.frame_num, v.block_num, v.address),
@@ -1217,6 +1294,8 @@ impl CplVar{
 			CplDataType::CplFileReader(_) 			=> eprintln!("File Reader"),
 			CplDataType::CplFileWriter(_) 			=> eprintln!("File Writer"),
 			CplDataType::CplStruct(a)				=> a.print(),
+			CplDataType::CplFunction(func)			=> eprintln!("fn {}", func.name),
+			CplDataType::CplDateTime(d)				=> eprintln!("{}", d),
 		}
 	}
 
@@ -1238,7 +1317,7 @@ impl CplVar{
 impl fmt::Display for CplVar{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match &self.var{
-			CplDataType::CplNumber(n) 			=> write!(f,"{}",n.cpl_number),
+			CplDataType::CplNumber(n) 			=> write!(f,"{}",n),
 			CplDataType::CplString(s)			=> write!(f,"{}",s.cpl_string),
 			CplDataType::CplBool(b)				=> write!(f,"{}",b.cpl_bool),
 			CplDataType::CplVarRef(v)			=> write!(f,"VarRef: {},{},{}", v.frame_num, v.block_num, v.address),
@@ -1249,6 +1328,8 @@ impl fmt::Display for CplVar{
 			CplDataType::CplFileReader(_) 		=> write!(f,"File Reader"),
 			CplDataType::CplFileWriter(_) 		=> write!(f,"File Writer"),
 			CplDataType::CplStruct(_)			=> write!(f,"Struct"),
+			CplDataType::CplFunction(func)		=> write!(f,"fn {}", func.name),
+			CplDataType::CplDateTime(d)			=> write!(f,"{}", d),
 		}	
 	}
 }
@@ -1325,6 +1406,386 @@ impl CplUndefined{
 	}
 }
 
+//	A function value.  The frame number is the called function's frame in the FrameMap.
+//	The captured values are the enclosing variables an anonymous function refers to,
+//	copied when the function literal was evaluated.  They're passed to the function
+//	after the declared arguments
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub struct CplFunction{
+	pub frame_num : usize,
+	pub name : String,
+	pub captures : Vec<CplVar>,
+}
+impl CplFunction{
+	pub fn new(frame_num : usize, name : &str, captures : Vec<CplVar>) -> CplFunction{
+		CplFunction{
+			frame_num : frame_num,
+			name : name.to_string(),
+			captures : captures,
+		}
+	}
+}
+
+impl Clone for CplFunction{
+	fn clone(&self) -> CplFunction{
+		CplFunction::new(self.frame_num, &self.name, self.captures.clone())
+	}
+}
+
+//	A moment in time:  seconds since 1970-01-01T00:00:00Z.  offset is the fixed offset from
+//	UTC (in seconds) used to get the date's components and to show it.  There's no time zone
+//	database so an offset doesn't change with daylight saving time
+#[derive(Copy, Clone)]
+pub struct CplDateTime{
+	pub seconds : i64,
+	pub offset : i32,
+}
+
+const MONTH_NAMES : [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"];
+const WEEKDAY_NAMES : [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+const SECONDS_PER_DAY : i64 = 86400;
+
+//	Dates are kept to the years 0000 to 9999 (UTC)
+const MIN_DATE_SECONDS : i64 = -62167219200;
+const MAX_DATE_SECONDS : i64 = 253402300799;
+
+//	The number of days from 1970-01-01 to year-month-day (proleptic Gregorian calendar)
+fn days_from_civil(year : i64, month : u32, day : u32) -> i64{
+	let y = if month <= 2 {year - 1} else {year};
+	let era = y.div_euclid(400);
+	let year_of_era = y - era * 400;
+	let day_of_year = (153 * ((month + 9) % 12) as i64 + 2) / 5 + day as i64 - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146097 + day_of_era - 719468
+}
+
+//	The year, month and day that is days after 1970-01-01
+fn civil_from_days(days : i64) -> (i64, u32, u32){
+	let z = days + 719468;
+	let era = z.div_euclid(146097);
+	let day_of_era = z - era * 146097;
+	let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let mp = (5 * day_of_year + 2) / 153;
+	let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+	let month = if mp < 10 {mp + 3} else {mp - 9} as u32;
+	(year_of_era + era * 400 + if month <= 2 {1} else {0}, month, day)
+}
+
+fn is_leap_year(year : i64) -> bool{
+	(year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year : i64, month : u32) -> u32{
+	match month{
+		2 => if is_leap_year(year) {29} else {28},
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	}
+}
+
+//	The components of a date while it's parsed
+struct ParsedDate{
+	year : i64,
+	month : u32,
+	day : u32,
+	hour : u32,
+	minute : u32,
+	second : u32,
+	pm : Option<bool>,
+	offset : i32,
+}
+
+impl CplDateTime{
+	pub fn new(seconds : i64, offset : i32) -> CplDateTime{
+		CplDateTime{
+			seconds : seconds,
+			offset : offset,
+		}
+	}
+
+	pub fn now() -> CplDateTime{
+		let since_epoch = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+		CplDateTime::new(since_epoch, 0)
+	}
+
+	//	None if a component is out of range
+	pub fn from_components(year : i64, month : u32, day : u32, hour : u32, minute : u32, second : u32, offset : i32) -> Option<CplDateTime>{
+		if year < 0 || year > 9999 || month < 1 || month > 12 || day < 1 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 59{
+			return None;
+		}
+		let local = days_from_civil(year, month, day) * SECONDS_PER_DAY + (hour * 3600 + minute * 60 + second) as i64;
+		CplDateTime::new(0, offset).add_seconds(local - offset as i64)
+	}
+
+	//	(year, month, day, hour, minute, second) at the date's offset
+	pub fn components(&self) -> (i64, u32, u32, u32, u32, u32){
+		let local = self.seconds + self.offset as i64;
+		let (year, month, day) = civil_from_days(local.div_euclid(SECONDS_PER_DAY));
+		let time = local.rem_euclid(SECONDS_PER_DAY) as u32;
+		(year, month, day, time / 3600, time / 60 % 60, time % 60)
+	}
+
+	//	0 is Sunday
+	pub fn weekday(&self) -> u32{
+		((self.seconds + self.offset as i64).div_euclid(SECONDS_PER_DAY) + 4).rem_euclid(7) as u32
+	}
+
+	pub fn day_of_year(&self) -> u32{
+		let (year, month, day, _, _, _) = self.components();
+		(days_from_civil(year, month, day) - days_from_civil(year, 1, 1) + 1) as u32
+	}
+
+	//	None if the new date is outside of the years 0000 to 9999
+	pub fn add_seconds(&self, seconds : i64) -> Option<CplDateTime>{
+		let new_seconds = self.seconds.checked_add(seconds)?;
+		if new_seconds < MIN_DATE_SECONDS || new_seconds > MAX_DATE_SECONDS{
+			return None;
+		}
+		Some(CplDateTime::new(new_seconds, self.offset))
+	}
+
+	pub fn add_days(&self, days : i64) -> Option<CplDateTime>{
+		self.add_seconds(days.checked_mul(SECONDS_PER_DAY)?)
+	}
+
+	//	The day is kept unless the new month is shorter (January 31 + 1 month is February 28 or 29)
+	pub fn add_months(&self, months : i64) -> Option<CplDateTime>{
+		let (year, month, day, hour, minute, second) = self.components();
+		let month_number = year.checked_mul(12)?.checked_add(month as i64 - 1)?.checked_add(months)?;
+		let (new_year, new_month) = (month_number.div_euclid(12), month_number.rem_euclid(12) as u32 + 1);
+		let new_day = day.min(days_in_month(new_year, new_month));
+		CplDateTime::from_components(new_year, new_month, new_day, hour, minute, second, self.offset)
+	}
+
+	//	d += seconds or d -= seconds
+	pub fn apply_binary_operator_to_datetime(&mut self, rvalue : &CplVar, op : Opcode) -> Result<(), String>{
+		let seconds = match rvalue.var{
+			CplDataType::CplNumber(ref n) if op == Opcode::AddEq || op == Opcode::SubEq => n.as_int(),
+			_ => return Err(format!("invalid date operation: {} {} {}", self, operator_symbol(op), rvalue)),
+		};
+		let rslt = match op{
+			Opcode::AddEq => self.add_seconds(seconds),
+			_ => seconds.checked_neg().and_then(|seconds| self.add_seconds(seconds)),
+		};
+		match rslt{
+			Some(d) => *self = d,
+			None => return Err(format!("date overflow: {} {} {}", self, operator_symbol(op), seconds)),
+		}
+		Ok(())
+	}
+
+	//	+hh:mm, +hhmm, +hh or Z
+	pub fn parse_offset(text : &str) -> Option<i32>{
+		if text == "Z" || text == "z"{
+			return Some(0);
+		}
+		let sign = match text.chars().next(){
+			Some('+') => 1,
+			Some('-') => -1,
+			_ => return None,
+		};
+		let digits : String = text[1..].chars().filter(|c| *c != ':').collect();
+		if !digits.chars().all(|c| c.is_ascii_digit()) || (digits.len() != 2 && digits.len() != 4) || text[1..].len() > 5{
+			return None;
+		}
+		let hours = digits[..2].parse::<i32>().ok()?;
+		let minutes = if digits.len() == 4 {digits[2..].parse::<i32>().ok()?} else {0};
+		if hours > 23 || minutes > 59{
+			return None;
+		}
+		Some(sign * (hours * 3600 + minutes * 60))
+	}
+
+	fn offset_text(&self, colon : bool) -> String{
+		let sign = if self.offset < 0 {'-'} else {'+'};
+		let minutes = self.offset.abs() / 60;
+		if colon{
+			format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+		}else{
+			format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60)
+		}
+	}
+
+	//	Parse text with a format like "%Y-%m-%d %H:%M:%S".  The format's fields are:
+	//
+	//		%Y year, %y two digit year, %m month, %d or %e day, %H hour, %I hour (1 to 12) with %p (AM or PM),
+	//		%M minute, %S second, %b or %B month name, %a or %A weekday name (skipped), %z offset (+hh:mm or Z),
+	//		%% a '%'.  A space matches any amount of white space.
+	//
+	//	Anything that's missing is the start of its range (e.g. "%Y-%m" is the first day of the month at midnight)
+	pub fn parse(text : &str, format : &str) -> Result<CplDateTime, String>{
+		let mut parsed = ParsedDate{year : 1970, month : 1, day : 1, hour : 0, minute : 0, second : 0, pm : None, offset : 0};
+		let text_chars : Vec<char> = text.chars().collect();
+		let mut at = 0;
+		let mut format_chars = format.chars();
+
+		//	up to max_digits digits from the text
+		let number = |at : &mut usize, max_digits : usize, what : &str| -> Result<i64, String>{
+			let start = *at;
+			while *at < text_chars.len() && *at - start < max_digits && text_chars[*at].is_ascii_digit(){
+				*at += 1;
+			}
+			if *at == start{
+				return Err(format!("'{}' doesn't match the format '{}':  expecting the {} at position {}", text, format, what, start + 1));
+			}
+			Ok(text_chars[start..*at].iter().collect::<String>().parse::<i64>().unwrap())
+		};
+
+		//	a word from the text that's one of names or the first three letters of one.  Returns the name's index
+		let name = |at : &mut usize, names : &[&str], what : &str| -> Result<usize, String>{
+			let start = *at;
+			while *at < text_chars.len() && text_chars[*at].is_alphabetic(){
+				*at += 1;
+			}
+			let word = text_chars[start..*at].iter().collect::<String>().to_lowercase();
+			match names.iter().position(|n| n.to_lowercase() == word || (word.len() == 3 && n.to_lowercase().starts_with(&word))){
+				Some(i) => Ok(i),
+				None => Err(format!("'{}' doesn't match the format '{}':  expecting the {} at position {}", text, format, what, start + 1)),
+			}
+		};
+
+		while let Some(f) = format_chars.next(){
+			if f == ' '{
+				while at < text_chars.len() && text_chars[at].is_whitespace(){
+					at += 1;
+				}
+				continue;
+			}
+			if f != '%'{
+				if at >= text_chars.len() || text_chars[at] != f{
+					return Err(format!("'{}' doesn't match the format '{}':  expecting '{}' at position {}", text, format, f, at + 1));
+				}
+				at += 1;
+				continue;
+			}
+
+			match format_chars.next(){
+				Some('Y') => parsed.year = number(&mut at, 4, "year")?,
+				Some('y') => {
+					let year = number(&mut at, 2, "year")?;
+					parsed.year = if year < 69 {2000 + year} else {1900 + year};
+				},
+				Some('m') => parsed.month = number(&mut at, 2, "month")? as u32,
+				Some('d') | Some('e') => {
+					while at < text_chars.len() && text_chars[at] == ' '{
+						at += 1;
+					}
+					parsed.day = number(&mut at, 2, "day")? as u32;
+				},
+				Some('H') | Some('I') => parsed.hour = number(&mut at, 2, "hour")? as u32,
+				Some('M') => parsed.minute = number(&mut at, 2, "minute")? as u32,
+				Some('S') => parsed.second = number(&mut at, 2, "second")? as u32,
+				Some('b') | Some('B') => parsed.month = name(&mut at, &MONTH_NAMES, "month name")? as u32 + 1,
+				Some('a') | Some('A') => {name(&mut at, &WEEKDAY_NAMES, "weekday name")?;},
+				Some('p') => {
+					let word : String = text_chars.iter().skip(at).take(2).collect::<String>().to_uppercase();
+					if word != "AM" && word != "PM"{
+						return Err(format!("'{}' doesn't match the format '{}':  expecting AM or PM at position {}", text, format, at + 1));
+					}
+					parsed.pm = Some(word == "PM");
+					at += 2;
+				},
+				Some('z') => {
+					let start = at;
+					while at < text_chars.len() && (text_chars[at] == '+' || text_chars[at] == '-' || text_chars[at] == ':' || text_chars[at] == 'Z' || text_chars[at].is_ascii_digit()){
+						at += 1;
+					}
+					match CplDateTime::parse_offset(&text_chars[start..at].iter().collect::<String>()){
+						Some(offset) => parsed.offset = offset,
+						None => return Err(format!("'{}' doesn't match the format '{}':  expecting an offset (e.g. +05:00) at position {}", text, format, start + 1)),
+					}
+				},
+				Some('%') => {
+					if at >= text_chars.len() || text_chars[at] != '%'{
+						return Err(format!("'{}' doesn't match the format '{}':  expecting '%' at position {}", text, format, at + 1));
+					}
+					at += 1;
+				},
+				Some(c) => return Err(format!("%{} isn't a date format field", c)),
+				None => return Err(format!("the date format '{}' ends with a '%'", format)),
+			}
+		}
+
+		if at < text_chars.len(){
+			return Err(format!("'{}' doesn't match the format '{}':  there's extra text at position {}", text, format, at + 1));
+		}
+
+		match parsed.pm{
+			Some(_) if parsed.hour < 1 || parsed.hour > 12 => return Err(format!("'{}' has an hour of {} with AM or PM", text, parsed.hour)),
+			Some(pm) => parsed.hour = parsed.hour % 12 + if pm {12} else {0},
+			None => {},
+		}
+
+		match CplDateTime::from_components(parsed.year, parsed.month, parsed.day, parsed.hour, parsed.minute, parsed.second, parsed.offset){
+			Some(date) => Ok(date),
+			None => Err(format!("'{}' isn't a valid date", text)),
+		}
+	}
+
+	//	Format using the fields of parse plus %j (day of the year)
+	pub fn format(&self, format : &str) -> Result<String, String>{
+		let (year, month, day, hour, minute, second) = self.components();
+		let mut rtn = String::new();
+		let mut format_chars = format.chars();
+		while let Some(f) = format_chars.next(){
+			if f != '%'{
+				rtn.push(f);
+				continue;
+			}
+			match format_chars.next(){
+				Some('Y') => rtn.push_str(&format!("{:04}", year)),
+				Some('y') => rtn.push_str(&format!("{:02}", year.rem_euclid(100))),
+				Some('m') => rtn.push_str(&format!("{:02}", month)),
+				Some('d') => rtn.push_str(&format!("{:02}", day)),
+				Some('e') => rtn.push_str(&format!("{:2}", day)),
+				Some('H') => rtn.push_str(&format!("{:02}", hour)),
+				Some('I') => rtn.push_str(&format!("{:02}", if hour % 12 == 0 {12} else {hour % 12})),
+				Some('p') => rtn.push_str(if hour < 12 {"AM"} else {"PM"}),
+				Some('M') => rtn.push_str(&format!("{:02}", minute)),
+				Some('S') => rtn.push_str(&format!("{:02}", second)),
+				Some('b') => rtn.push_str(&MONTH_NAMES[month as usize - 1][..3]),
+				Some('B') => rtn.push_str(MONTH_NAMES[month as usize - 1]),
+				Some('a') => rtn.push_str(&WEEKDAY_NAMES[self.weekday() as usize][..3]),
+				Some('A') => rtn.push_str(WEEKDAY_NAMES[self.weekday() as usize]),
+				Some('j') => rtn.push_str(&format!("{:03}", self.day_of_year())),
+				Some('z') => rtn.push_str(&self.offset_text(true)),
+				Some('%') => rtn.push('%'),
+				Some(c) => return Err(format!("%{} isn't a date format field", c)),
+				None => return Err(format!("the date format '{}' ends with a '%'", format)),
+			}
+		}
+		Ok(rtn)
+	}
+}
+
+//	ISO 8601:  2024-03-05T14:30:00Z or 2024-03-05T14:30:00+05:30
+impl fmt::Display for CplDateTime{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let (year, month, day, hour, minute, second) = self.components();
+		let offset = if self.offset == 0 {"Z".to_string()} else {self.offset_text(true)};
+		write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}", year, month, day, hour, minute, second, offset)
+	}
+}
+
+//	Dates are the same if they're the same moment, whatever their offsets
+impl PartialEq for CplDateTime{
+	fn eq(&self, other : &Self) -> bool{
+		self.seconds == other.seconds
+	}
+}
+impl Eq for CplDateTime{}
+impl PartialOrd for CplDateTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+    }
+}
+impl Ord for CplDateTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+		self.seconds.cmp(&other.seconds)
+    }
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 pub struct CplArray{
 	pub cpl_array : Vec<CplVar>,
@@ -1364,7 +1825,7 @@ impl CplArray{
 	}
 
 	//	update the element at index
-	pub fn update_indexed(&mut self, local_index : usize, rvalue : &CplVar, op : Opcode){
+	pub fn update_indexed(&mut self, local_index : usize, rvalue : &CplVar, op : Opcode) -> Result<(), String>{
 		if local_index >= self.cpl_array.len(){
 			panic!("From CplArray.update_indexed:  index is out of range {} {}",local_index, self.cpl_array.len());
 		}
@@ -1372,7 +1833,7 @@ impl CplArray{
 		//	If it's a simple replacement then just do it
 		if op == Opcode::Update{
 			self.cpl_array[local_index] = rvalue.clone();
-			return;
+			return Ok(());
 		}
 		
 		//	Otherwise it's an assignment op but we have to evaluate the lvalue and rvalue to determine
@@ -1383,16 +1844,13 @@ impl CplArray{
 		if let CplDataType::CplNumber(ref new_v) = rvalue.var{
 			if let CplDataType::CplNumber(ref e) = self.cpl_array[local_index].var{
 				match op{
-					Opcode::AddEq => self.update_indexed_op_number(local_index, e.cpl_number+new_v.cpl_number),
-					Opcode::SubEq => self.update_indexed_op_number(local_index, e.cpl_number-new_v.cpl_number),
-					Opcode::DivEq => self.update_indexed_op_number(local_index, e.cpl_number/new_v.cpl_number),
-					Opcode::MulEq => self.update_indexed_op_number(local_index, e.cpl_number*new_v.cpl_number),
-					Opcode::ModEq => self.update_indexed_op_number(local_index, (e.cpl_number as i64%new_v.cpl_number as i64) as f64),
-					Opcode::OrEq  => self.update_indexed_op_number(local_index, (e.cpl_number as i64|new_v.cpl_number as i64) as f64),
-					Opcode::AndEq => self.update_indexed_op_number(local_index, (e.cpl_number as i64&new_v.cpl_number as i64) as f64),
+					Opcode::AddEq | Opcode::SubEq | Opcode::DivEq | Opcode::MulEq | Opcode::ModEq | Opcode::OrEq | Opcode::AndEq
+						| Opcode::XorEq | Opcode::PowEq | Opcode::ShlEq | Opcode::ShrEq => {
+						let rslt = e.arithmetic(new_v, op);
+						return self.update_indexed_op_number(local_index, rslt);
+					},
 					_=> abend!(format!("from CplArray:update_indexed: Expecting an arithmetic assignment operator (e.g. '+=').  Got {}", op)),
 				}
-				return;
 			}else if let CplDataType::CplString(_) = self.cpl_array[local_index].var{
 				//if the lvalue is a string then only "." or "+" to a string is supported
 				if op != Opcode::AppendEq && op != Opcode::AddEq {
@@ -1402,13 +1860,14 @@ impl CplArray{
 			}else{
 				abend!(format!("from CplArray:update_indexed_op: Op {} only works on numbers. Array element is {}", op, self.cpl_array[local_index].var));
 			}
-			return;
+			return Ok(());
 		}
 		
 		//	if the rvalue is a string, the lvalue must be a string then only append is supported
 		if let CplDataType::CplString(_) = rvalue.var{
 			self.update_indexed_append(local_index, rvalue);
 		}
+		Ok(())
 	}
 
 	//	We'll treat append as kind of special case since it only works with strings
@@ -1418,7 +1877,7 @@ impl CplArray{
 			if let CplDataType::CplString(ref new_s) = rvalue.var{
 				updated_value.push_str(&new_s.cpl_string);
 			}else if let CplDataType::CplNumber(ref new_n) = rvalue.var{
-				updated_value.push_str(&new_n.cpl_number.to_string());
+				updated_value.push_str(&new_n.to_string());
 			}
 			self.cpl_array[index] = CplVar::new(CplDataType::CplString(CplString::new(updated_value)));
 		}else{
@@ -1426,12 +1885,13 @@ impl CplArray{
 		}
 	}
 
-	fn update_indexed_op_number(&mut self, index : usize, raw_value : f64){
-		self.cpl_array[index] = CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Real, raw_value)));
+	fn update_indexed_op_number(&mut self, index : usize, rslt : Result<CplNumber, String>) -> Result<(), String>{
+		self.cpl_array[index] = CplVar::new(CplDataType::CplNumber(rslt?));
+		Ok(())
 	}
 
 	//	Perform an operation on an element
-	pub fn update_indexed_op(&mut self, index : &CplVar, rvalue : &CplVar, op : Opcode){
+	pub fn update_indexed_op(&mut self, index : &CplVar, rvalue : &CplVar, op : Opcode) -> Result<(), String>{
 		let local_index : usize;
 
 		//	otherwise, first make sure the index is in range and is a number
@@ -1448,13 +1908,11 @@ impl CplArray{
 		if let CplDataType::CplNumber(ref new_v) = rvalue.var{
 			if let CplDataType::CplNumber(ref e) = self.cpl_array[local_index].var{
 				match op{
-					Opcode::AddEq => self.update_indexed_op_number(local_index, e.cpl_number+new_v.cpl_number),
-					Opcode::SubEq => self.update_indexed_op_number(local_index, e.cpl_number-new_v.cpl_number),
-					Opcode::DivEq => self.update_indexed_op_number(local_index, e.cpl_number/new_v.cpl_number),
-					Opcode::MulEq => self.update_indexed_op_number(local_index, e.cpl_number*new_v.cpl_number),
-					Opcode::ModEq => self.update_indexed_op_number(local_index, (e.cpl_number as i64%new_v.cpl_number as i64) as f64),
-					Opcode::OrEq  => self.update_indexed_op_number(local_index, (e.cpl_number as i64|new_v.cpl_number as i64) as f64),
-					Opcode::AndEq => self.update_indexed_op_number(local_index, (e.cpl_number as i64&new_v.cpl_number as i64) as f64),
+					Opcode::AddEq | Opcode::SubEq | Opcode::DivEq | Opcode::MulEq | Opcode::ModEq | Opcode::OrEq | Opcode::AndEq
+						| Opcode::XorEq | Opcode::PowEq | Opcode::ShlEq | Opcode::ShrEq => {
+						let rslt = e.arithmetic(new_v, op);
+						return self.update_indexed_op_number(local_index, rslt);
+					},
 					_=> abend!(format!("from CplArray:update_indexed_op: Expecting an arithmetic assignment operator (e.g. '+=').  Got {}", op)),
 				}
 			}else if let CplDataType::CplString(_) = self.cpl_array[local_index].var{
@@ -1473,6 +1931,7 @@ impl CplArray{
 				abend!(format!("from CplArray:update_indexed_op: Op {} only works on strings. Array element is {}", op, self.cpl_array[local_index].var));
 			}
 		}
+		Ok(())
 	}
 
 	pub fn delete(&mut self, key : &CplVar){
@@ -1564,13 +2023,15 @@ impl Clone for CplArray{
 		
 		for var in &self.cpl_array{
 			match &var.var{
-				CplDataType::CplNumber(n) 				=> cpl_array.push(&CplVar::inew(CplDataType::CplNumber(CplNumber::new(n.rust_data_type, n.cpl_number)),var.interner)),
+				CplDataType::CplNumber(n) 				=> cpl_array.push(&CplVar::inew(CplDataType::CplNumber(n.copy()),var.interner)),
 				CplDataType::CplString(n)				=> cpl_array.push(&CplVar::inew(CplDataType::CplString(CplString::new(n.cpl_string.clone())),var.interner)),
 				CplDataType::CplBool(n)					=> cpl_array.push(&CplVar::inew(CplDataType::CplBool(CplBool::new(n.cpl_bool)),var.interner)),
 				CplDataType::CplArray(a)				=> cpl_array.push(&CplVar::inew(CplDataType::CplArray(a.clone()),var.interner)),
 				CplDataType::CplUninitialized(_) 		=> cpl_array.push(&CplVar::inew(CplDataType::CplUninitialized(CplUninitialized::new()),var.interner)),
 				CplDataType::CplUndefined(_) 			=> cpl_array.push(&undefined!()),
 				CplDataType::CplDict(d) 				=> cpl_array.push(&CplVar::inew(CplDataType::CplDict(d.clone()),var.interner)),
+				CplDataType::CplFunction(f) 			=> cpl_array.push(&CplVar::inew(CplDataType::CplFunction(f.clone()),var.interner)),
+				CplDataType::CplDateTime(d) 			=> cpl_array.push(&CplVar::inew(CplDataType::CplDateTime(*d),var.interner)),
 				_ 										=> abend!(format!("Unable to clone an array with a {} object in it", var.var)),
 			};
 		}
@@ -1642,7 +2103,7 @@ impl CplString{
 				self.cpl_string.push_str(&s.cpl_string);
 			}
 			CplDataType::CplNumber(n) => {
-				let s = self.stringify(n.cpl_number);
+				let s = n.to_string();
 				self.cpl_string.push_str(&s);
 			}
 			CplDataType::CplBool(b) => {
@@ -1662,7 +2123,7 @@ impl CplString{
 		let mut newv = String::new();
 		
 		if let CplDataType::CplNumber(n) = &rvalue.var{
-			newv.push_str(&n.cpl_number.to_string());
+			newv.push_str(&n.to_string());
 		} else if let CplDataType::CplString(s) = &rvalue.var{
 			newv.push_str(&s.cpl_string);
 		} else if let CplDataType::CplBool(b) = &rvalue.var{
@@ -1781,9 +2242,257 @@ impl CplBool{
 	}
 }
 
+//	How the quotient of a decimal division is rounded to the places that are kept
+#[derive(PartialEq, Copy, Clone)]
+pub enum DecimalRounding{
+	HalfEven,			// to the nearest, ties to the even digit (banker's rounding)
+	HalfUp,				// to the nearest, ties away from zero
+	Down,				// toward zero
+	Up,					// away from zero
+	Floor,				// toward negative infinity
+	Ceiling,			// toward positive infinity
+}
+
+impl DecimalRounding{
+	pub fn from_name(name : &str) -> Option<DecimalRounding>{
+		match name{
+			"half_even"	=> Some(DecimalRounding::HalfEven),
+			"half_up"	=> Some(DecimalRounding::HalfUp),
+			"down"		=> Some(DecimalRounding::Down),
+			"up"		=> Some(DecimalRounding::Up),
+			"floor"		=> Some(DecimalRounding::Floor),
+			"ceiling"	=> Some(DecimalRounding::Ceiling),
+			_			=> None,
+		}
+	}
+}
+
+//	The most digits a decimal can have after the decimal point
+pub const MAX_DECIMAL_PLACES : u32 = 28;
+
+//	A fixed point decimal number:  units / 10^scale.  12.34 is 1234 with a scale of 2.
+//	Adding, subtracting and multiplying are exact.  Overflow gives None
+#[derive(Copy, Clone)]
+pub struct CplDecimal{
+	pub units : i128,
+	pub scale : u32,
+}
+
+impl CplDecimal{
+	pub fn new(units : i128, scale : u32) -> CplDecimal{
+		CplDecimal{
+			units : units,
+			scale : scale,
+		}
+	}
+
+	pub fn from_int(i : i64) -> CplDecimal{
+		CplDecimal::new(i as i128, 0)
+	}
+
+	//	A real is converted using the shortest text that reads back as the same real,
+	//	so 0.1 is 0.1 and not 0.1000000000000000055511151231257827
+	pub fn from_real(r : f64) -> Option<CplDecimal>{
+		if !r.is_finite(){
+			return None;
+		}
+		CplDecimal::parse(&r.to_string())
+	}
+
+	//	[+-]digits[.digits]
+	pub fn parse(text : &str) -> Option<CplDecimal>{
+		let text = text.trim();
+		let (negative, digits) = match text.as_bytes().first(){
+			Some(b'-') => (true, &text[1..]),
+			Some(b'+') => (false, &text[1..]),
+			_ => (false, text),
+		};
+		let (whole, fraction) = match digits.find('.'){
+			Some(dot) => (&digits[..dot], &digits[dot+1..]),
+			None => (digits, ""),
+		};
+		if whole.is_empty() && fraction.is_empty(){
+			return None;
+		}
+		if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) || fraction.len() as u32 > MAX_DECIMAL_PLACES{
+			return None;
+		}
+
+		let mut units : i128 = 0;
+		for c in whole.chars().chain(fraction.chars()){
+			units = units.checked_mul(10)?.checked_add(c.to_digit(10).unwrap() as i128)?;
+		}
+		Some(CplDecimal::new(if negative {-units} else {units}, fraction.len() as u32))
+	}
+
+	pub fn as_real(&self) -> f64{
+		self.to_string().parse::<f64>().unwrap()
+	}
+
+	//	the units of this value with more places after the decimal point
+	fn units_at(&self, scale : u32) -> Option<i128>{
+		10i128.checked_pow(scale - self.scale)?.checked_mul(self.units)
+	}
+
+	//	both values' units with the same scale
+	fn align(&self, other : &CplDecimal) -> Option<(i128, i128, u32)>{
+		let scale = self.scale.max(other.scale);
+		Some((self.units_at(scale)?, other.units_at(scale)?, scale))
+	}
+
+	pub fn add(&self, other : &CplDecimal) -> Option<CplDecimal>{
+		let (a, b, scale) = self.align(other)?;
+		Some(CplDecimal::new(a.checked_add(b)?, scale))
+	}
+
+	pub fn sub(&self, other : &CplDecimal) -> Option<CplDecimal>{
+		let (a, b, scale) = self.align(other)?;
+		Some(CplDecimal::new(a.checked_sub(b)?, scale))
+	}
+
+	pub fn mul(&self, other : &CplDecimal) -> Option<CplDecimal>{
+		let product = CplDecimal::new(self.units.checked_mul(other.units)?, self.scale + other.scale);
+		if product.scale > MAX_DECIMAL_PLACES{
+			return product.round(MAX_DECIMAL_PLACES, DecimalRounding::HalfEven);
+		}
+		Some(product)
+	}
+
+	//	The remainder has the sign of the dividend (like %).  The divisor can't be zero
+	pub fn rem(&self, other : &CplDecimal) -> Option<CplDecimal>{
+		let (a, b, scale) = self.align(other)?;
+		Some(CplDecimal::new(a.checked_rem(b)?, scale))
+	}
+
+	//	The quotient with places digits after the decimal point.  The divisor can't be zero
+	pub fn div(&self, other : &CplDecimal, places : u32, rounding : DecimalRounding) -> Option<CplDecimal>{
+		//	(a / 10^sa) / (b / 10^sb) * 10^places = (a * 10^(places + sb)) / (b * 10^sa)
+		let numerator = self.units.checked_mul(10i128.checked_pow(places + other.scale)?)?;
+		let denominator = other.units.checked_mul(10i128.checked_pow(self.scale)?)?;
+		let quotient = numerator / denominator;
+		let remainder = numerator % denominator;
+		if remainder == 0{
+			return Some(CplDecimal::new(quotient, places));
+		}
+
+		let negative = (numerator < 0) != (denominator < 0);
+		let away_from_zero = match rounding{
+			DecimalRounding::Down		=> false,
+			DecimalRounding::Up			=> true,
+			DecimalRounding::Floor		=> negative,
+			DecimalRounding::Ceiling	=> !negative,
+			DecimalRounding::HalfUp | DecimalRounding::HalfEven => {
+				match (remainder.unsigned_abs() * 2).cmp(&denominator.unsigned_abs()){
+					Ordering::Greater	=> true,
+					Ordering::Less		=> false,
+					Ordering::Equal		=> rounding == DecimalRounding::HalfUp || quotient % 2 != 0,
+				}
+			},
+		};
+
+		match (away_from_zero, negative){
+			(false, _) 		=> Some(CplDecimal::new(quotient, places)),
+			(true, false)	=> Some(CplDecimal::new(quotient.checked_add(1)?, places)),
+			(true, true)	=> Some(CplDecimal::new(quotient.checked_sub(1)?, places)),
+		}
+	}
+
+	//	None on overflow
+	pub fn pow(&self, power : u32) -> Option<CplDecimal>{
+		let mut rslt = CplDecimal::from_int(1);
+		let mut square = *self;
+		let mut power = power;
+		while power > 0{
+			if power & 1 == 1{
+				rslt = rslt.mul(&square)?;
+			}
+			power >>= 1;
+			if power > 0{
+				square = square.mul(&square)?;
+			}
+		}
+		Some(rslt)
+	}
+
+	pub fn round(&self, places : u32, rounding : DecimalRounding) -> Option<CplDecimal>{
+		self.div(&CplDecimal::from_int(1), places, rounding)
+	}
+
+	pub fn compare(&self, other : &CplDecimal) -> Ordering{
+		match self.align(other){
+			Some((a, b, _)) => a.cmp(&b),
+			None => self.as_real().partial_cmp(&other.as_real()).unwrap_or(Ordering::Equal),
+		}
+	}
+}
+
+impl fmt::Display for CplDecimal{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let digits = format!("{:0>width$}", self.units.unsigned_abs(), width = self.scale as usize + 1);
+		let sign = if self.units < 0 {"-"} else {""};
+		if self.scale == 0{
+			write!(f, "{}{}", sign, digits)
+		}else{
+			let (whole, fraction) = digits.split_at(digits.len() - self.scale as usize);
+			write!(f, "{}{}.{}", sign, whole, fraction)
+		}
+	}
+}
+
+//	An Int is exact:  its value is cpl_int and cpl_number is the same value as a float
+//	(for the code that only needs an approximation, e.g. an index).  A Real only uses cpl_number.
+//	A Decimal is exact too:  its value is cpl_decimal and cpl_number and cpl_int approximate it
 pub struct CplNumber {
 	pub rust_data_type : RustDataType,
 	pub cpl_number : f64,
+	pub cpl_int : i64,
+	pub cpl_decimal : CplDecimal,
+}
+
+//	For error messages
+pub fn operator_symbol(op : Opcode) -> &'static str{
+	match op{
+		Opcode::Add		=> "+",
+		Opcode::Sub		=> "-",
+		Opcode::Mul		=> "*",
+		Opcode::Div		=> "/",
+		Opcode::IntDiv	=> "div",
+		Opcode::Mod		=> "%",
+		Opcode::Pow		=> "**",
+		Opcode::BwAnd	=> "&",
+		Opcode::BwOr	=> "|",
+		Opcode::BwXor	=> "^",
+		Opcode::Shl		=> "<<",
+		Opcode::Shr		=> ">>",
+		Opcode::Concat	=> ".",
+		Opcode::Lt		=> "<",
+		Opcode::Gt		=> ">",
+		Opcode::Le		=> "<=",
+		Opcode::Ge		=> ">=",
+		Opcode::Eq		=> "==",
+		Opcode::Ne		=> "!=",
+		Opcode::AddEq	=> "+=",
+		Opcode::SubEq	=> "-=",
+		Opcode::MulEq	=> "*=",
+		Opcode::DivEq	=> "/=",
+		Opcode::ModEq	=> "%=",
+		Opcode::PowEq	=> "**=",
+		Opcode::AndEq	=> "&=",
+		Opcode::OrEq	=> "|=",
+		Opcode::XorEq	=> "^=",
+		Opcode::ShlEq	=> "<<=",
+		Opcode::ShrEq	=> ">>=",
+		_				=> "?",
+	}
+}
+
+//	For error messages:  ++ and -- (and += 1, which is compiled as ++) are "n + 1"
+fn unary_expression(op : Opcode, n : &CplNumber) -> String{
+	match op{
+		Opcode::Inc		=> format!("{} + 1", n),
+		Opcode::Dec		=> format!("{} - 1", n),
+		_				=> format!("-({})", n),
+	}
 }
 
 impl CplNumber{
@@ -1791,6 +2500,60 @@ impl CplNumber{
 		CplNumber{
 			rust_data_type : rust_data_type,
 			cpl_number : data,
+			cpl_int : data as i64,
+			cpl_decimal : CplDecimal::new(0, 0),
+		}
+	}
+
+	pub fn new_int(data : i64) -> CplNumber{
+		CplNumber{
+			rust_data_type : RustDataType::Int,
+			cpl_number : data as f64,
+			cpl_int : data,
+			cpl_decimal : CplDecimal::new(0, 0),
+		}
+	}
+
+	pub fn new_decimal(data : CplDecimal) -> CplNumber{
+		let approximation = data.as_real();
+		CplNumber{
+			rust_data_type : RustDataType::Decimal,
+			cpl_number : approximation,
+			cpl_int : approximation as i64,
+			cpl_decimal : data,
+		}
+	}
+
+	//	A number without a fraction or an exponent is an Int
+	pub fn parse(text : &str) -> Option<CplNumber>{
+		if let Ok(i) = text.parse::<i64>(){
+			return Some(CplNumber::new_int(i));
+		}
+		match text.parse::<f64>(){
+			Ok(r) => Some(CplNumber::new(RustDataType::Real, r)),
+			Err(_) => None,
+		}
+	}
+
+	pub fn is_int(&self) -> bool{
+		self.rust_data_type == RustDataType::Int
+	}
+
+	pub fn is_decimal(&self) -> bool{
+		self.rust_data_type == RustDataType::Decimal
+	}
+
+	//	Ints and Decimals
+	pub fn is_exact(&self) -> bool{
+		self.is_int() || self.is_decimal()
+	}
+
+	//	None for a Real that is infinite or not a number
+	pub fn as_decimal(&self) -> Option<CplDecimal>{
+		match self.rust_data_type{
+			RustDataType::Decimal	=> Some(self.cpl_decimal),
+			RustDataType::Int		=> Some(CplDecimal::from_int(self.cpl_int)),
+			_						=> CplDecimal::from_real(self.cpl_number),
 		}
 	}
 
@@ -1799,7 +2562,11 @@ impl CplNumber{
 	}
 
 	pub fn as_int(&self) ->i64{
-		self.cpl_number as i64
+		if self.is_int(){
+			self.cpl_int
+		}else{
+			self.cpl_number as i64
+		}
 	}
 
 	pub fn as_char(&self) -> char{
@@ -1818,107 +2585,251 @@ impl CplNumber{
 		}
 	}
 
-	pub fn apply_unary_operator (&mut self, op : Opcode){
+	//	Two Ints give an Int (except '/', and '**' with a negative power, which give a Real) and
+	//	the result has to fit in 64 bits.  If either is a Decimal, so is the result.  Otherwise,
+	//	if either is a Real, so is the result.  Bitwise operators and shifts work on the integer
+	//	values and give an Int.  The assignment operators (e.g. +=) are the same as their operator
+	pub fn arithmetic(&self, other : &CplNumber, op : Opcode) -> Result<CplNumber, String>{
+		let op = match op{
+			Opcode::AddEq	=> Opcode::Add,
+			Opcode::SubEq	=> Opcode::Sub,
+			Opcode::MulEq	=> Opcode::Mul,
+			Opcode::DivEq	=> Opcode::Div,
+			Opcode::ModEq	=> Opcode::Mod,
+			Opcode::OrEq	=> Opcode::BwOr,
+			Opcode::AndEq	=> Opcode::BwAnd,
+			Opcode::XorEq	=> Opcode::BwXor,
+			Opcode::PowEq	=> Opcode::Pow,
+			Opcode::ShlEq	=> Opcode::Shl,
+			Opcode::ShrEq	=> Opcode::Shr,
+			_ => op,
+		};
+
+		//	the bitwise and shift operators don't truncate a real or a decimal
+		let bitwise = op == Opcode::BwAnd || op == Opcode::BwOr || op == Opcode::BwXor || op == Opcode::Shl || op == Opcode::Shr;
+		if bitwise && (!self.is_int() || !other.is_int()){
+			return Err(format!("integers needed: {} {} {}", self, operator_symbol(op), other));
+		}
+
+		if op == Opcode::BwAnd || op == Opcode::BwOr || op == Opcode::BwXor{
+			let (a, b) = (self.as_int(), other.as_int());
+			return Ok(CplNumber::new_int(match op {Opcode::BwAnd => a & b, Opcode::BwOr => a | b, _ => a ^ b}));
+		}
+
+		//	the bits shifted out of a 64 bit integer are lost.  '>>' keeps the sign
+		if op == Opcode::Shl || op == Opcode::Shr{
+			let (a, b) = (self.as_int(), other.as_int());
+			if b < 0 || b > 63{
+				return Err(format!("invalid shift count: {} {} {}", a, operator_symbol(op), b));
+			}
+			return Ok(CplNumber::new_int(if op == Opcode::Shl {a << b} else {a >> b}));
+		}
+
+		if self.is_decimal() || other.is_decimal(){
+			//	a '/' keeps the places of the operand with the most
+			let places = self.as_decimal().map_or(0, |d| d.scale).max(other.as_decimal().map_or(0, |d| d.scale));
+			return self.decimal_arithmetic(other, op, places, DecimalRounding::HalfEven);
+		}
+
+		if self.is_int() && other.is_int() && op != Opcode::Div{
+			let (a, b) = (self.cpl_int, other.cpl_int);
+			if b == 0 && (op == Opcode::IntDiv || op == Opcode::Mod){
+				return Err(format!("integer division by zero: {} {} {}", a, operator_symbol(op), b));
+			}
+			if op == Opcode::Pow && b < 0{
+				return Ok(CplNumber::new(RustDataType::Real, (a as f64).powf(b as f64)));
+			}
+			let rslt = match op{
+				Opcode::Add		=> a.checked_add(b),
+				Opcode::Sub		=> a.checked_sub(b),
+				Opcode::Mul		=> a.checked_mul(b),
+				Opcode::Mod		=> a.checked_rem(b),
+				//	rounded down (-7 div 2 is -4)
+				Opcode::IntDiv	=> a.checked_div(b).map(|q| if a % b != 0 && (a < 0) != (b < 0) {q - 1} else {q}),
+				Opcode::Pow		=> u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+				_ => abend!(format!("from CplNumber.arithmetic: {} isn't an arithmetic operator", op)),
+			};
+			return match rslt{
+				Some(n) => Ok(CplNumber::new_int(n)),
+				None => Err(format!("integer overflow: {} {} {}", a, operator_symbol(op), b)),
+			};
+		}
+
+		let (a, b) = (self.cpl_number, other.cpl_number);
+		let rslt = match op{
+			Opcode::Add		=> a + b,
+			Opcode::Sub		=> a - b,
+			Opcode::Mul		=> a * b,
+			Opcode::Div		=> a / b,
+			Opcode::IntDiv	=> (a / b).floor(),
+			Opcode::Pow		=> a.powf(b),
+			Opcode::Mod		=> {
+				if b as i64 == 0{
+					return Err(format!("integer division by zero: {} {} {}", a, operator_symbol(op), b));
+				}
+				(a as i64 % b as i64) as f64
+			},
+			_ => abend!(format!("from CplNumber.arithmetic: {} isn't an arithmetic operator", op)),
+		};
+		Ok(CplNumber::new(RustDataType::Real, rslt))
+	}
+
+	//	A Decimal operation.  The other operand is converted to a Decimal.  places and rounding
+	//	are for division
+	pub fn decimal_arithmetic(&self, other : &CplNumber, op : Opcode, places : u32, rounding : DecimalRounding) -> Result<CplNumber, String>{
+		let (a, b) = match (self.as_decimal(), other.as_decimal()){
+			(Some(a), Some(b)) => (a, b),
+			_ => return Err(format!("{} {} {} can't be done with decimals", self, operator_symbol(op), other)),
+		};
+		if b.units == 0 && (op == Opcode::Div || op == Opcode::IntDiv || op == Opcode::Mod){
+			return Err(format!("decimal division by zero: {} {} {}", a, operator_symbol(op), b));
+		}
+		let rslt = match op{
+			Opcode::Add		=> a.add(&b),
+			Opcode::Sub		=> a.sub(&b),
+			Opcode::Mul		=> a.mul(&b),
+			Opcode::Div		=> a.div(&b, places, rounding),
+			Opcode::IntDiv	=> a.div(&b, 0, DecimalRounding::Floor),
+			Opcode::Mod		=> a.rem(&b),
+			Opcode::Pow		=> match b.round(0, DecimalRounding::Down){
+				Some(power) if power.compare(&b) == Ordering::Equal && power.units >= 0 && power.units <= u32::MAX as i128 => a.pow(power.units as u32),
+				_ => return Err(format!("a decimal's power has to be a whole number of 0 or more: {} ** {}", a, b)),
+			},
+			_ => abend!(format!("from CplNumber.decimal_arithmetic: {} isn't an arithmetic operator", op)),
+		};
+		match rslt{
+			Some(d) => Ok(CplNumber::new_decimal(d)),
+			None => Err(format!("decimal overflow: {} {} {}", a, operator_symbol(op), b)),
+		}
+	}
+
+	//	Ints and Decimals are compared exactly
+	pub fn compare(&self, other : &CplNumber) -> Ordering{
+		if self.is_int() && other.is_int(){
+			self.cpl_int.cmp(&other.cpl_int)
+		}else if let (true, Some(a), Some(b)) = (self.is_decimal() || other.is_decimal(), self.as_decimal(), other.as_decimal()){
+			a.compare(&b)
+		}else{
+			self.cpl_number.partial_cmp(&other.cpl_number).unwrap_or(Ordering::Equal)
+		}
+	}
+
+	//	An overflow is returned for the executor to throw
+	pub fn apply_unary_operator (&mut self, op : Opcode) -> Result<(), String>{
+		//	'~' flips the bits of the integer value
+		if op == Opcode::BwNot{
+			if !self.is_int(){
+				return Err(format!("integer needed: ~{}", self));
+			}
+			*self = CplNumber::new_int(!self.as_int());
+			return Ok(());
+		}
+
+		if self.is_int(){
+			let rslt = match op{
+				Opcode::Inc	   => self.cpl_int.checked_add(1),
+				Opcode::Dec    => self.cpl_int.checked_sub(1),
+				Opcode::Uminus => self.cpl_int.checked_neg(),
+				_=> abend!(format!("from CplNumber.apply_unary_operator: Unable to apply {} to {}", op, self)),
+			};
+			match rslt{
+				Some(n) => *self = CplNumber::new_int(n),
+				None => return Err(format!("integer overflow: {}", unary_expression(op, self))),
+			}
+			return Ok(());
+		}
+
+		if self.is_decimal(){
+			let one = CplDecimal::from_int(1);
+			let rslt = match op{
+				Opcode::Inc	   => self.cpl_decimal.add(&one),
+				Opcode::Dec    => self.cpl_decimal.sub(&one),
+				Opcode::Uminus => self.cpl_decimal.units.checked_neg().map(|units| CplDecimal::new(units, self.cpl_decimal.scale)),
+				_=> abend!(format!("from CplNumber.apply_unary_operator: Unable to apply {} to {}", op, self)),
+			};
+			match rslt{
+				Some(d) => *self = CplNumber::new_decimal(d),
+				None => return Err(format!("decimal overflow: {}", unary_expression(op, self))),
+			}
+			return Ok(());
+		}
+
 		match op{
 			Opcode::Inc	   => self.cpl_number += 1.0,
 			Opcode::Dec    => self.cpl_number -= 1.0,
 			Opcode::Uminus => self.cpl_number *= -1.0,
 			_=> abend!(format!("from CplNumber.apply_unary_operator: Unable to apply {} to {}", op, self.cpl_number)),
 		}
+		Ok(())
 	}
 
-	pub fn apply_binary_operator_to_number (&mut self, rvalue : &CplVar, op : Opcode, ){
+	//	x += y etc.  Like the binary operators, an arithmetic error is returned for the
+	//	executor to throw
+	pub fn apply_binary_operator_to_number (&mut self, rvalue : &CplVar, op : Opcode) -> Result<(), String>{
 		//	first, get the new value as just a number
-		let newv : f64;
+		let newv : &CplNumber;
 		
 		if let CplDataType::CplNumber(n) = &rvalue.var{
-			newv = n.cpl_number;
+			newv = n;
 		}else{
 			panic!("from CplNumber.update_operator: the operand is not numeric: {}", rvalue.var);
 		}
 		//	now perform the operation specified by op
 		match op{
-			Opcode::Update => self.cpl_number  = newv,
-			Opcode::AddEq  => self.cpl_number += newv,
-			Opcode::SubEq  => self.cpl_number -= newv,
-			Opcode::DivEq  => self.cpl_number /= newv,
-			Opcode::MulEq  => self.cpl_number *= newv,
-			Opcode::ModEq  => self.cpl_number = (self.cpl_number as i32 % newv as i32) as f64,
-			Opcode::OrEq   => self.cpl_number = (self.cpl_number as i32 | newv as i32) as f64,
-			Opcode::AndEq  => self.cpl_number = (self.cpl_number as i32 & newv as i32) as f64,
-			_=> abend!(format!("from CplNumber.apply_operator_to_number: Unable to operate to perform {} {} {}", self.cpl_number, op, newv)),
+			Opcode::Update => *self = newv.copy(),
+			Opcode::AddEq | Opcode::SubEq | Opcode::DivEq | Opcode::MulEq | Opcode::ModEq | Opcode::OrEq | Opcode::AndEq
+				| Opcode::XorEq | Opcode::PowEq | Opcode::ShlEq | Opcode::ShrEq => {
+				*self = self.arithmetic(newv, op)?;
+			},
+			_=> abend!(format!("from CplNumber.apply_operator_to_number: Unable to operate to perform {} {} {}", self, op, newv)),
 		}
+		Ok(())
 	}
 	
-	pub fn add (&mut self, num : f64){
-		self.cpl_number += num;
-	}
-
-	pub fn mul (&mut self, num : f64){
-		self.cpl_number *= num;
-	}
-
-	pub fn div (&mut self, num : f64){
-		self.cpl_number /= num;
-	}
-
-	pub fn modulo (&mut self, num : f64){
-		self.cpl_number = self.cpl_number % num;
-	}
-
-	pub fn bw_and (&mut self, num : f64){
-		self.cpl_number = (self.cpl_number as i64 & num as i64) as f64;
-	}
-
-	pub fn bw_or (&mut self, num : f64){
-		self.cpl_number = (self.cpl_number as i64 | num as i64) as f64;
-	}
-
 	pub fn update(&mut self, num : &CplVar){
 		if let CplDataType::CplNumber(ref n) = num.var{
-			self.cpl_number = n.cpl_number;
+			*self = n.copy();
 		}else{
 			abend!(format!("from CplNumber.update: Update Value is not a number.  It is a {}", num.var));
 		}
 	}
 
 	pub fn copy(&self) -> CplNumber{
-		CplNumber::new(self.rust_data_type, self.cpl_number)
+		CplNumber{
+			rust_data_type : self.rust_data_type,
+			cpl_number : self.cpl_number,
+			cpl_int : self.cpl_int,
+			cpl_decimal : self.cpl_decimal,
+		}
 	}
 }
 impl PartialEq for CplNumber{
 	fn eq(&self, other : &Self) -> bool{
-		return self.cpl_number == other.cpl_number;
+		return self.compare(other) == Ordering::Equal;
 	}
 }
 impl Eq for CplNumber{}
 
 impl PartialOrd for CplNumber {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-		if self.cpl_number > other.cpl_number{
-			return Some(Ordering::Greater);
-		}else if self.cpl_number < other.cpl_number{
-			return Some(Ordering::Less);
-		}else{
-			return Some(Ordering::Equal);
-		}
+		Some(self.compare(other))
     }
 }
 impl Ord for CplNumber {
     fn cmp(&self, other: &Self) -> Ordering {
-		if self.cpl_number > other.cpl_number{
-			return Ordering::Greater;
-		}else if self.cpl_number < other.cpl_number{
-			return Ordering::Less;
-		}else{
-			return Ordering::Equal;
-		}
+		self.compare(other)
     }
 }
 
 impl fmt::Display for CplNumber{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{}",self.cpl_number)
+		if self.is_int(){
+			write!(f, "{}",self.cpl_int)
+		}else if self.is_decimal(){
+			write!(f, "{}",self.cpl_decimal)
+		}else{
+			write!(f, "{}",self.cpl_number)
+		}
 	}
 }
 
@@ -1931,6 +2842,7 @@ pub struct CplFileReader{
 	pub file_len : usize,
 	pub reader : BufReader<File>,
 	pub open_mode : String,
+	pub csv_decimals : bool,			// read_csv reads numbers as decimals
 }
 
 impl CplFileReader{
@@ -1943,6 +2855,7 @@ impl CplFileReader{
 			count : 0,
 			file_len : 0,
 			open_mode : open_mode.to_string(),
+			csv_decimals : open_mode.len() > 2 && open_mode.as_bytes()[2] as char == 'd',
 		};
 
 
@@ -2021,9 +2934,19 @@ impl CplFileReader{
 			//
 			//	For built-in functions, the "as_string()" function should always return a string, even if the
 			//	operand is a number.  Really??????
-			match element.parse::<f64>() {
-				Err(_) => rtn.push(&CplVar::new(CplDataType::CplString(CplString::new(element.trim_end_matches('\n').to_string())))),
-				Ok(parsed) => rtn.push(&CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Real, parsed)))),
+			//
+			//	With a 'd' after the delimiter in the open mode (e.g. "<,d"), numbers are read
+			//	as decimals so that amounts add up exactly
+			if self.csv_decimals{
+				match CplDecimal::parse(&element) {
+					None => rtn.push(&CplVar::new(CplDataType::CplString(CplString::new(element.trim_end_matches('\n').to_string())))),
+					Some(parsed) => rtn.push(&CplVar::new(CplDataType::CplNumber(CplNumber::new_decimal(parsed)))),
+				}
+			}else{
+				match element.parse::<f64>() {
+					Err(_) => rtn.push(&CplVar::new(CplDataType::CplString(CplString::new(element.trim_end_matches('\n').to_string())))),
+					Ok(parsed) => rtn.push(&CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Real, parsed)))),
+				}
 			}
 			if !ex{break}
 		}
@@ -2086,7 +3009,7 @@ impl CplFileWriter{
 			}
 			match &item.var{
 				CplDataType::CplNumber (n) => {
-					self.write(&n.cpl_number.to_string(),false);
+					self.write(&n.to_string(),false);
 				}
 				CplDataType::CplString (s) => {
 					match s.cpl_string.find(','){
@@ -2149,7 +3072,7 @@ impl CplFileWriter{
 		for item in &array.cpl_array{
 			match &item.var{
 				CplDataType::CplNumber (n) => {
-					self.write(&format!("<{}>{}</{}>",xml_tag!(item.interner), n.cpl_number.to_string(),xml_tag!(item.interner)),true);
+					self.write(&format!("<{}>{}</{}>",xml_tag!(item.interner), n.to_string(),xml_tag!(item.interner)),true);
 				}
 				CplDataType::CplString (s) => {
 					self.write(&format!("<{}>{}</{}>",xml_tag!(item.interner), s.cpl_string, xml_tag!(item.interner)),true);
@@ -2219,7 +3142,7 @@ impl CplKey{
 	//	Build a key from supported Data Types
 	pub fn to_key(cpl_var : &CplDataType) -> CplKey{
 		match &cpl_var{
-			CplDataType::CplNumber(n) 				=> CplKey::new(&n.cpl_number.to_string()),
+			CplDataType::CplNumber(n) 				=> CplKey::new(&n.to_string()),
 			CplDataType::CplString(ref s)			=> CplKey::new(&s.cpl_string),
 			CplDataType::CplBool(b)					=> CplKey::new(&b.cpl_bool.to_string()),
 			_										=> abend!(format!("Sorry, you can't use {} as a key to a dictionary", cpl_var)),
@@ -2281,32 +3204,30 @@ impl CplDict{
 	}
 
 
-	fn update_indexed_op_number(&mut self, key : &CplKey, raw_value : f64){
-		self.cpl_dict.insert(key.clone(),CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Real, raw_value))));
+	fn update_indexed_op_number(&mut self, key : &CplKey, rslt : Result<CplNumber, String>) -> Result<(), String>{
+		self.cpl_dict.insert(key.clone(),CplVar::new(CplDataType::CplNumber(rslt?)));
+		Ok(())
 	}
 
 	//	Perform an operation on an element
-	pub fn update_indexed_op(&mut self, index : &CplVar, rvalue : &CplVar, op : Opcode){
+	pub fn update_indexed_op(&mut self, index : &CplVar, rvalue : &CplVar, op : Opcode) -> Result<(), String>{
 		let key : CplKey = CplKey::to_key(&index.var);
 
 		if let CplDataType::CplNumber(ref new_v) = rvalue.var{
 			if let CplDataType::CplNumber(ref e) = self.cpl_dict[&key].var{
 				match op{
-					Opcode::AddEq => {
-						self.update_indexed_op_number(&key, e.cpl_number+new_v.cpl_number);
+					Opcode::AddEq | Opcode::SubEq | Opcode::DivEq | Opcode::MulEq | Opcode::ModEq | Opcode::OrEq | Opcode::AndEq
+						| Opcode::XorEq | Opcode::PowEq | Opcode::ShlEq | Opcode::ShrEq => {
+						let rslt = e.arithmetic(new_v, op);
+						return self.update_indexed_op_number(&key, rslt);
 					},
-					Opcode::SubEq => self.update_indexed_op_number(&key, e.cpl_number-new_v.cpl_number),
-					Opcode::DivEq => self.update_indexed_op_number(&key, e.cpl_number/new_v.cpl_number),
-					Opcode::MulEq => self.update_indexed_op_number(&key, e.cpl_number*new_v.cpl_number),
-					Opcode::ModEq => self.update_indexed_op_number(&key, (e.cpl_number as i64%new_v.cpl_number as i64) as f64),
-					Opcode::OrEq  => self.update_indexed_op_number(&key, (e.cpl_number as i64|new_v.cpl_number as i64) as f64),
-					Opcode::AndEq => self.update_indexed_op_number(&key, (e.cpl_number as i64&new_v.cpl_number as i64) as f64),
 					_=> abend!(format!("from CplArray:update_indexed_op: Expecting an assignment operator.  Got {}", op)),
 				}
 			}else{
 				abend!(format!("from CplArray:update_indexed_op: Op {} only works on numbers. Array element is {}", op, index));
 			}
 		}
+		Ok(())
 	}
 
 	//	This seems to be purpose built for the builtin insert function
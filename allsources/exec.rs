@@ -13,6 +13,7 @@ use runtimestats::*;
 use std::time::SystemTime;
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
+use std::cmp::Ordering;
 
 
 //	These enums indicate the data types of two operands
@@ -34,6 +35,12 @@ enum OperandAnalysis{
 	ArrayNumber,
 	ArrayBool,
 	ArrayArray,
+	DateTimeDateTime,
+	DateTimeNumber,
+	DateTimeString,
+	StringDateTime,
+	NumberDateTime,
+	DateTimeInvalid,
 }
 
 //	These are the types that are possible (any other types discovered
@@ -44,10 +51,37 @@ enum OperandType{
 	OtNumber,
 	OtBool,
 	OtArray,
+	OtDateTime,
+}
+
+//	A catch block registered by a Try instruction.  depth is the length of the
+//	block_end_return_info stack when the Try was executed and variable_count is the
+//	number of operands in the block that contains the try statement
+struct TryInfo{
+	catch_block : usize,
+	return_block : usize,
+	return_address : usize,
+	depth : usize,
+	variable_count : usize,
 }
 
 
 
+fn string_var(s : &str) -> CplVar{
+	CplVar::new(CplDataType::CplString(CplString::new(s.to_string())))
+}
+
+//	The value of a thrown error is a dictionary containing the message, the line
+//	number where it was thrown and the thrown value
+fn error_value(message : &str, value : &CplVar, token : &Token) -> CplVar{
+	let mut dict = CplDict::new();
+	dict.update_indexed(&string_var("message"), &string_var(message));
+	dict.update_indexed(&string_var("line"), &CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Int, token.line_number as f64))));
+	dict.update_indexed(&string_var("file"), &string_var(&token.file_name));
+	dict.update_indexed(&string_var("value"), value);
+	CplVar::new(CplDataType::CplDict(dict))
+}
+
 //	An Executor contains all of the code, operands and processing data
 //	for the execution of a single function.  There is no communication
 //	between functions except for call (arguments) and return (return_value)
@@ -104,8 +138,24 @@ pub struct Executor<'a>{
 	//	just keep track of begin and ends for sanity checking
 	block_counter : i32,
 
-	//	hmmmm, just a diagnostic
-	_call_flag : bool,
+	//	The catch blocks of the try statements that are currently executing
+	try_info : Vec<TryInfo>,
+
+	//	The error value handed to the catch block by the Catch instruction
+	caught : CplVar,
+
+	//	true when a throw transferred control to a catch block
+	catching : bool,
+
+	//	integer overflow or division by zero in the current binary operation
+	arithmetic_error : Option<String>,
+
+	//	An error that wasn't caught in this function.  The calling function
+	//	throws it again
+	exception : Option<CplVar>,
+
+	//	false for the entry function
+	call_flag : bool,
 
 	runtime_data : & 'a mut RuntimeData,
 	runtime_data_qual : String,
@@ -137,7 +187,12 @@ impl<'a> Executor<'a>{
 			instruction_counter : 0,
 			return_value : CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())),
 			block_counter : 0,
-			_call_flag : false,
+			try_info : Vec::new(),
+			caught : CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())),
+			catching : false,
+			arithmetic_error : None,
+			exception : None,
+			call_flag : false,
 			runtime_data : runtime_data,
 			runtime_data_qual : String::new(),
 			names : names,
@@ -172,7 +227,12 @@ impl<'a> Executor<'a>{
 			instruction_counter : 0,
 			return_value : CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())),
 			block_counter : 0,
-			_call_flag : true,
+			try_info : Vec::new(),
+			caught : CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())),
+			catching : false,
+			arithmetic_error : None,
+			exception : None,
+			call_flag : true,
 			runtime_data : runtime_data,
 			runtime_data_qual : String::new(),
 			names : names,
@@ -278,8 +338,13 @@ impl<'a> Executor<'a>{
 				Opcode::Sub 					=> self.exec_binary_operator(instruction),
 				Opcode::Mul 					=> self.exec_binary_operator(instruction),
 				Opcode::Div 					=> self.exec_binary_operator(instruction),
+				Opcode::IntDiv 					=> self.exec_binary_operator(instruction),
 				Opcode::Mod 					=> self.exec_binary_operator(instruction),
 				Opcode::BwOr 					=> self.exec_binary_operator(instruction),
+				Opcode::BwXor 					=> self.exec_binary_operator(instruction),
+				Opcode::Shl 					=> self.exec_binary_operator(instruction),
+				Opcode::Shr 					=> self.exec_binary_operator(instruction),
+				Opcode::Pow 					=> self.exec_binary_operator(instruction),
 				Opcode::BwAnd 					=> self.exec_binary_operator(instruction),
 				Opcode::Concat 					=> self.exec_binary_operator(instruction),
 
@@ -291,6 +356,10 @@ impl<'a> Executor<'a>{
 				Opcode::ModEq 					=> self.exec_update(instruction),
 				Opcode::OrEq 					=> self.exec_update(instruction),
 				Opcode::AndEq 					=> self.exec_update(instruction),
+				Opcode::XorEq 					=> self.exec_update(instruction),
+				Opcode::PowEq 					=> self.exec_update(instruction),
+				Opcode::ShlEq 					=> self.exec_update(instruction),
+				Opcode::ShrEq 					=> self.exec_update(instruction),
 				Opcode::AppendEq 				=> self.exec_update(instruction),
 				Opcode::Update					=> self.exec_update(instruction),
 
@@ -304,6 +373,7 @@ impl<'a> Executor<'a>{
 				Opcode::Dec 					=> self.exec_inc_dec(instruction),
 				Opcode::Uminus 					=> self.exec_unary_op(instruction),	
 				Opcode::Damnit					=> self.exec_unary_op(instruction),
+				Opcode::BwNot					=> self.exec_unary_op(instruction),
 				Opcode::LengthOf				=> self.exec_length_of(),
 
 				Opcode::J						=> self.exec_j(instruction),
@@ -314,14 +384,23 @@ impl<'a> Executor<'a>{
 				Opcode::Break					=> self.exec_break(instruction),
 				Opcode::Continue				=> self.exec_continue(instruction),
 
+				Opcode::Try						=> self.exec_try(instruction),
+				Opcode::EndTry					=> self.exec_end_try(instruction),
+				Opcode::Catch					=> self.exec_catch(instruction),
+				Opcode::Throw					=> self.exec_throw(instruction),
+
 				Opcode::Lt						=> self.exec_binary_operator(instruction),
 				Opcode::Gt						=> self.exec_binary_operator(instruction),
 				Opcode::Le						=> self.exec_binary_operator(instruction),
 				Opcode::Ge						=> self.exec_binary_operator(instruction),
 				Opcode::Ne						=> self.exec_binary_operator(instruction),
 				Opcode::Eq						=> self.exec_binary_operator(instruction),
+				Opcode::In						=> self.exec_binary_operator(instruction),
+				Opcode::NotIn					=> self.exec_binary_operator(instruction),
+				Opcode::RegexMatch				=> self.exec_binary_operator(instruction),
 	
 				Opcode::FunctionCall			=> self.exec_function_call(instruction),
+				Opcode::Closure					=> self.exec_closure(instruction),
 				Opcode::FetchIndexed			=> self.exec_fetch_indexed(instruction),
 
 				Opcode::IncArgCount 			=> self.arg_count += 1,
@@ -333,6 +412,7 @@ impl<'a> Executor<'a>{
 				Opcode::Diag					=> self.exec_diag(instruction),
 
 				Opcode::Foreach					=> self.exec_foreach(instruction),
+				Opcode::Unpack					=> self.exec_unpack(instruction),
 
 				_ => abend!(format!("{} Not Implemented Yet", instruction.opcode)),
 			}
@@ -347,6 +427,17 @@ impl<'a> Executor<'a>{
 				self.dump_operands("After instruction Execution");
 			}
 
+			//	A throw either transferred control to a catch block or, if there isn't
+			//	one, ends this function so that the caller can throw it again
+			if self.exception.is_some(){
+				break;
+			}
+
+			if self.catching{
+				self.catching = false;
+				continue;
+			}
+
 			//	Don't increment if the instruction counter was set by the following
 			//	because those opcodes set the next address to get the instruction from rather than
 			//	the next opcode in the instruction stream.  Return is kind of a special case because
@@ -376,6 +467,19 @@ impl<'a> Executor<'a>{
 
 		if self.cli.is_debug_bit(TRACE_EXEC_DISPATCHER){eprintln!("exec(dispatcher -- exit): {}:{}",self.code_block_num, self.instruction_counter);}
 		self.operand_stack.pop_frame();
+
+		if !self.call_flag{
+			if let Some(ref error) = self.exception{
+				if let CplDataType::CplDict(ref d) = error.var{
+					let mut location = format!("line {}", d.get(&CplKey::to_key(&string_var("line").var)));
+					let file = d.get(&CplKey::to_key(&string_var("file").var)).as_string();
+					if !file.is_empty(){
+						location = format!("{} of {}", location, file);
+					}
+					abend!(format!("Uncaught error at {}: {}", location, d.get(&CplKey::to_key(&string_var("message").var))));
+				}
+			}
+		}
 		return CplVar::new(self.return_value.var.clone());
 	}
 
@@ -493,13 +597,28 @@ impl<'a> Executor<'a>{
 		match instruction.literal.token_type{
 			TokenType::INTEGER => rust_type = RustDataType::Int,
 			TokenType::FLOAT => rust_type = RustDataType::Real,
+			TokenType::DECIMAL => rust_type = RustDataType::Decimal,
 			TokenType::STRING => rust_type = RustDataType::String,
 			TokenType::BOOL => rust_type = RustDataType::Bool,
 			_ => abend!(format!("token_type_to_rust_type:  unknown token_type: {}",rust_type)),
 		}
 
-		self.operand_stack.push(&CplVar::new(
-			CplDataType::CplNumber (CplNumber::new (rust_type, instruction.literal.token_value.parse::<f64>().unwrap()))));
+		//	integer literals are exact
+		let number = if rust_type == RustDataType::Int{
+			match instruction.literal.token_value.parse::<i64>(){
+				Ok(i) => CplNumber::new_int(i),
+				Err(_) => abend!(format!("integer literal too big: {} at {}", instruction.literal.token_value, instruction.literal.location())),
+			}
+		}else if rust_type == RustDataType::Decimal{
+			match CplDecimal::parse(&instruction.literal.token_value){
+				Some(d) => CplNumber::new_decimal(d),
+				None => abend!(format!("decimal literal too big: {}d at {}", instruction.literal.token_value, instruction.literal.location())),
+			}
+		}else{
+			CplNumber::new (rust_type, instruction.literal.token_value.parse::<f64>().unwrap())
+		};
+
+		self.operand_stack.push(&CplVar::new(CplDataType::CplNumber(number)));
 	}
 
 	//	used by push_lit_bool and comparison ops
@@ -524,7 +643,7 @@ impl<'a> Executor<'a>{
 	}
 
 	fn push_lit(&mut self, instruction : &MachineInstruction, instruction_address : usize){
-		if instruction.literal.token_type == TokenType::INTEGER || instruction.literal.token_type == TokenType::FLOAT 	{
+		if instruction.literal.token_type == TokenType::INTEGER || instruction.literal.token_type == TokenType::FLOAT || instruction.literal.token_type == TokenType::DECIMAL{
 			self.push_lit_number(instruction, instruction_address);
 		}else if instruction.literal.token_type == TokenType::STRING{
 			self.push_lit_string(instruction, instruction_address);
@@ -547,6 +666,8 @@ impl<'a> Executor<'a>{
 			CplDataType::CplNumber(_)			|
 			CplDataType::CplString(_)			|
 			CplDataType::CplBool(_) 			|
+			CplDataType::CplFunction(_)			|
+			CplDataType::CplDateTime(_)			|
 			CplDataType::CplUninitialized(_)	|
 			CplDataType::CplUndefined(_) 		=> {
 				self.runtime_data_qual = "Scalar".to_string();
@@ -659,6 +780,19 @@ impl<'a> Executor<'a>{
 			}
 			OpcodeMode::Arg				=> self.push_arg(instruction, self.instruction_counter),
 
+			OpcodeMode::GlobalVar		=> {
+				let operand = self.operand_stack.fetch_global_var(instruction.address);
+				self.push_copy(&operand, instruction);
+			}
+			OpcodeMode::GlobalVarRef	=> {
+				let operand = self.operand_stack.fetch_global_var(instruction.address);
+				if let CplDataType::CplVarRef(_) = operand.var{
+					self.operand_stack.push(&operand);
+				}else{
+					self.operand_stack.push(&CplVar::new(CplDataType::CplVarRef(CplVarRef::new(GLOBAL_FRAME, 0, instruction.address))));
+				}
+			}
+
 			//	If we're sitting on top of an array, we can just add the element directly to it
 			OpcodeMode::Array			=> self.operand_stack.push_array_element(instruction.interner),
 
@@ -713,7 +847,7 @@ impl<'a> Executor<'a>{
 
 		match tos_ref.var{
 			CplDataType::CplNumber(ref v) => {
-				exec_print!(v.cpl_number, is_print, has_nl);
+				exec_print!(v, is_print, has_nl);
 			}
 
 			CplDataType::CplString(ref v) => {
@@ -727,6 +861,14 @@ impl<'a> Executor<'a>{
 			CplDataType::CplArray(ref v) => {
 				exec_print!(v, is_print, has_nl);
 			}
+
+			CplDataType::CplFunction(ref v) => {
+				exec_print!(format!("fn {}", v.name), is_print, has_nl);
+			}
+
+			CplDataType::CplDateTime(ref v) => {
+				exec_print!(v, is_print, has_nl);
+			}
 			_ => eprintln!("Can't print: {}", tos_ref.var),
 		}
 
@@ -737,23 +879,23 @@ impl<'a> Executor<'a>{
 
 	//  Apply an operator to a scalar lvalue.  The value may be either direct or via
 	//	a VarRef (if this is a called function with a pass by reference argument)
-	fn apply_assignment_operator(&mut self, block_num : usize, address : usize, opcode : Opcode) {
+	fn apply_assignment_operator(&mut self, block_num : usize, address : usize, opcode : Opcode) -> Result<(), String>{
 		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("     assignment_operator: {}", opcode);}
 
 		//	determine if the TOS is the variable to update or is a VarRef pointing at it.  Note that
 		//	we are only support a single level of indirection (i.e. we can't deal with a VarRef pointint at another
 		//	VarRef)
 		match self.operand_stack.fetch_local_var(block_num, address).var{
-			CplDataType::CplNumber(_) | CplDataType::CplString(_) => {
+			CplDataType::CplNumber(_) | CplDataType::CplString(_) | CplDataType::CplDateTime(_) => {
 				//	If the collection is direct (i.e. an array or dictionay) do this
 				// n.apply_binary_operator_to_number(&new_value_var, instruction.opcode);
-				self.operand_stack.apply_binary_operator_scalar_local(block_num, address, opcode);
+				self.operand_stack.apply_binary_operator_scalar_local(block_num, address, opcode)
 			}
 
 			CplDataType::CplVarRef(vr) =>{
 				//	Get the address of the lvalue via the VarRef
 				// let varref : &mut CplVar = self.operand_stack.fetch_mutable_ref(vr.frame_num, vr.block_num, vr.address);
-				self.operand_stack.apply_binary_operator_scalar_global(vr.frame_num, vr.block_num, vr.address, opcode);
+				self.operand_stack.apply_binary_operator_scalar_global(vr.frame_num, vr.block_num, vr.address, opcode)
 			}
 
 			_=> panic!("from exec.apply_assignment_operator:  unknown type: {}", self.operand_stack.fetch_local_var(block_num, address).var),
@@ -764,11 +906,14 @@ impl<'a> Executor<'a>{
 
 	//	This updates a local operand in situ via the address in the instruction.  Whether this
 	//	is a simple replacement or an operator assignment is determined by the opcode
-	fn update_scalar_tos(&mut self, block_num : usize, address : usize, op : Opcode){
+	fn update_scalar_tos(&mut self, block_num : usize, address : usize, op : Opcode) -> Result<(), String>{
 		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("      update_scalar_tos(start): {},{} op={}", block_num, address, op)}
 
 		match op{
-			Opcode::Update => self.operand_stack.update_local_from_tos(block_num, address),
+			Opcode::Update => {
+				self.operand_stack.update_local_from_tos(block_num, address);
+				Ok(())
+			},
 			_ => self.apply_assignment_operator(block_num, address, op),
 		}
 		//if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("      update_scalar_tos: {},{} {}", block_num, address, self.operand_stack.fetch_local_var(block_num, address))}
@@ -779,19 +924,19 @@ impl<'a> Executor<'a>{
 	//
 	//	If the mode is UpdateStructMember, the indices are in the qualfifier.  Structs
 	//	are always arrays so the indicies are always numbers.
-	fn update_indexed_direct(&mut self, instruction : &MachineInstruction){
+	fn update_indexed_direct(&mut self, instruction : &MachineInstruction) -> Result<(), String>{
 		if self.cli.is_debug_bit(TRACE_EXEC){
 			eprintln!("      update_indexed_direct: {} {} index_list={:?} op={}", instruction, self.operand_stack.fetch_local_var(instruction.block_num, instruction.address), instruction.qualifier, instruction.opcode);
 			//self.dump_operands("========== at update_indexed_direct");
 		}
 
-		self.operand_stack.update_local_collection(instruction.block_num, instruction.address, &instruction.qualifier, instruction.opcode, instruction.opcode_mode);
 		self.runtime_data_qual = "Indexed".to_string();
+		self.operand_stack.update_local_collection(instruction.block_num, instruction.address, &instruction.qualifier, instruction.opcode, instruction.opcode_mode)
 	}
 
 	//	As noted, this is a bit tricky:  we need to get a rust reference to the
 	//	operand pointed to by the VarRef
-	fn update_indexed_indirect(&mut self, instruction : &MachineInstruction, current_frame_num : usize){
+	fn update_indexed_indirect(&mut self, instruction : &MachineInstruction, current_frame_num : usize) -> Result<(), String>{
 		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("      update_indexed_indirect: {} {}", instruction, self.operand_stack.fetch_local_var(instruction.block_num, instruction.address))}
 	
 		let collection_ref = &self.operand_stack.operand_frames[current_frame_num].operand_blocks[instruction.block_num].operand_block[instruction.address];
@@ -812,9 +957,10 @@ impl<'a> Executor<'a>{
 				eprintln!("      update_indexed_indirect: frame={} block={} address={}", frame_num, block_num, address);
 			}
 
-			self.operand_stack.update_global_collection(frame_num, block_num, address, &instruction.qualifier, instruction.opcode, instruction.opcode_mode);
 			self.runtime_data_qual = "Indexed-Indirect".to_string();
+			return self.operand_stack.update_global_collection(frame_num, block_num, address, &instruction.qualifier, instruction.opcode, instruction.opcode_mode);
 		}
+		Ok(())
 	}
 
 
@@ -824,7 +970,7 @@ impl<'a> Executor<'a>{
 	//	the VarRef points at the collection.  This will happen when the collection was passed as
 	//	an argument to a function:  collections are always passed by reference.
 	//
-	fn update_indexed (&mut self, instruction : &MachineInstruction){
+	fn update_indexed (&mut self, instruction : &MachineInstruction) -> Result<(), String>{
 		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("      update_indexed: {} {} indices={:?}", instruction, self.operand_stack.fetch_local_var(instruction.block_num, instruction.address),instruction.qualifier)}
 
 		let frame_num = self.operand_stack.operand_frames.len()-1;
@@ -835,6 +981,15 @@ impl<'a> Executor<'a>{
 		}
 	}
 
+	//	An arithmetic error (e.g. overflow) in an assignment operator, ++ or -- is
+	//	thrown like one in an expression
+	fn throw_arithmetic_error(&mut self, rslt : Result<(), String>, instruction : &MachineInstruction){
+		if let Err(message) = rslt{
+			let error = error_value(&message, &string_var(&message), &instruction.literal);
+			self.throw(error);
+		}
+	}
+
 	//	Adds a Var to an array that is at the top of the stack.  Tos is the value to update.
 	//	Tos-1 is the array to update.
 	fn update_collection_array(&mut self, instruction : &MachineInstruction){
@@ -859,18 +1014,36 @@ impl<'a> Executor<'a>{
 		//	Update:					update a scalar
 		//	UpdateStructMember:		update the struct array (indices in instruction.qualifier)
 		//	UpdateIndexed:			update an normal array (Indices on stack, count in instruction.qualifier)
-		match instruction.opcode_mode{
+		let rslt = match instruction.opcode_mode{
 			OpcodeMode::Update => {
-				self.update_scalar_tos(instruction.block_num, instruction.address, instruction.opcode);
 				self.runtime_data_qual = "Scalar".to_string();
+				self.update_scalar_tos(instruction.block_num, instruction.address, instruction.opcode)
 			}
 
 			OpcodeMode::UpdateStructElement | OpcodeMode::UpdateIndexed => {
-				self.update_indexed(instruction);
+				let rslt = self.update_indexed(instruction);
 				self.runtime_data_qual = "Indexed".to_string();
+				rslt
+			}
+
+			OpcodeMode::UpdateGlobal => {
+				self.runtime_data_qual = "Global".to_string();
+				match instruction.opcode{
+					Opcode::Update => {
+						self.operand_stack.update_global_from_tos(instruction.address);
+						Ok(())
+					},
+					_ => self.operand_stack.apply_binary_operator_scalar_global(GLOBAL_FRAME, 0, instruction.address, instruction.opcode),
+				}
+			}
+
+			OpcodeMode::UpdateGlobalIndexed => {
+				self.runtime_data_qual = "Global-Indexed".to_string();
+				self.operand_stack.update_global_collection(GLOBAL_FRAME, 0, instruction.address, &instruction.qualifier, instruction.opcode, OpcodeMode::UpdateIndexed)
 			}
 			_=> abend!(format!("From exec_update:  I don't know what this means: {}", instruction.opcode_mode)),
-		}
+		};
+		self.throw_arithmetic_error(rslt, instruction);
 
 		if self.cli.is_debug_bit(DUMP_OPERANDS){self.dump_operands("at exec_update")}
 	}
@@ -906,7 +1079,8 @@ impl<'a> Executor<'a>{
 
 			CplDataType::CplArray(_) | CplDataType::CplDict(_)=> {
 				//	If the collection is direct (i.e. an array or dictionay) do this
-				self.operand_stack.apply_binary_operator_indexed(instruction.block_num, instruction.address, instruction.opcode);
+				let rslt = self.operand_stack.apply_binary_operator_indexed(instruction.block_num, instruction.address, instruction.opcode);
+				self.throw_arithmetic_error(rslt, instruction);
 			}
 
 
@@ -914,16 +1088,17 @@ impl<'a> Executor<'a>{
 			//	of collection update the element at the index
 			CplDataType::CplVarRef(vr) =>{
 				let varref : &mut CplVar = self.operand_stack.fetch_mutable_ref(vr.frame_num, vr.block_num, vr.address);
-				match varref.var{
+				let rslt = match varref.var{
 					CplDataType::CplArray (ref mut a) => {
-						a.update_indexed_op(&index, &new_value, instruction.opcode);
+						a.update_indexed_op(&index, &new_value, instruction.opcode)
 					}
 
 					CplDataType::CplDict (ref mut d) => {
-						d.update_indexed_op(&index, &new_value, instruction.opcode);
+						d.update_indexed_op(&index, &new_value, instruction.opcode)
 					}
 					_=> panic!("from exec.apply_binary_operator_indexed:  expected a VarRef to array or dictionary.  Got {}",varref.var),
-				}
+				};
+				self.throw_arithmetic_error(rslt, instruction);
 			}
 
 			_=> panic!("from exec.apply_binary_operator_indexed:  unknown type: {}", var.var),
@@ -946,7 +1121,31 @@ impl<'a> Executor<'a>{
 		self.operand_stack.push(&CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Int, length as f64))));
 	}
 
-	fn inc_dec_in_situ(&mut self, opcode : Opcode, frame_num : usize, block_num : usize, address : usize){
+	//	replace the array at the tos with its elements so that the first element
+	//	ends up on top.  The destructuring assignment that follows pops them into
+	//	its targets in order.  qualifier[0] is the number of targets
+	fn exec_unpack(&mut self, instruction : &MachineInstruction){
+		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : exec_unpack: {}", self.code_block_num, self.instruction_counter, instruction)}
+		let value = self.operand_stack.dereference_tos();
+		let target_count = instruction.qualifier[0];
+
+		let message = match value.var{
+			CplDataType::CplArray(ref a) => {
+				if a.cpl_array.len() == target_count{
+					for element in a.cpl_array.iter().rev(){
+						self.operand_stack.push(element);
+					}
+					return;
+				}
+				format!("Can't assign {} values to {} targets", a.cpl_array.len(), target_count)
+			},
+			_ => format!("Can only assign an array to {} targets", target_count),
+		};
+		let error = error_value(&message, &value, &instruction.literal);
+		self.throw(error);
+	}
+
+	fn inc_dec_in_situ(&mut self, opcode : Opcode, frame_num : usize, block_num : usize, address : usize) -> Result<(), String>{
 
 		if let CplDataType::CplNumber(ref mut n) = self.operand_stack.operand_frames
 					.get_mut(frame_num).unwrap()
@@ -954,11 +1153,7 @@ impl<'a> Executor<'a>{
 					.operand_block.get_mut(address).unwrap()
 					.var
 		{
-			if opcode == Opcode::Inc{
-				n.cpl_number += 1.0;
-			}else{
-				n.cpl_number -= 1.0;
-			}
+			n.apply_unary_operator(opcode)
 		}else{
 			panic!("from inc_dec_in_situ: can only increment or decrement a number");
 		}
@@ -971,27 +1166,32 @@ impl<'a> Executor<'a>{
 	// is Var then increment or decrement in situ.
 	fn exec_inc_dec (&mut self, instruction : &MachineInstruction){
 		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : exec_inc_dec: {}", self.code_block_num, self.instruction_counter, instruction)}
-		if instruction.opcode_mode == OpcodeMode::Var{
-			self.inc_dec_in_situ(instruction.opcode, self.operand_stack.current_frame(), instruction.block_num, instruction.address);
+		let rslt = if instruction.opcode_mode == OpcodeMode::Var{
+			self.inc_dec_in_situ(instruction.opcode, self.operand_stack.current_frame(), instruction.block_num, instruction.address)
+		}else if instruction.opcode_mode == OpcodeMode::GlobalVar{
+			self.inc_dec_in_situ(instruction.opcode, GLOBAL_FRAME, 0, instruction.address)
 		}else if instruction.opcode_mode == OpcodeMode::NONE {
 			let mut tos = self.operand_stack.pop();
 			if let CplDataType::CplVarRef(ref vr) = tos.var{
-				self.inc_dec_in_situ(instruction.opcode, vr.frame_num, vr.block_num, vr.address);
+				self.inc_dec_in_situ(instruction.opcode, vr.frame_num, vr.block_num, vr.address)
 			}else if let CplDataType::CplNumber(ref mut n) = tos.var{
-				if instruction.opcode == Opcode::Inc{
-					n.cpl_number += 1.0;
-				}else{
-					n.cpl_number -= 1.0;
+				let rslt = n.apply_unary_operator(instruction.opcode);
+				if rslt.is_ok(){
+					self.operand_stack.push(&tos);
 				}
-				self.operand_stack.push(&tos);
+				rslt
 			}else{
 				panic!("Huston, we have a problem.  Inc/Dec mode is not Var or NONE. It's {}", instruction.opcode_mode);
 			}
-		}
+		}else{
+			Ok(())
+		};
+		self.throw_arithmetic_error(rslt, instruction);
 	}
 
 	fn exec_unary_op(&mut self, instruction : &MachineInstruction){
-		self.operand_stack.perform_unary_op(instruction.address, instruction.opcode);
+		let rslt = self.operand_stack.perform_unary_op(instruction.address, instruction.opcode);
+		self.throw_arithmetic_error(rslt, instruction);
 	}
 
 	//	A special purpose opcode used internally for diagnosit purposes
@@ -1007,7 +1207,7 @@ impl<'a> Executor<'a>{
 		match tos.var {
 			CplDataType::CplNumber(ref v) => {
 				// eprintln!("================ grab_an_argument match number {}, mode={}",v,_opcode_mode);
-				return CplVar::new(CplDataType::CplNumber(CplNumber::new(v.rust_data_type, v.cpl_number)))
+				return CplVar::new(CplDataType::CplNumber(v.copy()))
 			},
 			CplDataType::CplBool(b) => return CplVar::new(CplDataType::CplBool(CplBool::new(b.cpl_bool))),
 			CplDataType::CplString(v) => return CplVar::new(CplDataType::CplString(CplString::new(v.cpl_string.clone()))),
@@ -1022,6 +1222,8 @@ impl<'a> Executor<'a>{
 			CplDataType::CplFileWriter(handle) => return CplVar::new(CplDataType::CplFileWriter(handle)),
 			CplDataType::CplDict(d) => return CplVar::new(CplDataType::CplDict(d.clone())),
 			CplDataType::CplStruct(s) => return CplVar::new(CplDataType::CplArray(s.clone())),
+			CplDataType::CplFunction(f) => return CplVar::new(CplDataType::CplFunction(f)),
+			CplDataType::CplDateTime(d) => return CplVar::new(CplDataType::CplDateTime(d)),
 			//_ => panic!("From grab_an_argument:  I don't understand this {}", tos.var)
 		}
 	}
@@ -1043,6 +1245,16 @@ impl<'a> Executor<'a>{
 		//	We don't need this from now on so reset it
 		self.arg_count = 0;
 
+		//	For an indirect call, the function value was pushed after the arguments
+		let mut function : Option<CplFunction> = None;
+		if instruction.opcode_mode == OpcodeMode::Indirect{
+			let callee = self.operand_stack.dereference_tos();
+			match callee.var{
+				CplDataType::CplFunction(f) => function = Some(f),
+				_ => abend!(format!("{} is not a function at {}.  Found: {}", instruction.literal.token_value, instruction.literal.location(), callee.var)),
+			}
+		}
+
 		//	grab the arguments
 		let mut arguments : Vec<CplVar> = Vec::new();
 
@@ -1054,21 +1266,77 @@ impl<'a> Executor<'a>{
 			arg_counter += 1;
 		}
 
+		let mut code_frame_num = instruction.block_num;
+		let mut is_builtin = instruction.opcode_mode == OpcodeMode::Builtin;
+		let mut capture_count = 0;
+		if let Some(ref function) = function{
+			code_frame_num = function.frame_num;
+			capture_count = function.captures.len();
+		}
+
+		//	Named arguments, defaults, spread arrays and a rest parameter mean the values pushed
+		//	aren't simply the arguments in parameter order.  If so, the qualifier says what
+		//	each value is
+		let parameters = &self.code_frames[code_frame_num].parameters;
+		let declared_parameters = parameters[..parameters.len() - capture_count].to_vec();
+		if instruction.qualifier.len() > 2 || declared_parameters.last().map_or(false, |p| p.starts_with("...")){
+			arguments.reverse();
+			arguments = self.bind_arguments(arguments, &instruction.qualifier[2..], &declared_parameters, code_frame_num, &instruction.literal.token_value);
+		}
+
+		//	The captured values of a function value are passed after the declared arguments
+		//	(i.e. ahead of them in the list, which is in reverse order)
+		if let Some(function) = function{
+			is_builtin = self.code_frames[code_frame_num].is_builtin;
+
+			//	the compiler didn't know which function it was calling so the defaults
+			//	of missing trailing arguments are filled in here
+			let declared_count = self.code_frames[code_frame_num].parameters.len() - function.captures.len();
+			while arguments.len() < declared_count{
+				match self.parameter_default(code_frame_num, arguments.len()){
+					Some(default) => arguments.insert(0, default),
+					None => break,
+				}
+			}
+			if arguments.len() != declared_count{
+				abend!(format!("Argument count ({}) doesn't match parameter count ({}) for function {} called via {} at {}"
+					, arguments.len(), declared_count, function.name, instruction.literal.token_value, instruction.literal.location()));
+			}
+
+			let mut all_arguments : Vec<CplVar> = function.captures.iter().rev().cloned().collect();
+			all_arguments.extend(arguments);
+			arguments = all_arguments;
+		}
+
 		//	Now, if the called function is a builtin function, call it directly (somehow),
 		//	otherwise we launch a new executor
-		if instruction.opcode_mode == OpcodeMode::Builtin{
+		if is_builtin{
 			//	Write the name of the built-in function being called to the runtime stats
 			//	qualifier
 			self.runtime_data_qual = instruction.literal.token_value.clone();
-			let rslt = (self.builtin_functions.builtin_function_list.get_mut(instruction.block_num).unwrap().target)(&mut self.builtin_functions, &arguments, &mut self.operand_stack);
+
+			//	Sort with a comparator has to call back into the program
+			if self.code_frames[code_frame_num].function_name == "Sort"{
+				if let CplDataType::CplFunction(compare) = self.operand_stack.dereference(&arguments[0]).var{
+					self.sort_with_comparator(&arguments[1], &compare, instruction);
+					return;
+				}
+			}
+
+			let rslt = (self.builtin_functions.builtin_function_list.get_mut(code_frame_num).unwrap().target)(&mut self.builtin_functions, &arguments, &mut self.operand_stack);
 			if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("      return from Builtin \"{}\" rslt={}", instruction.literal.token_value, rslt)}
+
+			//	A builtin that failed leaves an error message behind rather than a result
+			if let Some(message) = self.builtin_functions.error.take(){
+				let error = error_value(&format!("{}: {}", instruction.literal.token_value, message), &string_var(&message), &instruction.literal);
+				self.throw(error);
+				return;
+			}
 			self.operand_stack.push(&rslt);
 			return;
 		}
 
 
-		let code_frame_num = instruction.block_num;
-
 		let arguments_len = arguments.len();
 
 		//	instantiate a new exec object
@@ -1086,12 +1354,18 @@ impl<'a> Executor<'a>{
 		//	This is the actual call to the run function
 		let return_value = executor.exec();
 
+		//	If the called function didn't catch an error then throw it here
+		if let Some(error) = executor.exception.take(){
+			self.throw(error);
+			return;
+		}
+
 		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : Return from function call: {} return value {}", self.code_block_num, self.instruction_counter, instruction, return_value)}
 
 		//	If the call was from a statement (as opposed to a term in an expression)
 		if instruction.qualifier[1] == 0{
 			match return_value.var{
-				CplDataType::CplUninitialized(_) => abend!(format!("Call to {} did not return a value",instruction.literal.token_value)),
+				CplDataType::CplUninitialized(_) => abend!(format!("Call to {} at {} did not return a value", instruction.literal.token_value, instruction.literal.location())),
 				_ => {},
 			}
 		}
@@ -1099,6 +1373,280 @@ impl<'a> Executor<'a>{
 		self.operand_stack.push(&return_value);
 	}
 
+	//	Sort(&array, compare) where compare(a, b) returns a negative number if a goes before b,
+	//	0 if they're equal and a positive number if a goes after b.  It's a merge sort so equal
+	//	values keep their order and a compare that contradicts itself can't upset it
+	fn sort_with_comparator(&mut self, collection : &CplVar, compare : &CplFunction, instruction : &MachineInstruction){
+		let (frame_num, block_num, address) = match &collection.var{
+			CplDataType::CplVarRef(var_ref) => (var_ref.frame_num, var_ref.block_num, var_ref.address),
+			_ => return self.throw_builtin_error("Expecting parameter 1 to be a reference to an array (e.g. Sort(&array))", instruction),
+		};
+		let mut values = match &self.operand_stack.operand_frames[frame_num].operand_blocks[block_num].operand_block[address].var{
+			CplDataType::CplArray(array) => array.cpl_array.clone(),
+			_ => return self.throw_builtin_error("Expecting parameter 1 to be a reference to an array (e.g. Sort(&array))", instruction),
+		};
+		if self.code_frames[compare.frame_num].parameters.len() - compare.captures.len() != 2{
+			return self.throw_builtin_error(&format!("the compare function {} needs 2 parameters", compare.name), instruction);
+		}
+
+		let mut width = 1;
+		while width < values.len(){
+			let mut merged : Vec<CplVar> = Vec::with_capacity(values.len());
+			let mut start = 0;
+			while start < values.len(){
+				let middle = (start + width).min(values.len());
+				let end = (start + 2 * width).min(values.len());
+				let (mut i, mut j) = (start, middle);
+				while i < middle && j < end{
+					match self.call_comparator(compare, &values[i], &values[j], instruction){
+						Some(order) if order > 0.0 => {merged.push(values[j].clone()); j += 1;},
+						Some(_) => {merged.push(values[i].clone()); i += 1;},
+						None => return,
+					}
+				}
+				merged.extend_from_slice(&values[i..middle]);
+				merged.extend_from_slice(&values[j..end]);
+				start = end;
+			}
+			values = merged;
+			width *= 2;
+		}
+
+		if let CplDataType::CplArray(array) = &mut self.operand_stack.operand_frames[frame_num].operand_blocks[block_num].operand_block[address].var{
+			array.cpl_array = values;
+		}
+		self.operand_stack.push(&CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())));
+	}
+
+	//	Call the compare function of a sort.  If it throws (or doesn't return a number) the
+	//	error is thrown here and None is returned
+	fn call_comparator(&mut self, compare : &CplFunction, a : &CplVar, b : &CplVar, instruction : &MachineInstruction) -> Option<f64>{
+		//	the captures come first and the arguments are in reverse order
+		let mut arguments : Vec<CplVar> = compare.captures.iter().rev().cloned().collect();
+		arguments.push(b.clone());
+		arguments.push(a.clone());
+		let arguments_len = arguments.len();
+
+		let mut executor = Executor::call(self.cli
+			, self.code_frames
+			, compare.frame_num
+			, &mut arguments
+			, &mut self.operand_stack
+			, self.builtin_functions
+			, arguments_len
+			, &mut self.runtime_data
+			, self.names,
+		);
+		let return_value = executor.exec();
+		if let Some(error) = executor.exception.take(){
+			self.throw(error);
+			return None;
+		}
+
+		match return_value.var{
+			CplDataType::CplNumber(n) => Some(n.cpl_number),
+			_ => {
+				self.throw_builtin_error(&format!("the compare function {} returned {} rather than a number", compare.name, return_value), instruction);
+				None
+			},
+		}
+	}
+
+	//	An error found by the executor on behalf of a builtin is thrown like one the builtin found
+	fn throw_builtin_error(&mut self, message : &str, instruction : &MachineInstruction){
+		let error = error_value(&format!("{}: {}", instruction.literal.token_value, message), &string_var(message), &instruction.literal);
+		self.throw(error);
+	}
+
+	//	Put the values pushed for a call (in the order they were pushed) into parameter order
+	//	and return them as the argument list (which is in reverse order).  See ARG_POSITIONAL
+	//	for what the order list says about each value.  An empty order list means they're
+	//	all positional
+	fn bind_arguments(&mut self, values : Vec<CplVar>, order : &[usize], parameters : &Vec<String>, code_frame_num : usize, function_name : &str) -> Vec<CplVar>{
+		let parameter_name = |p : usize| parameters[p].trim_start_matches("...").splitn(2, ':').next().unwrap().to_string();
+		let mut rest_num : Option<usize> = None;
+		if parameters.last().map_or(false, |p| p.starts_with("...")){
+			rest_num = Some(parameters.len() - 1);
+		}
+		let positional_count = parameters.len() - rest_num.map_or(0, |_| 1);
+
+		//	spread the arrays first so that every value says where it goes
+		let mut kinds : Vec<(usize, CplVar)> = Vec::new();
+		for (pushed, value) in values.into_iter().enumerate(){
+			let kind = order.get(pushed).copied().unwrap_or(ARG_POSITIONAL);
+			if kind != ARG_SPREAD{
+				kinds.push((kind, value));
+				continue;
+			}
+
+			match self.operand_stack.dereference(&value).var{
+				CplDataType::CplArray(array) => {
+					for element in array.cpl_array{
+						kinds.push((ARG_POSITIONAL, element));
+					}
+				},
+				other => abend!(format!("Only an array can be spread into the arguments of {}.  Found: {}", function_name, other)),
+			}
+		}
+
+		let mut bound : Vec<Option<CplVar>> = vec!(None; parameters.len());
+		let mut rest = CplArray::new();
+		let mut next_positional = 0;
+		for (kind, value) in kinds{
+			if kind == ARG_POSITIONAL{
+				if next_positional < positional_count{
+					bound[next_positional] = Some(value);
+					next_positional += 1;
+				}else if rest_num.is_some(){
+					rest.push(&value);
+				}else{
+					abend!(format!("Too many arguments for function {} which has {} parameters", function_name, parameters.len()));
+				}
+			}else if kind >= ARG_DEFAULT{
+				if bound[kind - ARG_DEFAULT].is_none(){
+					bound[kind - ARG_DEFAULT] = Some(value);
+				}
+			}else if Some(kind) == rest_num{
+				rest.push(&value);
+			}else{
+				if bound[kind].is_some(){
+					abend!(format!("Parameter {} of {} is given more than one argument", parameter_name(kind), function_name));
+				}
+				bound[kind] = Some(value);
+			}
+		}
+
+		if let Some(rest_num) = rest_num{
+			bound[rest_num] = Some(CplVar::new(CplDataType::CplArray(rest)));
+		}
+
+		let mut arguments : Vec<CplVar> = Vec::new();
+		for (parameter_num, argument) in bound.into_iter().enumerate().rev(){
+			match argument.or_else(|| self.parameter_default(code_frame_num, parameter_num)){
+				Some(argument) => arguments.push(argument),
+				None => abend!(format!("There's no argument for parameter {} of {}", parameter_name(parameter_num), function_name)),
+			}
+		}
+		arguments
+	}
+
+	//	The value of a parameter's default (a literal), if it has one.  Calls the compiler
+	//	could bind push their defaults, this is for calls through a function value
+	fn parameter_default(&mut self, code_frame_num : usize, parameter_num : usize) -> Option<CplVar>{
+		let default = self.code_frames[code_frame_num].parameter_defaults.get(parameter_num).cloned().flatten()?;
+		if default.token_type == TokenType::ID{
+			abend!(format!("The default value of {} ({}) for function {} isn't a lit"
+				, self.code_frames[code_frame_num].parameters[parameter_num], default.token_value, self.code_frames[code_frame_num].function_name));
+		}
+
+		let instruction = MachineInstruction::new(Opcode::Push, OpcodeMode::Lit, code_frame_num, 0, 0, Vec::new(), 0, default);
+		self.push_lit(&instruction, 0);
+		Some(self.operand_stack.dereference_tos())
+	}
+
+	/******************************************************************
+	*** Exceptions
+	******************************************************************/
+
+	//	Register the catch block of a try statement.  The block_num and address
+	//	of the instruction are where the catch block returns to
+	fn exec_try(&mut self, instruction : &MachineInstruction){
+		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : exec_try: {}", self.code_block_num, self.instruction_counter, instruction)}
+
+		//	get rid of the catch blocks of try blocks that were left by break, continue, etc.
+		while let Some(handler) = self.try_info.last(){
+			if self.is_active_try(handler){
+				break;
+			}
+			self.try_info.pop();
+		}
+
+		self.try_info.push(TryInfo{
+			catch_block : instruction.qualifier[0],
+			return_block : instruction.block_num,
+			return_address : instruction.address,
+			depth : self.block_end_return_info.len(),
+			variable_count : self.operand_stack.variable_count(),
+		});
+	}
+
+	//	The try block finished normally
+	fn exec_end_try(&mut self, instruction : &MachineInstruction){
+		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : exec_end_try: {}", self.code_block_num, self.instruction_counter, instruction)}
+		self.try_info.pop();
+	}
+
+	//	Push the error value for the catch variable
+	fn exec_catch(&mut self, instruction : &MachineInstruction){
+		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : exec_catch: {} {}", self.code_block_num, self.instruction_counter, instruction, self.caught)}
+		let caught = std::mem::replace(&mut self.caught, CplVar::new(CplDataType::CplUninitialized(CplUninitialized::new())));
+		self.operand_stack.push(&caught);
+	}
+
+	//	Throw the value at the top of the stack.  Throwing an error that was caught
+	//	(i.e. rethrowing it) leaves it as it is
+	fn exec_throw(&mut self, instruction : &MachineInstruction){
+		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : exec_throw: {}", self.code_block_num, self.instruction_counter, instruction)}
+
+		let value = self.operand_stack.dereference_tos();
+		if let CplDataType::CplDict(ref d) = value.var{
+			if d.len() == 4 && d.contains_key(&CplKey::to_key(&string_var("message").var)) && d.contains_key(&CplKey::to_key(&string_var("file").var)){
+				self.throw(value.clone());
+				return;
+			}
+		}
+
+		let message = match value.var{
+			CplDataType::CplString(ref s) => s.cpl_string.clone(),
+			_ => format!("{}", value.var),
+		};
+		let error = error_value(&message, &value, &instruction.literal);
+		self.throw(error);
+	}
+
+	//	A try block is active if we're still inside it:  its Bl return info is
+	//	still on the stack
+	fn is_active_try(&self, handler : &TryInfo) -> bool{
+		match self.block_end_return_info.get(handler.depth){
+			Some(info) => info.1 == handler.return_block && info.2 + 1 == handler.return_address,
+			None => false,
+		}
+	}
+
+	//	Transfer control to the catch block of the innermost active try block.  The
+	//	blocks inside the try statement are removed just as if they ended and then
+	//	the catch block is entered as if by Bl.  If there isn't a try block, the
+	//	function ends and the error is left for the caller.
+	fn throw(&mut self, error : CplVar){
+		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : throw: {}", self.code_block_num, self.instruction_counter, error)}
+
+		while let Some(handler) = self.try_info.pop(){
+			if !self.is_active_try(&handler){
+				continue;
+			}
+
+			while self.block_end_return_info.len() > handler.depth{
+				self.block_end_return_info.pop();
+				self.break_info.pop();
+				self.operand_stack.pop_block();
+				self.block_counter -= 1;
+			}
+
+			//	get rid of anything left over from the expression that threw
+			self.operand_stack.truncate_block(handler.variable_count);
+
+			self.block_end_return_info.push((false, handler.return_block, handler.return_address));
+			self.break_info.push((false, 0, 0));
+			self.code_block_num = handler.catch_block;
+			self.instruction_counter = 0;
+			self.caught = error;
+			self.catching = true;
+			return;
+		}
+
+		self.exception = Some(error);
+	}
+
 	fn exec_return(&mut self, _instruction : &MachineInstruction){
 		//	get the operand at the top of the current frame
 		let operand = self.operand_stack.dereference_tos();
@@ -1108,11 +1656,34 @@ impl<'a> Executor<'a>{
 	}
 
 
-	//	Allocate (or reuse) a slot in the current block of the current frame.
+	//	Push a function value.  The block_num of the instruction is the frame of the
+	//	function and qualifier[0] the number of captured values on the operand stack
+	//	(pushed in the order of the extra parameters)
+	fn exec_closure(&mut self, instruction : &MachineInstruction){
+		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : exec_closure: {}", self.code_block_num, self.instruction_counter, instruction)}
+
+		let mut captures : Vec<CplVar> = Vec::new();
+		let mut capture_counter = 0;
+		while capture_counter < instruction.qualifier[0]{
+			captures.push(self.operand_stack.dereference_tos());
+			capture_counter += 1;
+		}
+		captures.reverse();
+
+		let function = CplFunction::new(instruction.block_num, &self.code_frames[instruction.block_num].function_name, captures);
+		self.operand_stack.push(&CplVar::new(CplDataType::CplFunction(function)));
+	}
+
+	//	Allocate (or reuse) a slot in the current block of the current frame or, if the
+	//	mode is GlobalVar, in the global frame.
 	fn exec_alloc(&mut self, instruction : &MachineInstruction){
 		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : exec_alloc: {} {} ({})", self.code_block_num, self.instruction_counter, instruction.block_num, instruction.address, self.names[instruction.interner])}
 		if self.cli.is_debug_bit(DUMP_OPERANDS){self.dump_operands("at exec_alloc");}
-		self.operand_stack.named_alloc(instruction.block_num, instruction.address, instruction.interner);
+		if instruction.opcode_mode == OpcodeMode::GlobalVar{
+			self.operand_stack.global_alloc(instruction.address, instruction.interner);
+		}else{
+			self.operand_stack.named_alloc(instruction.block_num, instruction.address, instruction.interner);
+		}
 	}
 
 	/******************************************************************
@@ -1304,17 +1875,13 @@ impl<'a> Executor<'a>{
 	*** Binary operators and support functions
 	******************************************************************/
 
-	fn perform_arithmetic_op(&mut self, v1 : f64, v2 : f64, op : Opcode) -> f64{
-		match op{
-			Opcode::Add=>{v1 + v2}
-			Opcode::Sub=>{v1 - v2}
-			Opcode::Mul=>{v1 * v2}
-			Opcode::Div=>{v1 / v2}
-			Opcode::Mod=>{(v1 as i32 % v2 as i32) as f64}
-			Opcode::BwAnd=>{(v1 as i32 & v2 as i32) as f64}
-			Opcode::BwOr=>{(v1 as i32 | v2 as i32) as f64}
-			_=> panic!("From perform_arithmetic_op: expecting a binary arithmetic operator.  Got {}",op),
-		}		
+	//	Push the result of an arithmetic operation.  Integer overflow and integer division
+	//	by zero are left in arithmetic_error for exec_binary_operator to throw
+	fn perform_arithmetic_op(&mut self, v1 : &CplNumber, v2 : &CplNumber, op : Opcode){
+		match v1.arithmetic(v2, op){
+			Ok(rslt) => self.operand_stack.push(&CplVar::new(CplDataType::CplNumber(rslt))),
+			Err(message) => self.arithmetic_error = Some(message),
+		}
 	}
 
 	fn perform_numeric_comparison_op(&mut self, v1 : f64, v2 : f64, op : Opcode) -> bool{
@@ -1403,7 +1970,16 @@ impl<'a> Executor<'a>{
 	fn compare_number_number(&mut self,tos1 : &CplVar,tos2 : &CplVar,opcode : Opcode){
 		if let CplDataType::CplNumber(ref v1) = tos1.var{
 			if let CplDataType::CplNumber(ref v2) = tos2.var{
-				let rslt = self.perform_numeric_comparison_op(v1.cpl_number, v2.cpl_number, opcode);
+				//	ints and decimals are compared exactly, anything else as reals
+				let rslt = match v1.compare(v2){
+					Ordering::Less		=> opcode == Opcode::Lt || opcode == Opcode::Le || opcode == Opcode::Ne,
+					Ordering::Greater	=> opcode == Opcode::Gt || opcode == Opcode::Ge || opcode == Opcode::Ne,
+					Ordering::Equal		=> if v1.is_exact() && v2.is_exact(){
+						opcode == Opcode::Le || opcode == Opcode::Ge || opcode == Opcode::Eq
+					}else{
+						self.perform_numeric_comparison_op(v1.cpl_number, v2.cpl_number, opcode)
+					},
+				};
 				self.operand_stack.push(&CplVar::new(CplDataType::CplBool(CplBool::new(rslt))));
 				return;
 			}
@@ -1487,20 +2063,31 @@ impl<'a> Executor<'a>{
 		self.operand_stack.push(&CplVar::new(CplDataType::CplBool(CplBool::new(false))));
 	}
 
+	//	dates are compared by the moment they're at (whatever their offsets)
+	fn compare_datetime_datetime(&mut self,tos1 : &CplVar,tos2 : &CplVar,opcode : Opcode){
+		if let CplDataType::CplDateTime(ref v1) = tos1.var{
+			if let CplDataType::CplDateTime(ref v2) = tos2.var{
+				let rslt = self.perform_numeric_comparison_op(v1.seconds as f64, v2.seconds as f64, opcode);
+				self.operand_stack.push(&CplVar::new(CplDataType::CplBool(CplBool::new(rslt))));
+				return;
+			}
+		}
+		self.operand_stack.push(&CplVar::new(CplDataType::CplBool(CplBool::new(false))));
+	}
+
 	fn do_op_string_string(&mut self,tos1 : &CplVar,tos2 : &CplVar,opcode : Opcode){
 		//	If both strings can be converted to numbers, then do_op_number_number, otherwise
 		//	it's only "." works  But if the opcode is "." then this only works with strings
 		if let CplDataType::CplString(ref v1) = tos1.var{
 			if let CplDataType::CplString(ref v2) = tos2.var{
 				if opcode != Opcode::Concat{
-					match v1.cpl_string.parse::<f64>() {
-						Err(_) => {},
-						Ok (parsed1) => {
-							match v2.cpl_string.parse::<f64>() {
-								Err(_) => {},
-								Ok (parsed2) => {
-									let rslt = self.perform_arithmetic_op(parsed1, parsed2, opcode);
-									self.operand_stack.push(&CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Real, rslt))));
+					match CplNumber::parse(&v1.cpl_string) {
+						None => {},
+						Some (parsed1) => {
+							match CplNumber::parse(&v2.cpl_string) {
+								None => {},
+								Some (parsed2) => {
+									self.perform_arithmetic_op(&parsed1, &parsed2, opcode);
 									return;
 								}
 							}
@@ -1528,17 +2115,18 @@ impl<'a> Executor<'a>{
 		
 		if let CplDataType::CplString(ref v1) = tos1.var{
 			if let CplDataType::CplNumber(ref v2) = tos2.var{
-				match v1.cpl_string.parse::<f64>(){
-					Err(_) => if opcode == Opcode::Concat || opcode == Opcode::Add{
+				//	"." joins the strings even if the string looks like a number
+				let parsed = if opcode == Opcode::Concat {None} else {CplNumber::parse(&v1.cpl_string)};
+				match parsed{
+					None => if opcode == Opcode::Concat || opcode == Opcode::Add{
 						let mut rslt = String::new();
 						rslt.push_str(&v1.cpl_string);
-						rslt.push_str(&v2.cpl_number.to_string());
+						rslt.push_str(&v2.to_string());
 						self.operand_stack.push(&CplVar::new(CplDataType::CplString(CplString::new(rslt))));
 					},
 				
-					Ok(parsed) => {
-						let rslt = self.perform_arithmetic_op(parsed, v2.cpl_number, opcode);
-						self.operand_stack.push(&CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Real, rslt))));
+					Some(parsed) => {
+						self.perform_arithmetic_op(&parsed, v2, opcode);
 					}
 				}
 				return;
@@ -1566,17 +2154,17 @@ impl<'a> Executor<'a>{
 		//	same as string_number
 		if let CplDataType::CplString(ref v2) = tos2.var{
 			if let CplDataType::CplNumber(ref v1) = tos1.var{
-				match v2.cpl_string.parse::<f64>(){
-					Err(_) => if opcode == Opcode::Concat || opcode == Opcode::Add{
+				let parsed = if opcode == Opcode::Concat {None} else {CplNumber::parse(&v2.cpl_string)};
+				match parsed{
+					None => if opcode == Opcode::Concat || opcode == Opcode::Add{
 						let mut rslt = String::new();
-						rslt.push_str(&v1.cpl_number.to_string());
+						rslt.push_str(&v1.to_string());
 						rslt.push_str(&v2.cpl_string);
 						self.operand_stack.push(&CplVar::new(CplDataType::CplString(CplString::new(rslt))));
 					},
 				
-					Ok(parsed) => {
-						let rslt = self.perform_arithmetic_op(parsed, v1.cpl_number, opcode);
-						self.operand_stack.push(&CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Real, rslt))));
+					Some(parsed) => {
+						self.perform_arithmetic_op(&parsed, v1, opcode);
 					}
 				}
 				return;
@@ -1588,8 +2176,13 @@ impl<'a> Executor<'a>{
 	fn do_op_number_number(&mut self,tos1 : &CplVar,tos2 : &CplVar,opcode : Opcode){
 		if let CplDataType::CplNumber(ref v2) = tos2.var{
 			if let CplDataType::CplNumber(ref v1) = tos1.var{
-				let rslt = self.perform_arithmetic_op(v1.cpl_number, v2.cpl_number, opcode);
-				self.operand_stack.push(&CplVar::new(CplDataType::CplNumber(CplNumber::new(RustDataType::Real, rslt))));
+				//	"." makes a string of the two numbers
+				if opcode == Opcode::Concat{
+					let rslt = format!("{}{}", v1, v2);
+					self.operand_stack.push(&CplVar::new(CplDataType::CplString(CplString::new(rslt))));
+					return;
+				}
+				self.perform_arithmetic_op(v1, v2, opcode);
 				return;
 			}
 		}
@@ -1655,6 +2248,59 @@ impl<'a> Executor<'a>{
 		panic!("from do_op_array_array: Invalid expression {} {} {}",tos1,opcode,tos2);
 	}
 
+	fn do_op_datetime_datetime(&mut self,tos1 : &CplVar,tos2 : &CplVar,opcode : Opcode){
+		//	the difference between two dates is in seconds
+		if opcode != Opcode::Sub {
+			return self.invalid_datetime_op(tos1, tos2, opcode);
+		}
+		if let CplDataType::CplDateTime(ref v1) = tos1.var{
+			if let CplDataType::CplDateTime(ref v2) = tos2.var{
+				match v1.seconds.checked_sub(v2.seconds){
+					Some(difference) => self.operand_stack.push(&CplVar::new(CplDataType::CplNumber(CplNumber::new_int(difference)))),
+					None => self.arithmetic_error = Some(format!("date overflow: {} - {}", v1, v2)),
+				}
+			}
+		}
+	}
+
+	fn do_op_datetime_number(&mut self,tos1 : &CplVar,tos2 : &CplVar,opcode : Opcode){
+		//	date + seconds or date - seconds
+		if opcode != Opcode::Add && opcode != Opcode::Sub {
+			return self.invalid_datetime_op(tos1, tos2, opcode);
+		}
+		if let CplDataType::CplDateTime(ref v1) = tos1.var{
+			if let CplDataType::CplNumber(ref v2) = tos2.var{
+				let seconds = if opcode == Opcode::Add {Some(v2.as_int())} else {v2.as_int().checked_neg()};
+				match seconds.and_then(|seconds| v1.add_seconds(seconds)){
+					Some(date) => self.operand_stack.push(&CplVar::new(CplDataType::CplDateTime(date))),
+					None => self.arithmetic_error = Some(format!("date overflow: {} {} {}", v1, operator_symbol(opcode), tos2)),
+				}
+			}
+		}
+	}
+
+	fn do_op_datetime_string(&mut self,tos1 : &CplVar,tos2 : &CplVar,opcode : Opcode){
+		//	concat (or +) makes a string of the date and the string
+		if opcode != Opcode::Concat && opcode != Opcode::Add {
+			return self.invalid_datetime_op(tos1, tos2, opcode);
+		}
+		let rslt = format!("{}{}", tos1, tos2);
+		self.operand_stack.push(&CplVar::new(CplDataType::CplString(CplString::new(rslt))));
+	}
+
+	fn do_op_number_datetime(&mut self,tos1 : &CplVar,tos2 : &CplVar,opcode : Opcode){
+		//	seconds + date is date + seconds.  seconds - date means nothing
+		if opcode != Opcode::Add {
+			return self.invalid_datetime_op(tos1, tos2, opcode);
+		}
+		self.do_op_datetime_number(tos2, tos1, opcode);
+	}
+
+	//	Any other operation with a date is a (catchable) runtime error rather than an abend
+	fn invalid_datetime_op(&mut self,tos1 : &CplVar,tos2 : &CplVar,opcode : Opcode){
+		self.arithmetic_error = Some(format!("invalid date operation: {} {} {}", tos1, operator_symbol(opcode), tos2));
+	}
+
 
 	fn operand_eval(&self, operand1 : &CplVar, operand2 : &CplVar) -> OperandAnalysis{		
 		let t1 = match operand1.var{
@@ -1662,6 +2308,7 @@ impl<'a> Executor<'a>{
 			CplDataType::CplNumber(_) => OperandType::OtNumber,
 			CplDataType::CplBool(_) => OperandType::OtBool,
 			CplDataType::CplArray(_) => OperandType::OtArray,
+			CplDataType::CplDateTime(_) => OperandType::OtDateTime,
 			_ => return OperandAnalysis::InvalidType,
 		};
 
@@ -1670,9 +2317,31 @@ impl<'a> Executor<'a>{
 			CplDataType::CplNumber(_) => OperandType::OtNumber,
 			CplDataType::CplBool(_) => OperandType::OtBool,
 			CplDataType::CplArray(_) => OperandType::OtArray,
+			CplDataType::CplDateTime(_) => OperandType::OtDateTime,
 			_ => return OperandAnalysis::InvalidType,
 		};
 
+		//	a date can be compared with a date, have a number of seconds added or subtracted
+		//	and be concatenated with a string
+		if t1==OperandType::OtDateTime && t2==OperandType::OtDateTime{
+			return OperandAnalysis::DateTimeDateTime;
+		}
+		if t1==OperandType::OtDateTime && t2==OperandType::OtNumber{
+			return OperandAnalysis::DateTimeNumber;
+		}
+		if t1==OperandType::OtDateTime && t2==OperandType::OtString{
+			return OperandAnalysis::DateTimeString;
+		}
+		if t1==OperandType::OtString && t2==OperandType::OtDateTime{
+			return OperandAnalysis::StringDateTime;
+		}
+		if t1==OperandType::OtNumber && t2==OperandType::OtDateTime{
+			return OperandAnalysis::NumberDateTime;
+		}
+		if t1==OperandType::OtDateTime || t2==OperandType::OtDateTime{
+			return OperandAnalysis::DateTimeInvalid;
+		}
+
 		if t1==OperandType::OtString && t2==OperandType::OtString{
 			return OperandAnalysis::StringString;
 		}
@@ -1739,6 +2408,29 @@ impl<'a> Executor<'a>{
 
 		if self.cli.is_debug_bit(TRACE_EXEC){eprintln!("{}:{} : exec_binary_operator({}): (tos1={} tos2={})", self.code_block_num, self.instruction_counter, instruction.opcode, tos1.dbg(), tos2.dbg())}
 
+		//	membership works on collections which operand_eval doesn't classify
+		if instruction.opcode == Opcode::In || instruction.opcode == Opcode::NotIn{
+			let found = self.is_member(&tos1, &tos2);
+			self.operand_stack.push(&CplVar::new(CplDataType::CplBool(CplBool::new(found == (instruction.opcode == Opcode::In)))));
+			return;
+		}
+
+		//	when /<regex>/:  tos1 is the eval target, tos2 the regex.  The target is matched as a string
+		if instruction.opcode == Opcode::RegexMatch{
+			let haystack = match tos1.var{
+				CplDataType::CplString(ref s) => s.cpl_string.clone(),
+				_ => format!("{}", tos1.var),
+			};
+			match self.builtin_functions.regex_is_match(&haystack, &tos2.as_string()){
+				Ok(is_match) => self.operand_stack.push(&CplVar::new(CplDataType::CplBool(CplBool::new(is_match)))),
+				Err(message) => {
+					let error = error_value(&message, &tos2, &instruction.literal);
+					self.throw(error);
+				},
+			}
+			return;
+		}
+
 		let eval = self.operand_eval(&tos1, &tos2);
 		match instruction.opcode{
 			Opcode::Lt | Opcode::Gt | Opcode::Le | Opcode::Ge | Opcode::Eq | Opcode::Ne => {
@@ -1752,13 +2444,20 @@ impl<'a> Executor<'a>{
 					OperandAnalysis::BoolString			=>	self.compare_bool_string(&tos1,&tos2,instruction.opcode),
 					OperandAnalysis::BoolNumber			=>	self.compare_bool_number(&tos1,&tos2,instruction.opcode),
 					OperandAnalysis::BoolBool			=>	self.compare_bool_bool(&tos1,&tos2,instruction.opcode),
+					OperandAnalysis::DateTimeDateTime	=>	self.compare_datetime_datetime(&tos1,&tos2,instruction.opcode),
+					OperandAnalysis::DateTimeNumber		|
+					OperandAnalysis::NumberDateTime		|
+					OperandAnalysis::DateTimeString		|
+					OperandAnalysis::StringDateTime		|
+					OperandAnalysis::DateTimeInvalid	=>	self.invalid_datetime_op(&tos1,&tos2,instruction.opcode),
 					_=> if self.cli.is_runtime_warnings(){
 						eprintln!("WARNING from exec_binary_operator: {}{}{} is invalid.  returning false", tos1, instruction.opcode, tos2);
 						self.operand_stack.push(&CplVar::new(CplDataType::CplBool(CplBool::new(false))));
 					}
 				}		
 			}
-			Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div | Opcode::Mod | Opcode::Concat | Opcode::BwAnd | Opcode::BwOr =>{
+			Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div | Opcode::IntDiv | Opcode::Mod | Opcode::Pow | Opcode::Concat
+				| Opcode::BwAnd | Opcode::BwOr | Opcode::BwXor | Opcode::Shl | Opcode::Shr =>{
 				match eval{
 					OperandAnalysis::StringString 		=>	self.do_op_string_string(&tos1,&tos2,instruction.opcode),
 					OperandAnalysis::StringNumber		=>	self.do_op_string_number(&tos1,&tos2,instruction.opcode),
@@ -1779,29 +2478,60 @@ impl<'a> Executor<'a>{
 					OperandAnalysis::ArrayNumber		=>	self.do_op_array_number(&tos1,&tos2,instruction.opcode),
 					OperandAnalysis::ArrayBool			=>	self.do_op_array_bool(&tos1,&tos2,instruction.opcode),
 					OperandAnalysis::ArrayArray			=>	self.do_op_array_array(&tos1,&tos2,instruction.opcode),
+
+					OperandAnalysis::DateTimeDateTime	=>	self.do_op_datetime_datetime(&tos1,&tos2,instruction.opcode),
+					OperandAnalysis::DateTimeNumber		=>	self.do_op_datetime_number(&tos1,&tos2,instruction.opcode),
+					OperandAnalysis::DateTimeString		|
+					OperandAnalysis::StringDateTime		=>	self.do_op_datetime_string(&tos1,&tos2,instruction.opcode),
+					OperandAnalysis::NumberDateTime		=>	self.do_op_number_datetime(&tos1,&tos2,instruction.opcode),
+					OperandAnalysis::DateTimeInvalid	=>	self.invalid_datetime_op(&tos1,&tos2,instruction.opcode),
 					_=> panic!("from exec_binary_operator: {}{}{} is invalid", tos1, instruction.opcode, tos2),
 				}		
 			}
 			_ => panic!("from exec_binary_operator:  I can't do anything with this opcode: {}",instruction.opcode),
 
 		}
+
+		if let Some(message) = self.arithmetic_error.take(){
+			let error = error_value(&message, &tos2, &instruction.literal);
+			self.throw(error);
+		}
 	}
 
 
+	//	x in collection:  for arrays, is there an element equal to x;  for dictionaries,
+	//	is x a key;  for strings, is x a substring
+	fn is_member(&self, item : &CplVar, collection : &CplVar) -> bool{
+		match collection.var{
+			CplDataType::CplArray(ref a) => a.cpl_array.iter().any(|e| e.is_equal(item, false)),
+			CplDataType::CplDict(ref d) => match item.var{
+				CplDataType::CplNumber(_) | CplDataType::CplString(_) | CplDataType::CplBool(_) => d.contains_key(&CplKey::to_key(&item.var)),
+				_ => false,
+			},
+			CplDataType::CplString(ref s) => match item.var{
+				CplDataType::CplString(ref sub) => s.cpl_string.contains(sub.cpl_string.as_str()),
+				CplDataType::CplNumber(_) | CplDataType::CplBool(_) => s.cpl_string.contains(item.to_string().as_str()),
+				_ => false,
+			},
+			_ => {
+				if self.cli.is_runtime_warnings(){
+					eprintln!("WARNING from exec_binary_operator: {} in {} is invalid.  returning false", item, collection);
+				}
+				false
+			},
+		}
+	}
+
+	//	'&&' and '||' are short circuited using Jf/Jt (see CodeGen::gen_expression_short_circuit_test)
+	//	so all that's left for these opcodes is making sure each operand is a bool.  The operand
+	//	is left on the stack for the jump (left operand) or as the result (right operand)
 	fn exec_lor_land(&mut self, instruction : &MachineInstruction){
-		let tos2 = self.operand_stack.dereference_tos();
-		let tos1 = self.operand_stack.dereference_tos();
+		let tos = self.operand_stack.dereference_tos();
 
-		if let CplDataType::CplBool(ref b1) = tos1.var{
-			if let CplDataType::CplBool(ref b2) = tos2.var{
-				if instruction.opcode == Opcode::Lor {
-					self.operand_stack.push(&CplVar::new(CplDataType::CplBool(CplBool::new(b1.cpl_bool || b2.cpl_bool))));
-				} else if instruction.opcode == Opcode::Land {
-					self.operand_stack.push(&CplVar::new(CplDataType::CplBool(CplBool::new(b1.cpl_bool && b2.cpl_bool))));
-				}else{
-					panic!("from exec_loc_land: Expending '||' or '&&' got: {}",instruction.opcode)
-				}
-			}
+		if let CplDataType::CplBool(_) = tos.var{
+			self.operand_stack.push(&tos);
+		}else{
+			abend!(format!("Operands of {} must be true or false.  Found: {} at {}", instruction.literal.token_value, tos, instruction.literal.location()));
 		}
 	}
 
@@ -1817,22 +2547,32 @@ impl<'a> Executor<'a>{
 		}
 	}
 
-	fn foreach_index_check(&self, index : usize, collection_block_num : usize, collection_address : usize) -> bool{
-		let collection_ref = self.operand_stack.fetch_local_ref(collection_block_num, collection_address);
-		if let CplDataType::CplArray(ref collection) = collection_ref.var{
-			if index >= collection.cpl_array.len(){
-				return false;
-			}else{
-				return true;
-			}
+	//	The array a foreach is looping through.  An array passed to a function (or
+	//	copied from a parameter) is a VarRef
+	fn foreach_collection(&self, collection_frame_num : usize, collection_block_num : usize, collection_address : usize) -> Option<&CplArray>{
+		let mut collection_ref = self.operand_stack.fetch_ref(collection_frame_num, collection_block_num, collection_address);
+		if let CplDataType::CplVarRef(ref vr) = collection_ref.var{
+			collection_ref = self.operand_stack.fetch_ref(vr.frame_num, vr.block_num, vr.address);
+		}
+
+		match collection_ref.var{
+			CplDataType::CplArray(ref collection) => Some(collection),
+			_ => None,
+		}
+	}
+
+	fn foreach_index_check(&self, index : usize, collection_frame_num : usize, collection_block_num : usize, collection_address : usize) -> bool{
+		match self.foreach_collection(collection_frame_num, collection_block_num, collection_address){
+			Some(collection) => index < collection.cpl_array.len(),
+			None => false,
 		}
-		return false;
 	}
 
 	fn foreach_index_increment (&mut self, index_block_num : usize, index_address : usize){
 		match self.operand_stack.fetch_local_mutable_ref(index_block_num, index_address).var{
+			//	the index can't get anywhere near overflowing
 			CplDataType::CplNumber(ref mut n) => {
-				n.cpl_number += 1.0;
+				let _ = n.apply_unary_operator(Opcode::Inc);
 			}
 			_ => {}
 		}
@@ -1840,6 +2580,7 @@ impl<'a> Executor<'a>{
 
 	fn foreach_target_update (&mut self
 			, index : usize
+			, collection_frame_num : usize
 			, collection_block_num : usize
 			, collection_address : usize
 			, target_block_num : usize
@@ -1847,7 +2588,10 @@ impl<'a> Executor<'a>{
 			)
 			{
 
-		let collection_element = self.operand_stack.fetch_indexed(index, collection_block_num, collection_address);
+		let collection_element = match self.foreach_collection(collection_frame_num, collection_block_num, collection_address){
+			Some(collection) => collection.cpl_array[index].clone(),
+			None => panic!("from exec_foreach: expecting an array at {},{},{}", collection_frame_num, collection_block_num, collection_address),
+		};
 		self.operand_stack.update_local(&collection_element, target_block_num, target_address);
 	}
 
@@ -1874,15 +2618,18 @@ impl<'a> Executor<'a>{
 		let collection_address = instruction.qualifier[4];
 		let loop_exit_address = instruction.qualifier[0];
 
+		//	a global collection is in the global frame, anything else is local
+		let collection_frame_num = if instruction.opcode_mode == OpcodeMode::GlobalVar {GLOBAL_FRAME} else {self.operand_stack.current_frame()};
+
 		let index = self.foreach_index (index_block_num, index_address);
-		if !self.foreach_index_check(index, collection_block_num, collection_address){
+		if !self.foreach_index_check(index, collection_frame_num, collection_block_num, collection_address){
 			self.instruction_counter = loop_exit_address;
 			return;
 		}
 
 		self.foreach_index_increment(index_block_num, index_address);
 
-		self.foreach_target_update(index, collection_block_num, collection_address, target_block_num, target_address);
+		self.foreach_target_update(index, collection_frame_num, collection_block_num, collection_address, target_block_num, target_address);
 
 		//	since the executor won't do this for foreach instructinos
 		self.instruction_counter += 1;
@@ -8,6 +8,7 @@ const REL_OP_PRECEDENCE : i32 = 1;
 const ADD_OP_PRECEDENCE : i32 = 5;
 const MUL_OP_PRECEDENCE : i32 = 6;
 const UNARY_OP_PRECEDENCE : i32 = 7;
+const POWER_OP_PRECEDENCE : i32 = 7;
 const FUNCTION_CALL_PRECEDENCE : i32 = 8;
 const INDEXED_ID_PRECEDENCE : i32 = 8;
 const FACTOR_PRECEDENCE : i32 = 5;
@@ -33,6 +34,7 @@ const ARG_SEPARATOR_PRECEDENCE : i32 = 1;
 const LOGICAL_PRECEDENCE : i32 = 5;
 
 const OP_UP_PRECEDENCE : i32 = 1;
+const TERNARY_PRECEDENCE : i32 = 0;
 
 const _UNKNOWN_PRECEDENCE : i32 = 0;
 const _NO_PRECEDENCE : i32 = 0;
@@ -70,18 +72,25 @@ impl <'a> InfixToPostfix<'a>{
 					(TokenType::NE,COMP_OP_PRECEDENCE),
 					(TokenType::GT,COMP_OP_PRECEDENCE),
 					(TokenType::LT,COMP_OP_PRECEDENCE),
+					(TokenType::IN,COMP_OP_PRECEDENCE),
+					(TokenType::NOT_IN,COMP_OP_PRECEDENCE),
 				
 					(TokenType::LOR,REL_OP_PRECEDENCE),
 					(TokenType::LAND,REL_OP_PRECEDENCE),
+
+					(TokenType::QUESTION,TERNARY_PRECEDENCE),
+					(TokenType::COLON,TERNARY_PRECEDENCE),
 				
 					(TokenType::BITWISE_OR,ADD_OP_PRECEDENCE),
 					(TokenType::BITWISE_AND,ADD_OP_PRECEDENCE),
+					(TokenType::BITWISE_XOR,ADD_OP_PRECEDENCE),
 				
 					(TokenType::PP,UNARY_OP_PRECEDENCE),
 					(TokenType::MM,UNARY_OP_PRECEDENCE),
 					(TokenType::UMINUS,UNARY_OP_PRECEDENCE),
 					(TokenType::UPLUS,UNARY_OP_PRECEDENCE),				
 					(TokenType::DAMNIT,UNARY_OP_PRECEDENCE),
+					(TokenType::BITWISE_NOT,UNARY_OP_PRECEDENCE),
 					(TokenType::LENGTH_OF,UNARY_OP_PRECEDENCE),
 
 				
@@ -90,7 +99,11 @@ impl <'a> InfixToPostfix<'a>{
 				
 					(TokenType::MUL,MUL_OP_PRECEDENCE),
 					(TokenType::DIV,MUL_OP_PRECEDENCE),
+					(TokenType::INT_DIV,MUL_OP_PRECEDENCE),
 					(TokenType::MOD,MUL_OP_PRECEDENCE),
+					(TokenType::SHIFT_LEFT,MUL_OP_PRECEDENCE),
+					(TokenType::SHIFT_RIGHT,MUL_OP_PRECEDENCE),
+					(TokenType::POWER,POWER_OP_PRECEDENCE),
 					(TokenType::CONCAT,ADD_OP_PRECEDENCE),
 			
 					(TokenType::LPAREN,LPAREN_PRECEDENCE),
@@ -117,6 +130,7 @@ impl <'a> InfixToPostfix<'a>{
 					(TokenType::STRING,FACTOR_PRECEDENCE),			// quoted with either ' or "
 					(TokenType::INTEGER,FACTOR_PRECEDENCE),
 					(TokenType::FLOAT,FACTOR_PRECEDENCE),
+					(TokenType::DECIMAL,FACTOR_PRECEDENCE),
 					(TokenType::BOOL,FACTOR_PRECEDENCE),
 				
 					(TokenType::ID,FACTOR_PRECEDENCE),
@@ -140,6 +154,15 @@ impl <'a> InfixToPostfix<'a>{
 
 		for t in infix{
 			self.i_to_p(t, &mut postfix, &mut operators);
+
+			//	'&&' and '||' short circuit so code generation needs to know where the
+			//	left operand ends.  At this point it is complete in the postfix expression
+			if t.token_type == TokenType::LAND || t.token_type == TokenType::LOR{
+				let mut test = t.clone();
+				test.token_type = if t.token_type == TokenType::LAND {TokenType::LAND_TEST} else {TokenType::LOR_TEST};
+				test.token_category = TokenCategory::Misc;
+				postfix.push(test);
+			}
 			if self.cli.is_debug_bit(TRACE_INFIX_TO_POSTFIX_DRIVER){
 				eprintln!("i_to_p:  --LAST Token: {} (\"{}\")\tpostfix={}\toperators={}",t.token_type, t.token_value, self.token_list_text(&mut postfix), self.token_list_text(&mut operators));
 			}
@@ -160,6 +183,11 @@ impl <'a> InfixToPostfix<'a>{
 
 	fn i_to_p (&mut self, token : &Token, postfix : &mut Vec<Token>, operators : &mut Vec<Token>){
 
+		if token.token_type == TokenType::QUESTION || token.token_type == TokenType::COLON{
+			self.move_ternary(token, postfix, operators);
+			return;
+		}
+
 		//  Operands don't move.  That is, they added to the postfix list
 		//  as soon as they are seen.
 		match token.token_category{
@@ -210,7 +238,11 @@ impl <'a> InfixToPostfix<'a>{
 				if self.cli.is_debug_bit(TRACE_INFIX_TO_POSTFIX){
 					eprintln!("i_to_p:  RParen move: {}\tpostfix={}\toperators={}",token.token_value, self.token_list_text(postfix), self.token_list_text(operators));
 				}
-				self.clear_operator_stack(postfix, operators);
+				//	only the operators inside the parentheses are moved
+				self.clear_operator_stack_with_stop(TokenCategory::LParen, postfix, operators);
+				if !operators.is_empty() && operators.last().unwrap().token_type == TokenType::LPAREN{
+					operators.pop();
+				}
 				return;
 			},
 
@@ -283,14 +315,20 @@ impl <'a> InfixToPostfix<'a>{
 		//
 		//  Unless the token on the stack is RINDEX in which case the input is moved
 		//
-		if self.token_prec(token) <= self.token_prec(operators.last().unwrap()){
+		if !self.outranks(token, operators.last().unwrap()){
 			//let last = operators.len() - 1;
 			if self.cli.is_debug_bit(TRACE_INFIX_TO_POSTFIX){
 				eprintln!("i_to_p: token='{}'({}) <= operator stack tos: {}", token.token_value, self.token_prec(&token), self.token_list_text(operators));
 			}
 				
-			while !operators.is_empty() && self.token_prec(&token) <= self.token_prec(operators.last().unwrap()){
+			while !operators.is_empty() && !self.outranks(&token, operators.last().unwrap()){
 			
+				//	'&&' and '||' have a lower precedence than the parentheses so don't let
+				//	them reach outside a parenthesized expression or a function call's argument list
+				if operators.last().unwrap().token_type == TokenType::LPAREN || operators.last().unwrap().token_type == TokenType::LPAREN_ARG{
+					break;
+				}
+
 				//	The input token < TOS so we want to move the tos to the postfix expression
 				//	but, if TOS is a parenthesis we just kill it and try again
 				let operator_token = operators.pop().unwrap();
@@ -362,8 +400,8 @@ impl <'a> InfixToPostfix<'a>{
 		//	get the last character
 		let last_char = up.token_value.chars().nth(up_loc).unwrap();
 
-		//	if the last character isn't a ^ then do nothing
-		if last_char != '^'{
+		//	if the last character isn't a ^ (or it's just the '^' operator) then do nothing
+		if last_char != '^' || up.token_value == "^"{
 			return;
 		}
 
@@ -376,15 +414,26 @@ impl <'a> InfixToPostfix<'a>{
 	}
 
 	//	If the parmameter token has "^" appended to the value return true
-	//	else return false
+	//	else return false.  The '^' operator is only <op>^ once it's "^^"
 	fn is_op_up(&self, token : &Token) -> bool{
 		let last = token.token_value.len() - 1;
-		if token.token_value.chars().nth(last).unwrap() == '^'{
+		if token.token_value.chars().nth(last).unwrap() == '^' && token.token_value != "^"{
 			return true;
 		}
 		return false;
 	}
 
+	//	true if token is applied before tos.  '**' and the unary operators group to the
+	//	right (2 ** 3 ** 2 is 2 ** 9 and 2 ** -1 is 2 ** (-1)) so they also outrank an
+	//	operator of the same precedence
+	fn outranks(&self, token : &Token, tos : &Token) -> bool{
+		let (token_prec, tos_prec) = (self.token_prec(token), self.token_prec(tos));
+		if token.token_type == TokenType::POWER || token.token_category == TokenCategory::UnaryOp{
+			return token_prec >= tos_prec;
+		}
+		token_prec > tos_prec
+	}
+
 	fn token_prec (&self, token : &Token) -> i32{
 		// eprintln!(".... token_prec {}", token.token_type);
 		if let TokenType::FUNCTION_CALL(_) = token.token_type{
@@ -452,7 +501,10 @@ impl <'a> InfixToPostfix<'a>{
 			return;
 		}
 
-		if self.token_prec(token_to_move) > self.token_prec(operators.last().unwrap()){
+		//	(parentheses always stay put, '&&' and '||' don't outrank them)
+		if self.outranks(token_to_move, operators.last().unwrap())
+		|| operators.last().unwrap().token_type == TokenType::LPAREN
+		|| operators.last().unwrap().token_type == TokenType::LPAREN_ARG{
 			self.trace_infix_to_postfix_moving(&token_to_move, "  Moving", TRACE_INFIX_TO_POSTFIX, postfix, line!());
 			postfix.push(token_to_move.clone());
 			return;
@@ -467,6 +519,46 @@ impl <'a> InfixToPostfix<'a>{
 		operators.push(token_to_move.clone());
 	}
 
+	//	cond ? a : b
+	//
+	//	'?' ends the condition and ':' ends the first choice.  Each moves the operators of
+	//	the part it ends and marks the spot in the postfix expression (TERNARY_TEST and
+	//	TERNARY_ELSE) for code generation.  The ':' replaces its '?' on the operator stack
+	//	and, when it's moved, marks the end of the second choice.  A '?' doesn't move a
+	//	pending ':' so a ? b : c ? d : e is a ? b : (c ? d : e)
+	fn move_ternary(&mut self, token : &Token, postfix : &mut Vec<Token>, operators : &mut Vec<Token>){
+		while !operators.is_empty(){
+			let tos = operators.last().unwrap();
+			if tos.token_type == TokenType::LPAREN
+			|| tos.token_type == TokenType::LPAREN_ARG
+			|| tos.token_type == TokenType::QUESTION
+			|| (tos.token_type == TokenType::COLON && token.token_type == TokenType::QUESTION)
+			|| self.is_op_up(tos){
+				break;
+			}
+			let operator_token = operators.pop().unwrap();
+			self.move_operator(&operator_token, postfix);
+		}
+
+		let mut marker = token.clone();
+		marker.token_category = TokenCategory::Misc;
+		if token.token_type == TokenType::QUESTION{
+			marker.token_type = TokenType::TERNARY_TEST;
+		}else{
+			if operators.is_empty() || operators.last().unwrap().token_type != TokenType::QUESTION{
+				abend!(format!("Found ':' without a '?' at line {}", token.line_number));
+			}
+			operators.pop();
+			marker.token_type = TokenType::TERNARY_ELSE;
+		}
+		operators.push(token.clone());
+		postfix.push(marker);
+
+		if self.cli.is_debug_bit(TRACE_INFIX_TO_POSTFIX){
+			eprintln!("i_to_p:  move_ternary: {}\tpostfix={}\toperators={}", token.token_value, self.token_list_text(postfix), self.token_list_text(operators));
+		}
+	}
+
 	//	Special Function Call Case A:  input is ARG_SEPARATOR (',').  Move everything from operators TOS down
 	//	to, but not including LPAREN_ARG.  If there is nothing to move just return.
 	fn special_case_a(&mut self, trigger : &Token, postfix : &mut Vec<Token>, operators : &mut Vec<Token> ){
@@ -475,9 +567,9 @@ impl <'a> InfixToPostfix<'a>{
 			return;
 		}
 
-		//	Move until operators are empty (and if it goes empty then we have another
-		//	error because the separator should be part of a function call which should
-		//	still be on the stack)
+		//	Move until operators are empty.  The arguments of a function call statement
+		//	(e.g. foo(a . b, c);) aren't preceded by an LPAREN_ARG so, for them, the
+		//	separator ends the argument when the stack goes empty
 		while !operators.is_empty(){
 			if operators.last().unwrap().token_type == TokenType::LPAREN_ARG {
 				return;
@@ -486,7 +578,9 @@ impl <'a> InfixToPostfix<'a>{
 			self.move_operator_prec(&operators.pop().unwrap(), postfix, operators);
 		}
 
-		abend!(format!("from special_case_a:  Houston, we have a problem.  LPAREN_ARG not found where it should be.  Triggered by {}",trigger.token_type));
+		if self.cli.is_debug_bit(TRACE_INFIX_TO_POSTFIX){
+			eprintln!("i_to_p:  special_case_a:\tno LPAREN_ARG for {}, all operators moved", trigger.token_type);
+		}
 	}
 
 	//	Input is FunctionCall; convert operator at TOS (if there is one) to <op>^ and
@@ -599,7 +693,7 @@ impl <'a> InfixToPostfix<'a>{
 			}
 
 			//	if the token > token at tos move it and loop
-			if self.token_prec(&token) > self.token_prec(operators.last().unwrap()){
+			if self.outranks(&token, operators.last().unwrap()){
 				self.move_operator(&token, postfix);
 				continue;
 			}
@@ -25,3 +25,29 @@ impl fmt::Display for LiteralStatement{
 		write!(f,"Literal: {} = {}",self.literal_id.token_value,token_list_text(&self.literal_value))
 	}
 }
+
+/****************************************
+****	Global Variable Statement
+*****************************************/
+
+//	global <id> = <expression>;  The expression is in postfix order and is empty
+//	when the global isn't initialized
+pub struct GlobalStatement{
+	pub global_id : Token,
+	pub global_value : Vec<Token>,
+}
+
+impl GlobalStatement{
+	pub fn new(global_id:Token, global_value : Vec<Token>) -> GlobalStatement{
+		GlobalStatement{
+			global_id : global_id,
+			global_value : global_value.clone(),
+		}
+	}
+}
+
+impl fmt::Display for GlobalStatement{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f,"Global: {} = {}",self.global_id.token_value,token_list_text(&self.global_value))
+	}
+}
@@ -20,6 +20,13 @@ pub struct MachineInstruction{
 	pub literal : Token,
 }
 
+//	A function call's qualifier is [<values pushed>, <is_statement>] followed, if the values
+//	aren't simply the arguments in parameter order, by one of these for each value pushed
+//	(or a parameter number when the value belongs to that parameter)
+pub const ARG_POSITIONAL : usize = usize::MAX;		// the value goes to the next positional parameter
+pub const ARG_SPREAD : usize = usize::MAX - 1;		// the value is an array spread over the next positional parameters
+pub const ARG_DEFAULT : usize = usize::MAX / 2;		// ARG_DEFAULT + <parameter number>: the default, used if nothing else was
+
 impl MachineInstruction{
 	pub fn new(opcode : Opcode, opcode_mode : OpcodeMode, function_num : usize, block_num : usize, adr : usize, qualifier : Vec<usize>, interner : usize, literal : Token) -> MachineInstruction{
 		MachineInstruction{
@@ -62,8 +69,15 @@ impl fmt::Display for MachineInstruction{
 				write!(f,"{} frame: {} block:{} address:{}", self.opcode, self.function_num, self.block_num, self.address)
 			},
 
-			Opcode::FunctionCall =>  write!(f,"{} ({}) arg count={} is_statement={}", self.opcode, self.literal.token_value, self.qualifier[0], self.qualifier[1]),
+			Opcode::FunctionCall => if self.qualifier.len() > 2{
+				write!(f,"{} ({}) arg count={} is_statement={} order={:?}", self.opcode, self.literal.token_value, self.qualifier[0], self.qualifier[1], &self.qualifier[2..])
+			}else{
+				write!(f,"{} ({}) arg count={} is_statement={}", self.opcode, self.literal.token_value, self.qualifier[0], self.qualifier[1])
+			},
+			Opcode::Closure =>  write!(f,"{} ({}) frame={} captures={}", self.opcode, self.literal.token_value, self.block_num, self.qualifier[0]),
 			Opcode::Diag => write!(f,"{} {}",self.opcode, self.literal.token_value),
+			Opcode::Unpack => write!(f,"{} count={}", self.opcode, self.qualifier[0]),
+			Opcode::Try => write!(f,"{} catch block={} rtn={}:{}", self.opcode, self.qualifier[0], self.block_num, self.address),
 			Opcode::FetchIndexed => {
 				if self.qualifier.len() > 0{
 					write!(f,"{} @{},{},{} indices={} ({})",self.opcode, self.function_num, self.block_num, self.address, self.qualifier[0], self.display_literal())
@@ -90,15 +104,20 @@ impl fmt::Display for MachineInstruction{
 					OpcodeMode::Var			=> write!(f,"{} @{},{},{} ({})",self.opcode, self.function_num, self.block_num, self.address, self.display_literal()),
 					OpcodeMode::VarRef		=> write!(f,"{} &{},{},{} ({})",self.opcode, self.function_num, self.block_num, self.address, self.display_literal()),
 					OpcodeMode::Arg			=> write!(f,"{} ^{},{},{}",self.opcode, self.function_num, self.block_num, self.address),
+					OpcodeMode::GlobalVar	=> write!(f,"{} @global,{} ({})",self.opcode, self.address, self.display_literal()),
+					OpcodeMode::GlobalVarRef => write!(f,"{} &global,{} ({})",self.opcode, self.address, self.display_literal()),
 					OpcodeMode::Function	=> write!(f,"{} target {} frame number {} parameter count {}",self.opcode, self.display_literal(), self.function_num, self.qualifier[0]),
 					OpcodeMode::Lit 		=> write!(f,"{}(lit) \"{}\"",self.opcode, self.display_literal()),
 					OpcodeMode::Builtin		=> write!(f,"{} '{}'",self.opcode, self.display_literal()),
+					OpcodeMode::Indirect	=> write!(f,"{} via '{}'",self.opcode, self.display_literal()),
 					OpcodeMode::Jump		=> write!(f,"{} *{}",self.opcode, self.address),
 					OpcodeMode::Bl			=> write!(f,"{} rtn={}:{} qual={:?}", self.opcode, self.block_num, self.address, self.qualifier),
 					OpcodeMode::Update		=> write!(f,"{}(update) {},{},{} ({})",self.opcode, self.function_num, self.block_num, self.address, self.display_literal()),
 					OpcodeMode::UpdateIndexed => write!(f,"{}(update indexed) {},{},{} ({})",self.opcode, self.function_num, self.block_num, self.address, self.display_literal()),
 					OpcodeMode::UpdateStructElement => write!(f,"{} #:{},{},{} ({})",self.opcode, self.function_num, self.block_num, self.address, self.display_literal()),
 					OpcodeMode::UpdateIndexedStructElement => write!(f,"{} #:#{},{},{} ({})",self.opcode, self.function_num, self.block_num, self.address, self.display_literal()),
+					OpcodeMode::UpdateGlobal => write!(f,"{}(update global) {} ({})",self.opcode, self.address, self.display_literal()),
+					OpcodeMode::UpdateGlobalIndexed => write!(f,"{}(update global indexed) {} ({})",self.opcode, self.address, self.display_literal()),
 					OpcodeMode::Alloc		=> write!(f,"{} {},{},{} ({})",self.opcode, self.function_num, self.block_num, self.address, self.display_literal()),
 					OpcodeMode::Array		=> write!(f,"{}(array) {},{},{},{} ({})",self.opcode, self.function_num, self.block_num, self.address, self.qualifier.len(), self.display_literal()),
 					OpcodeMode::Dict		=> write!(f,"{}(dict) {},{},{},{} ({})",self.opcode, self.function_num, self.block_num, self.address, self.qualifier.len(), self.display_literal()),
@@ -117,6 +136,10 @@ pub fn token_type_to_opcode(t : TokenType) -> Opcode{
 	
 		TokenType::BITWISE_OR	 	=> Opcode::BwOr,
 		TokenType::BITWISE_AND 		=> Opcode::BwAnd,
+		TokenType::BITWISE_XOR 		=> Opcode::BwXor,
+		TokenType::BITWISE_NOT 		=> Opcode::BwNot,
+		TokenType::SHIFT_LEFT 		=> Opcode::Shl,
+		TokenType::SHIFT_RIGHT 		=> Opcode::Shr,
 	
 		TokenType::PP 				=> Opcode::Inc,
 		TokenType::MM 				=> Opcode::Dec,
@@ -127,7 +150,9 @@ pub fn token_type_to_opcode(t : TokenType) -> Opcode{
 		TokenType::SUB 				=> Opcode::Sub,
 		TokenType::MUL 				=> Opcode::Mul,
 		TokenType::DIV 				=> Opcode::Div,
+		TokenType::INT_DIV			=> Opcode::IntDiv,
 		TokenType::MOD 				=> Opcode::Mod,
+		TokenType::POWER 			=> Opcode::Pow,
 
 		TokenType::ASG_ADD_EQ 		=> Opcode::AddEq,
 		TokenType::ASG_SUB_EQ 		=> Opcode::SubEq,
@@ -137,6 +162,10 @@ pub fn token_type_to_opcode(t : TokenType) -> Opcode{
 		TokenType::ASG_APPEND_EQ	=> Opcode::AppendEq,		// concat equal
 		TokenType::ASG_OR_EQ		=> Opcode::OrEq,
 		TokenType::ASG_AND_EQ		=> Opcode::AndEq,
+		TokenType::ASG_XOR_EQ		=> Opcode::XorEq,
+		TokenType::ASG_POWER_EQ		=> Opcode::PowEq,
+		TokenType::ASG_SHIFT_LEFT_EQ	=> Opcode::ShlEq,
+		TokenType::ASG_SHIFT_RIGHT_EQ	=> Opcode::ShrEq,
 
 		TokenType::CONCAT			=> Opcode::Concat,
 
@@ -150,6 +179,7 @@ pub fn token_type_to_opcode(t : TokenType) -> Opcode{
 		TokenType::CONTINUE			=> Opcode::Continue,
 		TokenType::RETURN			=> Opcode::Return,
 		TokenType::EXIT				=> Opcode::Exit,
+		TokenType::THROW			=> Opcode::Throw,
 
 		TokenType::LT 				=> Opcode::Lt,
 		TokenType::GT				=> Opcode::Gt,
@@ -157,6 +187,8 @@ pub fn token_type_to_opcode(t : TokenType) -> Opcode{
 		TokenType::GE				=> Opcode::Ge,
 		TokenType::NE 				=> Opcode::Ne,
 		TokenType::EQ 				=> Opcode::Eq,
+		TokenType::IN 				=> Opcode::In,
+		TokenType::NOT_IN 			=> Opcode::NotIn,
 
 		TokenType::COMMA			=> Opcode::IncArgCount,
 		TokenType::ARG_SEPARATOR	=> Opcode::IncArgCount,
@@ -6,6 +6,7 @@ use codeframe::*;
 use literalmodel::*;
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use names::*;
 
 
@@ -89,10 +90,10 @@ impl<'a> Model<'a>{
 	}
 
 	//	add a new function and make it the current function context (i.e. function_list.last())
-	pub fn add_function(&mut self, function_name:String, function_entry_flag : bool, function_parameters:Vec<String>, cl_args : & 'a Vec<String>){
+	pub fn add_function(&mut self, function_name:String, function_entry_flag : bool, function_parameters:Vec<String>, function_parameter_defaults : Vec<Option<Token>>, cl_args : & 'a Vec<String>){
 		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_function: {} Entry={} Pameters={}", function_name, function_entry_flag, self.parameters_text(&function_parameters));}
 		self.predicted_block_number = 0;
-		self.program.add_function(function_name, function_entry_flag, function_parameters, cl_args);
+		self.program.add_function(function_name, function_entry_flag, function_parameters, function_parameter_defaults, cl_args);
 	}
 
 	//	returns header index and struct index
@@ -108,11 +109,21 @@ impl<'a> Model<'a>{
 		struct_index
 	}
 
+	pub fn add_struct_method(&mut self, struct_name : &str, method : &str, function_name : &str){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("    Model.add_struct_method {}:{} function={}", struct_name, method, function_name);}
+		self.program.add_struct_method(struct_name, method, function_name);
+	}
+
 	pub fn add_struct_member(&mut self, member_name : &str, initializer : &Vec<Token>, struct_ix : usize){
 		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("    Model.add_struct_member {} init={:?} struct index={}",member_name, token_list_text(initializer), struct_ix);}
 		self.program.add_struct_member(member_name, initializer, struct_ix);
 	}
 
+	pub fn add_struct_embedding(&mut self, embedded_name : &str, struct_ix : usize){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("    Model.add_struct_embedding {} struct index={}", embedded_name, struct_ix);}
+		self.program.add_struct_embedding(embedded_name, struct_ix);
+	}
+
 	pub fn add_simple_statement(&mut self, token : Token, expression : Vec<Token>){
 		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_simple_statement: {} expression: {}", token.token_type, self.expression_text(&expression));}
 		let s = SimpleStatement::new(token, expression);
@@ -128,10 +139,10 @@ impl<'a> Model<'a>{
 		return f.add_statement(StatementType::EvalStatement(s));		
 	}
 
-	pub fn add_when_statement(&mut self, when_expression : Vec<Token>, eval_model_context : usize){
-		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_when {}",token_list_text(&when_expression));}
+	pub fn add_when_statement(&mut self, patterns : Vec<Vec<Token>>, guard : Vec<Token>, eval_model_context : usize){
 		self.predicted_block_number += 1;
-		let s = WhenStatement::new(when_expression, eval_model_context);
+		let s = WhenStatement::new(patterns, guard, eval_model_context);
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_when {}", s);}
 		let f = self.program.current_function();
 		f.add_statement(StatementType::WhenStatement(s));
 
@@ -147,6 +158,13 @@ impl<'a> Model<'a>{
 		f.add_statement(StatementType::OtherwiseStatement(s));
 	}
 
+	pub fn add_loop_label(&mut self, label : Token){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_loop_label {}", label.token_value);}
+		let s = LoopLabelStatement::new(label);
+		let f = self.program.current_function();
+		f.add_statement(StatementType::LoopLabelStatement(s));
+	}
+
 	pub fn add_loop_statement(&mut self){
 		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_loop_statement");}
 		self.predicted_block_number += 1;
@@ -155,24 +173,16 @@ impl<'a> Model<'a>{
 		f.add_statement(StatementType::LoopStatement(s));		
 	}
 
-	pub fn add_struct_instantiate(&mut self, instantiated_struct : &Token, struct_name : &Token){
+	pub fn add_struct_instantiate(&mut self, instantiated_struct : &Token, struct_name : &Token, has_arguments : bool){
 		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {
-			println!("Model.add_struct_instantiate: {}=new {}", instantiated_struct.token_value, struct_name);
+			println!("Model.add_struct_instantiate: {}=new {} has_arguments={}", instantiated_struct.token_value, struct_name, has_arguments);
 		}
-		let s = InstantiateStatement::new(instantiated_struct, struct_name);
+		let s = InstantiateStatement::new(instantiated_struct, struct_name, has_arguments);
 		let f = self.program.current_function();
 		f.add_statement(StatementType::InstantiateStatement(s));
 	}
 
-	pub fn add_assignment_statement(&mut self, assignment_target:Token, assignment_op:Token, target_index_expression : &Vec<Token>, expression : &Vec<Token>){
-		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {
-			if target_index_expression.len() == 0{
-				println!("Model.add_assignment_statement: {}{}{}", assignment_target.token_value, assignment_op.token_value, token_list_text(&expression));
-			}else{
-				println!("Model.add_assignment_statement: {}[{}]{}{}", assignment_target.token_value, token_list_text(&target_index_expression), assignment_op.token_value, token_list_text(&expression));
-			}
-		}
-
+	fn make_assignment_statement(&self, assignment_target:Token, assignment_targets : &Vec<Token>, assignment_op:Token, target_index_expression : &Vec<Token>, expression : &Vec<Token>) -> AssignmentStatement{
 		//	And, of course, here's a bit of a hack:  we would like to know the comma count for
 		//	the target_index_expression because, downstream from here, we are going to generate
 		//	and instruction that has to know how many indicies were specified in the target_index_expression
@@ -182,7 +192,23 @@ impl<'a> Model<'a>{
 				target_index_count += 1;
 			}
 		}
-		let s = AssignmentStatement::new(assignment_target, assignment_op, target_index_expression.clone(), target_index_count, expression.clone());
+		AssignmentStatement::new(assignment_target, assignment_targets.clone(), assignment_op, target_index_expression.clone(), target_index_count, expression.clone())
+	}
+
+	//	assignment_targets is empty unless this is a destructuring assignment (x, y = ...)
+	//	in which case it holds all of the targets
+	pub fn add_assignment_statement(&mut self, assignment_target:Token, assignment_targets : &Vec<Token>, assignment_op:Token, target_index_expression : &Vec<Token>, expression : &Vec<Token>){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {
+			if assignment_targets.len() > 0{
+				println!("Model.add_assignment_statement: {}{}{}", token_list_text(&assignment_targets), assignment_op.token_value, token_list_text(&expression));
+			}else if target_index_expression.len() == 0{
+				println!("Model.add_assignment_statement: {}{}{}", assignment_target.token_value, assignment_op.token_value, token_list_text(&expression));
+			}else{
+				println!("Model.add_assignment_statement: {}[{}]{}{}", assignment_target.token_value, token_list_text(&target_index_expression), assignment_op.token_value, token_list_text(&expression));
+			}
+		}
+
+		let s = self.make_assignment_statement(assignment_target, assignment_targets, assignment_op, target_index_expression, expression);
 		let f = self.program.current_function();
 		f.add_statement(StatementType::AssignmentStatement(s));
 	}
@@ -202,6 +228,57 @@ impl<'a> Model<'a>{
 		f.add_statement(StatementType::While(s));
 	}
 
+	//	The init part of the for has already been added as a normal assignment
+	//	statement.  If there is a step, codegen will put it in its own block so
+	//	we predict 2 new blocks instead of 1
+	pub fn add_for_statement(&mut self, condition : Vec<Token>, step_target : Option<(Token, Token, Vec<Token>, Vec<Token>)>){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_for_statement ({:?})",self.expression_text(&condition));}
+		self.predicted_block_number += 1;
+		let step = match step_target{
+			Some((target, op, target_index_expression, expression)) => {
+				self.predicted_block_number += 1;
+				Some(self.make_assignment_statement(target, &Vec::new(), op, &target_index_expression, &expression))
+			},
+			None => None,
+		};
+		let s = For::new(condition, step);
+		let f = self.program.current_function();
+		f.add_statement(StatementType::For(s));
+	}
+
+	pub fn add_do_statement(&mut self){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_do_statement");}
+		self.predicted_block_number += 1;
+		let s = Do::new();
+		let f = self.program.current_function();
+		f.add_statement(StatementType::Do(s));
+	}
+
+	//	The until doesn't start a block:  it's generated in the block that
+	//	contains the do
+	pub fn add_until_statement(&mut self, condition : Vec<Token>){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_until_statement ({:?})",self.expression_text(&condition));}
+		let s = Until::new(condition);
+		let f = self.program.current_function();
+		f.add_statement(StatementType::Until(s));
+	}
+
+	pub fn add_try_statement(&mut self){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_try_statement");}
+		self.predicted_block_number += 1;
+		let s = Try::new();
+		let f = self.program.current_function();
+		f.add_statement(StatementType::Try(s));
+	}
+
+	pub fn add_catch_statement(&mut self, variable : Token){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_catch_statement ({})", variable.token_value);}
+		self.predicted_block_number += 1;
+		let s = Catch::new(variable);
+		let f = self.program.current_function();
+		f.add_statement(StatementType::Catch(s));
+	}
+
 	pub fn add_foreach_statement(&mut self, target : Token, source_type : TokenType,  source : Vec<Token>){
 		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("    Model.add_foreach_statement: target:{} source{}", target, source[0]);}
 		self.predicted_block_number += 1;
@@ -237,12 +314,32 @@ impl<'a> Model<'a>{
 		f.set_else_flag(if_model_context, self.predicted_block_number);
 	}
 
+	//	We've seen if cond { ... }elseif cond {...} so add the elseif arm to the
+	//	IF statement via the if_model_context value
+	pub fn add_elseif_statement(&mut self, if_model_context : usize, condition : Vec<Token>){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("    Model.add_elseif_statement. context={} ({:?})", if_model_context, self.expression_text(&condition));}
+
+		//	Like else, codegen will add a new block for the elseif
+		self.predicted_block_number += 1;
+		let s = ElseIf::new();
+		let f = self.program.current_function();
+
+		f.add_statement(StatementType::ElseIf(s));
+		f.add_elseif_to_if(if_model_context, condition, self.predicted_block_number);
+	}
+
 	pub fn add_global_literal(&mut self, literal_id : Token, literal_value : Vec<Token>){
 		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_global_literal: {} literal_value: {}", literal_id, token_list_text(&literal_value));}
 		let s = LiteralStatement::new(literal_id, literal_value);
 		self.program.add_literal(s);
 	}
 
+	pub fn add_global_variable(&mut self, global_id : Token, global_value : Vec<Token>){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_global_variable: {} global_value: {}", global_id, token_list_text(&global_value));}
+		let s = GlobalStatement::new(global_id, global_value);
+		self.program.add_global_variable(s);
+	}
+
 	pub fn add_literal_statement(&mut self, literal_id : Token, literal_value : Vec<Token>){
 		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_literal_statement: {} literal_value: {}", literal_id, token_list_text(&literal_value));}
 		let s = LiteralStatement::new(literal_id, literal_value);
@@ -250,6 +347,11 @@ impl<'a> Model<'a>{
 		f.add_statement(StatementType::LiteralStatement(s));		
 	}
 
+	pub fn add_function_literals(&mut self, function_literals : &HashMap<String, Vec<String>>){
+		if self.cli.is_debug_bit(TRACE_MODEL_ADD) {println!("Model.add_function_literals: {} literals", function_literals.len());}
+		self.program.function_literals = function_literals.clone();
+	}
+
 	pub fn generate_code_from_model (&mut self){
 		//  First, add the structs to the symbol table
 		self.generator.add_structs_to_struct_map(&self.program.structs);
@@ -257,6 +359,13 @@ impl<'a> Model<'a>{
 		//	Then add global literals, if there are any
 		self.generator.add_global_literals(&self.program.global_literals);
 
+		//	and global variables.  Their initializers are generated at the start
+		//	of the entry function
+		self.generator.add_global_variables(&self.program.global_variables);
+
+		//	and the names function literals might capture
+		self.generator.add_function_literals(&self.program.function_literals);
+
 		//	Next, we need to know what where to start because
 		//	elsewhere we've added a bunch of builtin functions to
 		//	the frame map.  This call follows a chain of calls that
@@ -8,6 +8,7 @@ use literalmodel::*;
 use macrolib::*;
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use names::*;
 
 
@@ -17,11 +18,18 @@ pub enum StatementType<'a>{
 	AssignmentStatement(AssignmentStatement),
 	InstantiateStatement(InstantiateStatement),
 	While(While),
+	For(For),
+	Do(Do),
+	Until(Until),
+	Try(Try),
+	Catch(Catch),
 	ForeachStatement(ForeachStatement),
 	If(If),
+	ElseIf(ElseIf),
 	Else(Else),
 	SimpleStatement(SimpleStatement),
 	LoopStatement(LoopStatement),
+	LoopLabelStatement(LoopLabelStatement),
 	FunctionCallStatement(FunctionCallStatement),
 	BlockEnd(BlockEnd),				// block begin instructions generated implicitly
 	Function(Function<'a>),			// For context purposes Function is statement
@@ -37,11 +45,18 @@ impl<'a> fmt::Display for StatementType<'a>{
 			StatementType::AssignmentStatement(_) => write!(f, "AssignmentStatement"),
 			StatementType::InstantiateStatement(_) => write!(f, "InstantiateStatement"),
 			StatementType::While(_) => write!(f, "While"),
+			StatementType::For(_) => write!(f, "For"),
+			StatementType::Do(_) => write!(f, "Do"),
+			StatementType::Until(_) => write!(f, "Until"),
+			StatementType::Try(_) => write!(f, "Try"),
+			StatementType::Catch(_) => write!(f, "Catch"),
 			StatementType::ForeachStatement(_) => write!(f, "Foreach"),
 			StatementType::If(b) => write!(f, "If ({})", b.has_else),
+			StatementType::ElseIf(_) => write!(f, "ElseIf"),
 			StatementType::Else(_) => write!(f, "Else"),
 			StatementType::SimpleStatement(_) => write!(f, "Simple"),
 			StatementType::LoopStatement(_) => write!(f, "LoopStatement"),
+			StatementType::LoopLabelStatement(_) => write!(f, "LoopLabelStatement"),
 			StatementType::FunctionCallStatement(_) => write!(f, "FunctionCallStatement"),
 			StatementType::BlockEnd(_) => write!(f, "BlockEnd"),
 			StatementType::Function(_) => write!(f, "Function"),
@@ -103,6 +118,7 @@ pub struct Program<'a>{
 	cli : & 'a CLI<'a>,
 	pub functions : Vec<Function<'a>>,
 	pub global_literals : Vec<LiteralStatement>,
+	pub global_variables : Vec<GlobalStatement>,
 
 	//	The struct header table is a list of pointers into the structs table
 	//	Each element of this table represents a top-level struct
@@ -113,6 +129,10 @@ pub struct Program<'a>{
 	//	child; or rather, the relationships between parents and children are
 	//	maintained in the struct's member list.
 	pub structs : Vec<Struct>,
+
+	//	Function literals lifted out of expressions by the tokenizer mapped to
+	//	the names they might capture from their enclosing function
+	pub function_literals : HashMap<String, Vec<String>>,
 }
 
 impl<'a> Program<'a>{
@@ -121,15 +141,17 @@ impl<'a> Program<'a>{
 			cli : cli,
 			functions : Vec::new(),			// indexed by function_num			
 			global_literals : Vec::new(),
+			global_variables : Vec::new(),
 			struct_headers : Vec::new(),
 			structs : Vec::new(),
+			function_literals : HashMap::new(),
 		}
 	}
 
 	//	Adds a function to the end of the program's function list, making it the
 	//	current function context for future updates
-	pub fn add_function (&mut self, name : String,  function_entry_flag : bool, function_parameters : Vec<String>, cl_args : & 'a Vec<String>){
-		let f : Function = Function::new(self.cli, name, function_entry_flag, function_parameters, cl_args);
+	pub fn add_function (&mut self, name : String,  function_entry_flag : bool, function_parameters : Vec<String>, function_parameter_defaults : Vec<Option<Token>>, cl_args : & 'a Vec<String>){
+		let f : Function = Function::new(self.cli, name, function_entry_flag, function_parameters, function_parameter_defaults, cl_args);
 		self.functions.push(f);
 	}
 
@@ -137,6 +159,10 @@ impl<'a> Program<'a>{
 		self.global_literals.push(literal_statement);
 	}
 
+	pub fn add_global_variable(&mut self, global_statement : GlobalStatement){
+		self.global_variables.push(global_statement);
+	}
+
 	pub fn add_child_struct(&mut self, name : &str, current_struct_ix : usize) -> usize{
 		//	Adding a child struct requires adding a new struct to the structs table and
 		//	a new member to its parent containg the index of the newly added struct.
@@ -174,7 +200,46 @@ impl<'a> Program<'a>{
 	//	Adds a member to the struct to the map at context.  If the level is 0 then the context is a structs_list
 	//	index.  If the level > 0 then the context is the index in the child's table.
 	pub fn add_struct_member(&mut self, name : &str, initializer : &Vec<Token>, struct_ix : usize){
-		self.structs.get_mut(struct_ix).unwrap().members.push(&StructMemberType::Field(Field::new(name.to_string(), initializer)));
+		let s = self.structs.get_mut(struct_ix).unwrap();
+		if s.members.members.iter().any(|member| member.name() == name){
+			abend!(format!("from Program.add_struct_member: struct {} already has a member named {}", s.name, name));
+		}
+		s.members.push(&StructMemberType::Field(Field::new(name.to_string(), initializer)));
+	}
+
+	//	Adds a method to the top-level struct that declared it
+	pub fn add_struct_method(&mut self, struct_name : &str, method : &str, function_name : &str){
+		let struct_ix = match self.struct_headers.iter().find(|ix| self.structs[**ix].name == struct_name){
+			Some(ix) => *ix,
+			None => abend!(format!("from Program.add_struct_method: struct {} of method {} has not been declared", struct_name, method)),
+		};
+
+		let s = self.structs.get_mut(struct_ix).unwrap();
+		if s.members.members.iter().any(|member| member.name() == method){
+			abend!(format!("from Program.add_struct_method: method {} has the same name as a member of struct {}", method, struct_name));
+		}
+		s.methods.push((method.to_string(), function_name.to_string()));
+	}
+
+	//	use <struct>; in a struct body copies the members of that (top-level) struct into
+	//	the struct being declared so they're accessed just like its own members
+	pub fn add_struct_embedding(&mut self, embedded_name : &str, struct_ix : usize){
+		let embedded_ix = match self.struct_headers.iter().find(|ix| self.structs[**ix].name == embedded_name){
+			Some(ix) => *ix,
+			None => abend!(format!("from Program.add_struct_embedding: struct {} has not been declared.  A struct has to be declared before it's used by another", embedded_name)),
+		};
+		if embedded_ix == struct_ix{
+			abend!(format!("from Program.add_struct_embedding: struct {} can't use itself", embedded_name));
+		}
+
+		let members = self.structs[embedded_ix].members.members.clone();
+		let s = self.structs.get_mut(struct_ix).unwrap();
+		for member in &members{
+			if s.members.members.iter().any(|m| m.name() == member.name()){
+				abend!(format!("from Program.add_struct_embedding: {} of struct {} has the same name as a member of struct {}", member.name(), embedded_name, s.name));
+			}
+			s.members.push(member);
+		}
 	}
 
 	pub fn dump_structs(&self){
@@ -215,6 +280,7 @@ pub struct Function<'a>{
 	//cli : & 'a CLI<'a>,
 	pub name : String,
 	pub function_parameters : Vec<String>,
+	pub function_parameter_defaults : Vec<Option<Token>>,
 
 	//	Where all of the statements that implement the function end up.  The
 	//	Code Generator derives all of the pseudo machine instructions from this
@@ -226,11 +292,12 @@ pub struct Function<'a>{
 }
 
 impl<'a>Function<'a>{
-	pub fn new(cli : & 'a CLI<'a>, name : String, function_entry_flag:bool, function_parameters : Vec<String>, cl_args : & 'a Vec<String>) -> Function<'a>{
+	pub fn new(cli : & 'a CLI<'a>, name : String, function_entry_flag:bool, function_parameters : Vec<String>, function_parameter_defaults : Vec<Option<Token>>, cl_args : & 'a Vec<String>) -> Function<'a>{
 		Function{
 			//cli : cli,
 			name : name,
 			function_parameters : function_parameters,
+			function_parameter_defaults : function_parameter_defaults,
 			block : StatementBlock::new(cli),
 			function_entry_flag : function_entry_flag,
 			cl_args : cl_args,
@@ -263,6 +330,11 @@ impl<'a>Function<'a>{
 		self.block.set_else_flag(if_model_context, predicted_block_number);
 	}
 
+	//	Add an elseif arm to this IF statement
+	pub fn add_elseif_to_if(&mut self, if_model_context : usize, condition : Vec<Token>, predicted_block_number : usize){
+		self.block.add_elseif_to_if(if_model_context, condition, predicted_block_number);
+	}
+
 	pub fn add_when_to_when_list(&mut self, eval_model_context : usize){
 		self.block.add_when_to_when_list(eval_model_context);
 	}
@@ -315,6 +387,13 @@ impl<'a> StatementBlock<'a>{
 		}
 	}
 
+	pub fn add_elseif_to_if (&mut self, if_model_context :usize, condition : Vec<Token>, predicted_block_number : usize){
+		match self.statements.get_mut(if_model_context){
+			Some(StatementType::If(s)) => s.elseif_list.push((Expression::new(condition), predicted_block_number)),
+			_ => abend!(format!("from add_elseif_to_if: It appears you've included an elseif statement without a parent if.  Might be some other issue too.")),
+		}
+	}
+
 	pub fn add_when_to_when_list (&mut self, eval_model_context :usize){
 		let last_added = self.statements.len() - 1;
 		match self.statements.get_mut(eval_model_context).unwrap(){
@@ -365,6 +444,74 @@ impl While{
 	}
 }
 
+/****************************************
+****	For
+*****************************************/
+//	for <init>; <condition>; <step> {...}
+//
+//	The init is added to the model as an ordinary assignment statement
+//	ahead of the For so only the condition and the (optional) step are
+//	kept here.
+pub struct For{
+	pub condition : Expression,
+	pub step : Option<AssignmentStatement>,
+}
+impl For{
+	pub fn new(condition : Vec<Token>, step : Option<AssignmentStatement>) -> For{
+		For {
+			condition : Expression::new(condition),
+			step : step,
+		}
+	}
+}
+
+/****************************************
+****	Do/Until
+*****************************************/
+//	do {...} until <condition>;  The until follows the end of the do block
+pub struct Do{
+}
+impl Do{
+	pub fn new() -> Do{
+		Do {}
+	}
+}
+
+pub struct Until{
+	pub condition : Expression,
+}
+impl Until{
+	pub fn new(condition : Vec<Token>) -> Until{
+		Until {
+			condition : Expression::new(condition),
+		}
+	}
+}
+
+/****************************************
+****	Try/Catch
+*****************************************/
+//	try {...} catch <id> {...}  The catch follows the end of the try block
+pub struct Try{
+}
+impl Try{
+	pub fn new() -> Try{
+		Try {}
+	}
+}
+
+//	The variable is optional (i.e. catch {...}) in which case its token is empty
+pub struct Catch{
+	pub variable : Token,
+}
+impl Catch{
+	pub fn new(variable : Token) -> Catch{
+		Catch {
+			variable : variable,
+		}
+	}
+}
+
 /****************************************
 ****	Foreach
 *****************************************/
@@ -428,17 +575,26 @@ impl fmt::Display for EvalStatement{
 ****	WHEN
 *****************************************/
 
+//	The when matches if any of its patterns match the eval target (and the guard,
+//	if there is one, is true).  A pattern is one of:
+//
+//		<expression>			equal to the target
+//		<low> RANGE <high>		low <= target <= high
+//		REGEX					the regex matches the target
+//		WILDCARD				anything
 pub struct WhenStatement{
 	//cli : & 'a CLI<'a>,
-	pub when_expression : Expression,
+	pub patterns : Vec<Expression>,
+	pub guard : Expression,
 	pub eval_model_context : usize,
 }
 
 impl WhenStatement{
-	pub fn new(when_expression : Vec<Token>, eval_model_context : usize) -> WhenStatement{
+	pub fn new(patterns : Vec<Vec<Token>>, guard : Vec<Token>, eval_model_context : usize) -> WhenStatement{
 		WhenStatement {
 			// cli : cli,
-			when_expression : Expression::new(when_expression),
+			patterns : patterns.into_iter().map(|pattern| Expression::new(pattern)).collect(),
+			guard : Expression::new(guard),
 			eval_model_context : eval_model_context
 		}
 	}
@@ -446,7 +602,12 @@ impl WhenStatement{
 
 impl fmt::Display for WhenStatement{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f,"When: {}",token_list_text(&self.when_expression.expression_list))
+		let patterns : Vec<String> = self.patterns.iter().map(|p| token_list_text(&p.expression_list)).collect();
+		if self.guard.expression_list.is_empty(){
+			write!(f,"When: {}", patterns.join(" , "))
+		}else{
+			write!(f,"When: {} if {}", patterns.join(" , "), token_list_text(&self.guard.expression_list))
+		}
 	}
 }
 
@@ -487,6 +648,10 @@ pub struct If{
 	pub condition : Expression,
 	pub has_else : bool,
 	pub else_block_num : usize,
+
+	//	the condition and block number of each elseif arm in the order
+	//	they appear
+	pub elseif_list : Vec<(Expression, usize)>,
 }
 
 impl If{
@@ -496,13 +661,30 @@ impl If{
 			condition : Expression::new(condition),
 			has_else : false,
 			else_block_num : 0,
+			elseif_list : Vec::new(),
 		}
 	}
 }
 
 impl fmt::Display for If{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "If {} [has_else:{}]",token_list_text(&self.condition.expression_list),self.has_else)
+		write!(f, "If {} [elseif count:{} has_else:{}]",token_list_text(&self.condition.expression_list),self.elseif_list.len(),self.has_else)
+	}
+}
+
+/****************************************
+****	ElseIf
+*****************************************/
+
+//	Like else, an elseif just marks the place in the model where the
+//	elseif block begins.  Its condition is kept in the parent If
+
+pub struct ElseIf{
+}
+impl ElseIf{
+	pub fn new() -> ElseIf{
+		ElseIf {
+		}
 	}
 }
 
@@ -539,20 +721,29 @@ pub struct InstantiateStatement{
 	//cli : & 'a CLI<'a>,
 	pub instantiated_struct : Token,
 	pub struct_name : Token,
+
+	//	new foo(...) rather than new foo.  The arguments are passed to the struct's init
+	//	method by the method call statement that follows this one
+	pub has_arguments : bool,
 }
 impl InstantiateStatement{
-	pub fn new(instantiated_struct : &Token, struct_name : &Token) -> InstantiateStatement{
+	pub fn new(instantiated_struct : &Token, struct_name : &Token, has_arguments : bool) -> InstantiateStatement{
 		InstantiateStatement {
 			//cli : cli,
 			instantiated_struct : instantiated_struct.clone(),
 			struct_name : struct_name.clone(),
+			has_arguments : has_arguments,
 		}
 	}
 }
 
 impl fmt::Display for InstantiateStatement{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f,"InstantiateStatement: {}=new {}",self.instantiated_struct.token_value, self.struct_name.token_value)
+		if self.has_arguments{
+			write!(f,"InstantiateStatement: {}=new {}(...)",self.instantiated_struct.token_value, self.struct_name.token_value)
+		}else{
+			write!(f,"InstantiateStatement: {}=new {}",self.instantiated_struct.token_value, self.struct_name.token_value)
+		}
 	}
 } 
 
@@ -563,16 +754,18 @@ impl fmt::Display for InstantiateStatement{
 pub struct AssignmentStatement{
 	//cli : & 'a CLI<'a>,
 	pub target : Token,
+	pub targets : Vec<Token>,
 	pub target_index_expression : Expression,
 	pub target_index_count : usize,
 	pub op : Token,
 	pub expression : Expression,
 }
 impl AssignmentStatement{
-	pub fn new(target : Token, op : Token, target_index_expression : Vec<Token>, target_index_comma_count : usize, expression : Vec<Token>) -> AssignmentStatement{
+	pub fn new(target : Token, targets : Vec<Token>, op : Token, target_index_expression : Vec<Token>, target_index_comma_count : usize, expression : Vec<Token>) -> AssignmentStatement{
 		AssignmentStatement {
 			//cli : cli,
 			target : target,					// target of the AssignmentStatement
+			targets : targets,					// all of the targets of a destructuring assignment (x, y = ...)
 			target_index_expression : Expression::new(target_index_expression),
 			//	the number of indices in the target is the number of commas + 1
 			target_index_count : target_index_comma_count+1,
@@ -584,7 +777,11 @@ impl AssignmentStatement{
 
 impl fmt::Display for AssignmentStatement{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f,"AssignmentStatement: {}{}{}",self.target.token_value,self.op,token_list_text(&self.expression.expression_list))
+		if self.targets.len() > 0{
+			write!(f,"AssignmentStatement: {}{}{}",token_list_text(&self.targets),self.op,token_list_text(&self.expression.expression_list))
+		}else{
+			write!(f,"AssignmentStatement: {}{}{}",self.target.token_value,self.op,token_list_text(&self.expression.expression_list))
+		}
 	}
 }
 
@@ -636,6 +833,29 @@ impl fmt::Display for LoopStatement{
 	}
 }
 
+/****************************************
+****	Loop Label Statement
+*****************************************/
+
+//	The label of the loop statement that follows it (outer: foreach ...)
+pub struct LoopLabelStatement{
+	pub label : Token,
+}
+
+impl LoopLabelStatement{
+	pub fn new(label : Token) -> LoopLabelStatement{
+		LoopLabelStatement{
+			label : label,
+		}
+	}
+}
+
+impl fmt::Display for LoopLabelStatement{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f,"Loop Label: {}", self.label.token_value)
+	}
+}
+
 /****************************************
 ****	Function Call Statement
 *****************************************/
@@ -690,12 +910,20 @@ impl<'a> Generator<'_>{
 		self.code_gen.add_global_literals(global_literals);
 	}
 
+	pub fn add_global_variables(&mut self, global_variables : &Vec<GlobalStatement>){
+		self.code_gen.add_global_variables(global_variables);
+	}
+
+	pub fn add_function_literals(&mut self, function_literals : &HashMap<String, Vec<String>>){
+		self.code_gen.add_function_literals(function_literals);
+	}
+
 	pub fn get_frames(&self) -> FrameMap{
 		self.code_gen.get_frames()
 	}
 
 	pub fn add_function_to_frame(&mut self, function : &mut Function){
-		self.code_gen.add_function_to_frame(&function.name, &function.function_parameters, function.function_entry_flag);
+		self.code_gen.add_function_to_frame(&function.name, &function.function_parameters, &function.function_parameter_defaults, function.function_entry_flag);
 	}
 
 	pub fn gen_function_from_generator (&mut self, function : &mut Function, function_num : usize){
@@ -722,15 +950,28 @@ impl<'a> Generator<'_>{
 					self.code_gen.gen_simple(&t.token, &t.expression.expression_list, function_num);
 				}
 				StatementType::LoopStatement(_) => self.code_gen.gen_loop(function_num),
-				StatementType::AssignmentStatement(t) => self.code_gen.gen_assignment(&t.target, &t.op, &t.target_index_expression.expression_list, t.target_index_count, &t.expression.expression_list, function_num),
-				StatementType::InstantiateStatement(t) => self.code_gen.gen_struct_instantiate(&t.instantiated_struct, &t.struct_name, function_num),
+				StatementType::LoopLabelStatement(t) => self.code_gen.gen_loop_label(&t.label, function_num),
+				StatementType::AssignmentStatement(t) => self.code_gen.gen_assignment(&t.target, &t.targets, &t.op, &t.target_index_expression.expression_list, t.target_index_count, &t.expression.expression_list, function_num),
+				StatementType::InstantiateStatement(t) => self.code_gen.gen_struct_instantiate(&t.instantiated_struct, &t.struct_name, t.has_arguments, function_num),
 				StatementType::FunctionCallStatement(t) => self.code_gen.gen_function_call_statement(&t.function_name, &t.expression.expression_list, function_num, t.argument_count),
 				StatementType::While(t) => self.code_gen.gen_while(&t.condition.expression_list, function_num),
+				StatementType::For(t) => {
+					match &t.step{
+						Some(s) => self.code_gen.gen_for(&t.condition.expression_list, true, &s.target, &s.op, &s.target_index_expression.expression_list, s.target_index_count, &s.expression.expression_list, function_num),
+						None => self.code_gen.gen_for(&t.condition.expression_list, false, &Token::new(), &Token::new(), &Vec::new(), 0, &Vec::new(), function_num),
+					}
+				},
+				StatementType::Do(_) => self.code_gen.gen_do(function_num),
+				StatementType::Until(t) => self.code_gen.gen_until(&t.condition.expression_list, function_num),
+				StatementType::Try(_) => self.code_gen.gen_try(function_num),
+				StatementType::Catch(c) => self.code_gen.gen_catch(&c.variable, function_num),
 				StatementType::ForeachStatement(t) => self.code_gen.gen_foreach(&t.target, &t.source_type, &t.source, function_num),
 				StatementType::If(t) => {
-					self.code_gen.gen_if(&t.condition.expression_list, t.has_else, t.else_block_num, function_num);
+					let elseif_list : Vec<(Vec<Token>, usize)> = t.elseif_list.iter().map(|e| (e.0.expression_list.clone(), e.1)).collect();
+					self.code_gen.gen_if(&t.condition.expression_list, &elseif_list, t.has_else, t.else_block_num, function_num);
 				},
 
+				StatementType::ElseIf(_) => self.code_gen.gen_else(function_num),
 				StatementType::Else(_) => self.code_gen.gen_else(function_num),
 				StatementType::Function(_) => {},
 				StatementType::BlockEnd(b) =>{
@@ -749,7 +990,10 @@ impl<'a> Generator<'_>{
 					self.code_gen.gen_block_end(function_num);		
 				},
 				StatementType::EvalStatement(t) => self.code_gen.gen_eval(&t.target.expression_list, t.when_list.len(), function_num),
-				StatementType::WhenStatement(t) => self.code_gen.gen_when(&t.when_expression.expression_list, function_num),
+				StatementType::WhenStatement(t) => {
+					let patterns : Vec<Vec<Token>> = t.patterns.iter().map(|p| p.expression_list.clone()).collect();
+					self.code_gen.gen_when(&patterns, &t.guard.expression_list, function_num)
+				},
 				StatementType::OtherwiseStatement(_) => self.code_gen.gen_otherwise(function_num),
 				StatementType::LiteralStatement(t) => self.code_gen.gen_literal_statement(&t.literal_id, &t.literal_value),
 
@@ -13,8 +13,11 @@ pub enum Opcode{
 	Sub,
 	Mul,
 	Div,
+	IntDiv,
 	Mod,
+	Pow,
 	FunctionCall,
+	Closure,
 	FetchIndexed,
 	Inc,
 	Dec,
@@ -32,6 +35,10 @@ pub enum Opcode{
 	
 	BwAnd,
 	BwOr,
+	BwXor,
+	BwNot,
+	Shl,
+	Shr,
 	BlockBegin,
 	BlockEnd,
 	IncArgCount,
@@ -42,6 +49,13 @@ pub enum Opcode{
 	Bl,			// branch and link
 	Break,
 	Continue,
+
+	//	Exceptions
+	Try,		// register a catch block
+	EndTry,		// the try block finished normally so drop its catch block
+	Catch,		// push the caught error value
+	Throw,
+
 	Update,
 	Append,		// add a value to an array
 	Insert,		// add a value to a dictionary
@@ -56,6 +70,10 @@ pub enum Opcode{
 	AppendEq,
 	OrEq,
 	AndEq,
+	XorEq,
+	PowEq,
+	ShlEq,
+	ShrEq,
 
 	// Comparison Operators
 	Lt,
@@ -65,12 +83,18 @@ pub enum Opcode{
 	Ne,
 	Eq,
 
+	//	Membership Operators
+	In,
+	NotIn,
+	RegexMatch,				// when /<regex>/
+
 	//	Unary operators
 	Damnit,
 	LengthOf,
 
 	//	Kitchen Sink Operators
 	Foreach,
+	Unpack,		// replace an array with its elements for a destructuring assignment
 
 	//	Special purpose
 	Diag,
@@ -87,8 +111,11 @@ impl fmt::Display for Opcode{
 			Opcode::Sub						=> write!(f,"Sub"),
 			Opcode::Mul						=> write!(f,"Mul"),
 			Opcode::Div						=> write!(f,"Div"),
+			Opcode::IntDiv					=> write!(f,"IntDiv"),
 			Opcode::Mod						=> write!(f,"Mod"),
+			Opcode::Pow						=> write!(f,"Pow"),
 			Opcode::FunctionCall			=> write!(f,"FunctionCall"),
+			Opcode::Closure					=> write!(f,"Closure"),
 			Opcode::FetchIndexed			=> write!(f,"FetchIndexed"),
 			Opcode::Inc						=> write!(f,"Inc"),
 			Opcode::Dec						=> write!(f,"Dec"),
@@ -106,6 +133,10 @@ impl fmt::Display for Opcode{
 
 			Opcode::BwAnd					=> write!(f,"Bitwise And"),
 			Opcode::BwOr					=> write!(f,"Bitwise Or"),
+			Opcode::BwXor					=> write!(f,"Bitwise Xor"),
+			Opcode::BwNot					=> write!(f,"Bitwise Not"),
+			Opcode::Shl						=> write!(f,"Shift Left"),
+			Opcode::Shr						=> write!(f,"Shift Right"),
 			Opcode::IncArgCount				=> write!(f,"IncArgCount"),
 			Opcode::Return					=> write!(f,"Return"),
 			Opcode::BlockBegin				=> write!(f,"BlockBegin"),
@@ -117,6 +148,11 @@ impl fmt::Display for Opcode{
 
 			Opcode::Break					=> write!(f,"Break"),
 			Opcode::Continue				=> write!(f,"Continue"),
+
+			Opcode::Try						=> write!(f,"Try"),
+			Opcode::EndTry					=> write!(f,"EndTry"),
+			Opcode::Catch					=> write!(f,"Catch"),
+			Opcode::Throw					=> write!(f,"Throw"),
 		
 			Opcode::Update					=> write!(f,"Update"),
 			Opcode::Append					=> write!(f,"Append"),
@@ -131,6 +167,10 @@ impl fmt::Display for Opcode{
 			Opcode::AppendEq				=> write!(f,"AppendEq"),
 			Opcode::OrEq					=> write!(f,"OrEq"),
 			Opcode::AndEq					=> write!(f,"AndEq"),
+			Opcode::XorEq					=> write!(f,"XorEq"),
+			Opcode::PowEq					=> write!(f,"PowEq"),
+			Opcode::ShlEq					=> write!(f,"ShlEq"),
+			Opcode::ShrEq					=> write!(f,"ShrEq"),
 		
 			Opcode::Lt						=> write!(f,"<"),
 			Opcode::Gt						=> write!(f,">"),
@@ -139,12 +179,17 @@ impl fmt::Display for Opcode{
 			Opcode::Ne						=> write!(f,"!="),
 			Opcode::Eq						=> write!(f,"=="),
 
+			Opcode::In						=> write!(f,"in"),
+			Opcode::NotIn					=> write!(f,"not in"),
+			Opcode::RegexMatch				=> write!(f,"RegexMatch"),
+
 			Opcode::Damnit					=> write!(f,"!"),
 			Opcode::LengthOf				=> write!(f,"LengthOf"),
 
 			Opcode::Diag					=> write!(f,"Diag"),
 
 			Opcode::Foreach					=> write!(f,"Foreach"),
+			Opcode::Unpack					=> write!(f,"Unpack"),
 	   }
 	}
 }
@@ -158,7 +203,10 @@ pub enum OpcodeMode{
 	Var,
 	VarRef,
 	Arg,
+	GlobalVar,					// A variable in the global operand frame
+	GlobalVarRef,				// &<global>
 	Function,
+	Indirect,					// Call through a function value on the operand stack
 	Builtin,					// Builtin functions
 	Jump,						// Absolute Jump target address
 	//JumpRel,					// Relative Jump target address (i.e. jump location +/- address)
@@ -167,6 +215,8 @@ pub enum OpcodeMode{
 	UpdateIndexed,				// signals updates an element of a collection in situ
 	UpdateStructElement,		// Signals update of struct element which is really update array element
 	UpdateIndexedStructElement,	// Signals update of an array element owned by a struct member
+	UpdateGlobal,				// Update a global variable
+	UpdateGlobalIndexed,		// Update an element of a collection held by a global variable
 	
 	Array,						// Modifies push_collection, and update
 	Dict,						// ditto
@@ -183,8 +233,11 @@ impl fmt::Display for OpcodeMode{
 			OpcodeMode::Var							=> write!(f,"Var"),
 			OpcodeMode::VarRef						=> write!(f,"VarRef"),
 			OpcodeMode::Arg							=> write!(f,"Arg"),
+			OpcodeMode::GlobalVar					=> write!(f,"GlobalVar"),
+			OpcodeMode::GlobalVarRef				=> write!(f,"GlobalVarRef"),
 
 			OpcodeMode::Function					=> write!(f,"Function"),
+			OpcodeMode::Indirect					=> write!(f,"Indirect"),
 			OpcodeMode::Builtin						=> write!(f,"Builtin"),
 			OpcodeMode::Jump						=> write!(f,"Jump"),
 			// OpcodeMode::JumpRel					=> write!(f,"JumpRel"),
@@ -195,6 +248,8 @@ impl fmt::Display for OpcodeMode{
 			OpcodeMode::UpdateIndexed				=> write!(f,"UpdateIndexed"),
 			OpcodeMode::UpdateStructElement			=> write!(f,"UpdateStructElement"),
 			OpcodeMode::UpdateIndexedStructElement	=> write!(f,"UpdateIndexedStructElement"),
+			OpcodeMode::UpdateGlobal				=> write!(f,"UpdateGlobal"),
+			OpcodeMode::UpdateGlobalIndexed			=> write!(f,"UpdateGlobalIndexed"),
 
 			OpcodeMode::Array						=> write!(f,"Array"),
 			OpcodeMode::Dict						=> write!(f,"Dict"),
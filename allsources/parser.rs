@@ -20,6 +20,7 @@ pub enum ParserState{
 	FunctionBody,
 	FunctionSignature,
 	FunctionParameter,
+	FunctionParameterDefault,
 	Constant,
 	Statement,
 	Block,
@@ -32,6 +33,7 @@ pub enum ParserState{
 	IncDecStatement,		// Arrrrgghhhh!
 	DictLiteral,			// to detect the beginning of dictionary literal
 	Assignment,
+	AssignmentTargetList,	// after the ',' following the first target of x, y = ...
 	//AssignmentOp,
 	Expression,
 	ExpressionTerm,
@@ -46,6 +48,7 @@ pub enum ParserState{
 	StructMember,
 	StructInit,
 	StructInstantiate,		// used when <var> = new <struct>
+	StructUse,				// used when use <struct>; is in a struct body
 
 	Literal,
 	GlobalLiteral,
@@ -53,6 +56,8 @@ pub enum ParserState{
 	LocalLiteral,
 	LocalLiteralValue,
 
+	GlobalVariable,
+	GlobalVariableValue,
 }
 impl fmt::Display for ParserState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -64,6 +69,7 @@ impl fmt::Display for ParserState {
 			ParserState::FunctionBody				=> write!(f,"FunctionBody"),
 			ParserState::FunctionSignature			=> write!(f,"FunctionSignature"),
 			ParserState::FunctionParameter			=> write!(f,"FunctionParameter"),
+			ParserState::FunctionParameterDefault	=> write!(f,"FunctionParameterDefault"),
 			ParserState::Constant					=> write!(f,"Constant"),
 			ParserState::Statement					=> write!(f,"Statement"),
 			ParserState::Block						=> write!(f,"Block"),
@@ -76,6 +82,7 @@ impl fmt::Display for ParserState {
 			ParserState::IncDecStatement			=> write!(f,"Inc/Dec Statement"),
 			ParserState::DictLiteral				=> write!(f,"DictLiteral"),
 			ParserState::Assignment					=> write!(f,"Assignment"),
+			ParserState::AssignmentTargetList		=> write!(f,"AssignmentTargetList"),
 			// ParserState::AssignmentOp				=> write!(f,"AssignmentOp"),
 			ParserState::Expression					=> write!(f,"Expression"),
 			ParserState::ExpressionTerm				=> write!(f,"ExpressionTerm"),
@@ -89,12 +96,16 @@ impl fmt::Display for ParserState {
 			ParserState::StructMember				=> write!(f, "StructMember"),
 			ParserState::StructInit					=> write!(f, "StructInit"),
 			ParserState::StructInstantiate			=> write!(f, "StructInstantiate"),
+			ParserState::StructUse					=> write!(f, "StructUse"),
 
 			ParserState::Literal					=> write!(f, "Literal"),
 			ParserState::GlobalLiteral				=> write!(f, "GlobalLiteral"),
 			ParserState::GlobalLiteralValue			=> write!(f, "GlobalLiteralValue"),
 			ParserState::LocalLiteral				=> write!(f, "LocalLiteral"),
 			ParserState::LocalLiteralValue			=> write!(f, "LocalLiteralValue"),
+
+			ParserState::GlobalVariable				=> write!(f, "GlobalVariable"),
+			ParserState::GlobalVariableValue		=> write!(f, "GlobalVariableValue"),
 		}
 	}
 }
@@ -113,8 +124,16 @@ pub enum ParserContext{
 	Constant,				// const <id> <assignment operator> expression>;
 	Simple,					// print, return, etc.
 	If,
+	ElseIf,
 	Else,
 	While,
+	For,					// for <init>; -- we're collecting the init
+	ForCondition,			// for <init>; <condition>;
+	ForBlock,				// for ... {
+	Do,						// do {...}
+	Until,					// do {...} until <condition>;
+	Try,					// try {...}
+	Catch,					// try {...} catch <id> {...}
 	Foreach,
 	Eval,
 	When,
@@ -123,6 +142,7 @@ pub enum ParserContext{
 	FunctionCallTerm,
 	Struct,
 	StructMember,
+	Global,					// global <id> = <expression>;
 }
 
 impl fmt::Display for ParserContext {
@@ -139,8 +159,16 @@ impl fmt::Display for ParserContext {
 			ParserContext::Constant					=> write!(f,"Constant"),
 			ParserContext::Simple					=> write!(f,"Simple"),
 			ParserContext::If						=> write!(f,"If"),
+			ParserContext::ElseIf					=> write!(f,"ElseIf"),
 			ParserContext::Else						=> write!(f,"Else"),
 			ParserContext::While					=> write!(f,"While"),
+			ParserContext::For						=> write!(f,"For"),
+			ParserContext::ForCondition				=> write!(f,"ForCondition"),
+			ParserContext::ForBlock					=> write!(f,"ForBlock"),
+			ParserContext::Do						=> write!(f,"Do"),
+			ParserContext::Until					=> write!(f,"Until"),
+			ParserContext::Try						=> write!(f,"Try"),
+			ParserContext::Catch					=> write!(f,"Catch"),
 			ParserContext::Eval						=> write!(f,"Eval"),
 			ParserContext::When						=> write!(f,"When"),
 			ParserContext::Otherwise				=> write!(f,"Otherwise"),
@@ -149,6 +177,7 @@ impl fmt::Display for ParserContext {
 			ParserContext::FunctionCallTerm			=> write!(f,"FunctionCallTerm"),
 			ParserContext::Struct					=> write!(f,"Struct"),
 			ParserContext::StructMember				=> write!(f,"StructMember"),
+			ParserContext::Global					=> write!(f,"Global"),
 		}
 	}
 }
@@ -241,6 +270,7 @@ pub struct Parser<'a>{
 	function_call_paren_counters : Vec<i32>,	// counters specifically for one function call
 	comma_counters : Vec<i32>,		//  to figure out how many arguments a function call had
 	function_call_locations : Vec<usize>,	// for the backpatching operations
+	argument_names : Vec<Vec<String>>,		// the name of each argument of a function call ("" if positional)
 	/***************************************************************************************/
 
 	statement_list_item_counter : i32,		// used to count commas in non-function call contexts
@@ -258,23 +288,31 @@ pub struct Parser<'a>{
 	struct_member_name : String,
 
 	simple : Token,				// PRINT, EPRINT, RETURN, ETC.
+	catch_variable : Token,		// catch <id>
 
 	//	The value is a list because eventually we'll want to support
 	//	expressions, array literals, and dictionary literals.
 	literal_id : Token,			// literal <id>
 	literal_value : Vec<Token>,	// literal <id> = <literal expression>
 
+	global_id : Token,			// global <id> = <expression>;
+
 	//	When we see a function call in an expression, we save it's name and the parameter count
 	//expression_function_call_name: Vec<Token>,
 
 	function_name : Token,
 	function_parameters : Vec<String>,
+	function_parameter_defaults : Vec<Option<Token>>,	// fn f(a, b = 10)
+	negative_default : bool,							// fn f(a = -1)
+	rest_parameter : bool,								// fn f(a, ...rest)
 	function_entry_flag : bool,
 
 	statement_factor : Token,
 	assignment_target : Token,
+	assignment_targets : Vec<Token>,		// x, y = ... (empty unless it's a destructuring assignment)
 	assignment_target_index_expression : Vec<Token>,
 	assignment_operator : Token,
+	value_list : bool,						// return a, b; and x, y = a, b; build an array of the values
 
 	foreach_target : Token,					// the target of the foreach
 	foreach_source_type : TokenType,		// the type of source ID, LBRACKET, LBRACE				// if
@@ -282,6 +320,13 @@ pub struct Parser<'a>{
 
 	eval_brace_counter : Vec<Vec<usize>>,	// it's a stack of stacks to enable eval within eval
 
+	for_condition : Vec<Token>,				// the postfix condition of for <init>; <condition>; <step>
+
+	when_patterns : Vec<Vec<Token>>,		// the postfix patterns of when <pattern>, <pattern> ...
+	when_range_low : Vec<Token>,			// the postfix low bound of when <low>..<high>
+	when_range : Option<Token>,				// the RANGE token if the current pattern is a range
+	when_guard : bool,						// when <patterns> if <guard>
+
 	//  all of the tokens caputred that make up an expression
 	infix_expression : Vec<Token>,
 
@@ -308,6 +353,7 @@ impl <'a> Parser<'a>{
 			function_call_paren_counters : Vec::new(),
 			comma_counters : Vec::new(),
 			function_call_locations : Vec::new(),
+			argument_names : Vec::new(),
 			/***************************************************************************************/
 
 			statement_list_item_counter : -1,
@@ -320,18 +366,26 @@ impl <'a> Parser<'a>{
 			struct_member_name : String::new(),
 
 			simple :Token::new(),
+			catch_variable : Token::new(),
 
 			literal_id : Token::new(),			// literal <id>
 			literal_value : Vec::new(),			// litera <id> = <literal expression>
 
+			global_id : Token::new(),			// global <id>
+
 			function_name : Token::new(),
 			function_parameters : Vec::new(),
+			function_parameter_defaults : Vec::new(),
+			negative_default : false,
+			rest_parameter : false,
 			function_entry_flag : false,
 			
 			statement_factor : Token::new(),
 			assignment_target : Token::new(),
+			assignment_targets : Vec::new(),
 			assignment_target_index_expression : Vec::new(),
 			assignment_operator : Token::new(),
+			value_list : false,
 
 			foreach_target : Token::new(),					// the target of the foreach
 			foreach_source_type : TokenType::NONE,					// the type of source ID, LBRACKET, LBRACE				// if
@@ -339,6 +393,13 @@ impl <'a> Parser<'a>{
 
 			eval_brace_counter : Vec::new(),
 
+			for_condition : Vec::new(),
+
+			when_patterns : Vec::new(),
+			when_range_low : Vec::new(),
+			when_range : None,
+			when_guard : false,
+
 			current_state : ParserState::Program,
 			parser_context: vec![ParserContext::Nothing],
 
@@ -373,6 +434,14 @@ impl <'a> Parser<'a>{
 				ParserStateTransitionContent::new(ParserState::FunctionParameter, true, "do_nothing", Parser::do_nothing)),
 			(ParserStateTransitionKey::new(ParserState::FunctionParameter,TokenCategory::RParen),
 				ParserStateTransitionContent::new(ParserState::Block, false, "function_parameter", Parser::function_parameter)),
+			(ParserStateTransitionKey::new(ParserState::FunctionParameter,TokenCategory::Ellipsis),
+				ParserStateTransitionContent::new(ParserState::FunctionParameter, false, "function_parameter_rest", Parser::function_parameter_rest)),
+			(ParserStateTransitionKey::new(ParserState::FunctionParameter,TokenCategory::AssignmentOp),
+				ParserStateTransitionContent::new(ParserState::FunctionParameterDefault, false, "function_parameter_default", Parser::function_parameter_default)),
+			(ParserStateTransitionKey::new(ParserState::FunctionParameterDefault,TokenCategory::BinaryOp),
+				ParserStateTransitionContent::new(ParserState::FunctionParameterDefault, false, "function_parameter_default", Parser::function_parameter_default)),
+			(ParserStateTransitionKey::new(ParserState::FunctionParameterDefault,TokenCategory::Factor),
+				ParserStateTransitionContent::new(ParserState::FunctionParameter, false, "function_parameter_default_value", Parser::function_parameter_default_value)),
 			(ParserStateTransitionKey::new(ParserState::Program,TokenCategory::StructDeclaration),
 				ParserStateTransitionContent::new(ParserState::Struct, false, "struct_declaration", Parser::struct_declaration)),
 			(ParserStateTransitionKey::new(ParserState::Struct,TokenCategory::Factor),
@@ -391,6 +460,10 @@ impl <'a> Parser<'a>{
 				ParserStateTransitionContent::new(ParserState::Program, true, "do_nothing", Parser::do_nothing)),
 			(ParserStateTransitionKey::new(ParserState::StructBody,TokenCategory::StructDeclaration),
 				ParserStateTransitionContent::new(ParserState::Struct, false, "struct_declaration", Parser::struct_declaration)),
+			(ParserStateTransitionKey::new(ParserState::StructBody,TokenCategory::Use),
+				ParserStateTransitionContent::new(ParserState::StructUse, true, "do_nothing", Parser::do_nothing)),
+			(ParserStateTransitionKey::new(ParserState::StructUse,TokenCategory::Factor),
+				ParserStateTransitionContent::new(ParserState::StructBody, false, "struct_use", Parser::struct_use)),
 			(ParserStateTransitionKey::new(ParserState::Program,TokenCategory::Literal),
 				ParserStateTransitionContent::new(ParserState::GlobalLiteral, true, "do_nothing", Parser::do_nothing)),
 			(ParserStateTransitionKey::new(ParserState::GlobalLiteral,TokenCategory::Factor),
@@ -401,6 +474,14 @@ impl <'a> Parser<'a>{
 				ParserStateTransitionContent::new(ParserState::GlobalLiteralValue, false, "literal_value", Parser::literal_value)),
 			(ParserStateTransitionKey::new(ParserState::GlobalLiteralValue,TokenCategory::Semi),
 				ParserStateTransitionContent::new(ParserState::Program, true, "do_nothing", Parser::do_nothing)),
+			(ParserStateTransitionKey::new(ParserState::Program,TokenCategory::Global),
+				ParserStateTransitionContent::new(ParserState::GlobalVariable, true, "do_nothing", Parser::do_nothing)),
+			(ParserStateTransitionKey::new(ParserState::GlobalVariable,TokenCategory::Factor),
+				ParserStateTransitionContent::new(ParserState::GlobalVariableValue, false, "global_id", Parser::global_id)),
+			(ParserStateTransitionKey::new(ParserState::GlobalVariableValue,TokenCategory::AssignmentOp),
+				ParserStateTransitionContent::new(ParserState::Expression, false, "global_init", Parser::global_init)),
+			(ParserStateTransitionKey::new(ParserState::GlobalVariableValue,TokenCategory::Semi),
+				ParserStateTransitionContent::new(ParserState::Program, false, "global_add", Parser::global_add)),
 			(ParserStateTransitionKey::new(ParserState::Block,TokenCategory::LBrace),
 				ParserStateTransitionContent::new(ParserState::Statement, false, "begin_block", Parser::begin_block)),
 			(ParserStateTransitionKey::new(ParserState::Statement,TokenCategory::Factor),
@@ -409,8 +490,14 @@ impl <'a> Parser<'a>{
 				ParserStateTransitionContent::new(ParserState::Expression, false, "assignment_op", Parser::assignment_op)),
 			(ParserStateTransitionKey::new(ParserState::StructInstantiate,TokenCategory::Factor),
 				ParserStateTransitionContent::new(ParserState::Statement, false, "struct_instantiate", Parser::struct_instantiate)),
+			(ParserStateTransitionKey::new(ParserState::Assignment,TokenCategory::Comma),
+				ParserStateTransitionContent::new(ParserState::AssignmentTargetList, false, "destructuring_target", Parser::destructuring_target)),
+			(ParserStateTransitionKey::new(ParserState::AssignmentTargetList,TokenCategory::Factor),
+				ParserStateTransitionContent::new(ParserState::Assignment, false, "destructuring_next_target", Parser::destructuring_next_target)),
 			(ParserStateTransitionKey::new(ParserState::Assignment,TokenCategory::LBracket),
 				ParserStateTransitionContent::new(ParserState::Expression, false, "indexed_target", Parser::indexed_target)),
+			(ParserStateTransitionKey::new(ParserState::Assignment,TokenCategory::IncDec),
+				ParserStateTransitionContent::new(ParserState::ExpressionTerm, false, "for_step_inc_dec", Parser::for_step_inc_dec)),
 			(ParserStateTransitionKey::new(ParserState::Assignment,TokenCategory::LParen),
 				ParserStateTransitionContent::new(ParserState::Expression, false, "function_call_statement", Parser::function_call_statement)),
 			(ParserStateTransitionKey::new(ParserState::Statement,TokenCategory::Verb),
@@ -421,8 +508,12 @@ impl <'a> Parser<'a>{
 				ParserStateTransitionContent::new(ParserState::Block, false, "keyword_loop", Parser::keyword_loop)),
 			(ParserStateTransitionKey::new(ParserState::Statement,TokenCategory::Otherwise),
 				ParserStateTransitionContent::new(ParserState::Block, false, "keyword_otherwise", Parser::keyword_otherwise)),
+			(ParserStateTransitionKey::new(ParserState::Statement,TokenCategory::Keyword),
+				ParserStateTransitionContent::new(ParserState::Expression, false, "keyword", Parser::keyword)),
 			(ParserStateTransitionKey::new(ParserState::Statement,TokenCategory::Semi),
 				ParserStateTransitionContent::new(ParserState::Statement, true, "do_nothing", Parser::do_nothing)),
+			(ParserStateTransitionKey::new(ParserState::Statement,TokenCategory::NamedArgument),
+				ParserStateTransitionContent::new(ParserState::Statement, false, "loop_label", Parser::loop_label)),
 			(ParserStateTransitionKey::new(ParserState::Statement,TokenCategory::Literal),
 				ParserStateTransitionContent::new(ParserState::LocalLiteral, true, "do_nothing", Parser::do_nothing)),
 			(ParserStateTransitionKey::new(ParserState::LocalLiteral,TokenCategory::Factor),
@@ -457,6 +548,10 @@ impl <'a> Parser<'a>{
 				ParserStateTransitionContent::new(ParserState::ExpressionTerm, false, "expression_factor", Parser::expression_factor)),
 			(ParserStateTransitionKey::new(ParserState::Expression,TokenCategory::LParen),
 				ParserStateTransitionContent::new(ParserState::Expression, false, "lparen", Parser::lparen)),
+			(ParserStateTransitionKey::new(ParserState::Expression,TokenCategory::NamedArgument),
+				ParserStateTransitionContent::new(ParserState::Expression, false, "named_argument", Parser::named_argument)),
+			(ParserStateTransitionKey::new(ParserState::Expression,TokenCategory::Ellipsis),
+				ParserStateTransitionContent::new(ParserState::Expression, false, "spread_argument", Parser::spread_argument)),
 			(ParserStateTransitionKey::new(ParserState::Expression,TokenCategory::LBracket),
 				ParserStateTransitionContent::new(ParserState::Expression, false, "array_literal", Parser::array_literal)),
 			(ParserStateTransitionKey::new(ParserState::FunctionCallArg,TokenCategory::LParen),
@@ -481,6 +576,10 @@ impl <'a> Parser<'a>{
 				ParserStateTransitionContent::new(ParserState::Expression, false, "index_expression", Parser::index_expression)),
 			(ParserStateTransitionKey::new(ParserState::ExpressionTerm,TokenCategory::RBracket),
 				ParserStateTransitionContent::new(ParserState::ExpressionTerm, false, "rbracket", Parser::rbracket)),
+			(ParserStateTransitionKey::new(ParserState::ExpressionTerm,TokenCategory::Range),
+				ParserStateTransitionContent::new(ParserState::Expression, false, "when_range", Parser::when_range)),
+			(ParserStateTransitionKey::new(ParserState::ExpressionTerm,TokenCategory::Verb),
+				ParserStateTransitionContent::new(ParserState::Expression, false, "when_guard", Parser::when_guard)),
 			(ParserStateTransitionKey::new(ParserState::Expression,TokenCategory::LBrace),
 				ParserStateTransitionContent::new(ParserState::DictLiteral, false, "dict_literal_begin", Parser::dict_literal_begin)),
 			(ParserStateTransitionKey::new(ParserState::DictLiteral,TokenCategory::LBrace),
@@ -583,7 +682,7 @@ impl <'a> Parser<'a>{
 					None => self.current_state = self.current_transition.new_parser_state.clone(),
 					Some(p) => match p{
 						ParserState::Error(m) => {
-							eprintln!("{} from CPL source line {} -- ({})",m, self.token.line_number, self.token.line_text);
+							eprintln!("{} from CPL source {} -- ({})",m, self.token.location(), self.token.line_text);
 							error_flag = true;
 							break;
 						},
@@ -610,6 +709,7 @@ impl <'a> Parser<'a>{
 
 		if self.cli.is_debug_bit(DUMP_STRUCTS){self.model.dump_structs();}
 
+		self.model.add_function_literals(&self.tokenizer.function_literals);
 		self.model.generate_code_from_model();
 
 		if self.cli.is_debug_bit(DUMP_GEN_CODE){self.model.code_dump();}
@@ -638,9 +738,9 @@ impl <'a> Parser<'a>{
 
 		if self.token.token_category == TokenCategory::Factor{
 			let prev_token = self.tokenizer.prev_token(2);
-			error_text.push_str (&format!(":  It looks like you're missing a semi-colon on previous line {}: \"{}\"",prev_token.line_number, prev_token.line_text));
+			error_text.push_str (&format!(":  It looks like you're missing a semi-colon on previous {}: \"{}\"",prev_token.location(), prev_token.line_text));
 		}else{
-			error_text.push_str(&format!(": Got \"{}\" at line {}", self.token.token_value, self.token.line_number));
+			error_text.push_str(&format!(": Got \"{}\" at {}", self.token.token_value, self.token.location()));
 		}
 		error_text.clone()
 	}
@@ -715,8 +815,9 @@ impl <'a> Parser<'a>{
 		match self.token.token_type{
 			TokenType::SUB 		|
 			TokenType::ADD 		| 
-			TokenType::DAMNIT 	=>{
-				if !self.look_ahead_test_multiple(&[TokenType::INTEGER, TokenType::FLOAT, TokenType::ID, TokenType::LPAREN].to_vec()){
+			TokenType::DAMNIT 	|
+			TokenType::BITWISE_NOT =>{
+				if !self.look_ahead_test_multiple(&[TokenType::INTEGER, TokenType::FLOAT, TokenType::DECIMAL, TokenType::ID, TokenType::LPAREN].to_vec()){
 					return Some(ParserState::Error(format!("Syntax error from unary_op_test, line {}: Op can only predede an ID or Number",line!())));
 				}
 				self.infix_expression.push(self.binary_op_to_unary_op());
@@ -792,6 +893,13 @@ impl <'a> Parser<'a>{
 		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: function_name {}", self.token.token_type)}
 		self.function_name = self.token.clone();
 		self.function_parameters.clear();
+		self.function_parameter_defaults.clear();
+		self.rest_parameter = false;
+
+		//	a method that the tokenizer lifted out of its struct is named <struct>.<method>
+		if let Some((struct_name, method)) = self.token.token_value.rsplit_once('.'){
+			self.model.add_struct_method(struct_name, method, &self.token.token_value);
+		}
 		None
 	}
 
@@ -807,19 +915,96 @@ impl <'a> Parser<'a>{
 
 		//	if we're all done collecting parameters, then add the function declaration to the model
 		if self.token.token_type == TokenType::RPAREN{
-			self.model.add_function(self.function_name.token_value.clone(), self.function_entry_flag, self.function_parameters.clone(), &self.cli.cl_args);
+			//	once a parameter has a default, the ones that follow it (other than a rest parameter) need one too
+			let mut declared = self.function_parameters.len();
+			if self.rest_parameter{
+				declared -= 1;
+			}
+			if let Some(first_default) = self.function_parameter_defaults[..declared].iter().position(|d| d.is_some()){
+				if let Some(missing) = self.function_parameter_defaults[first_default..declared].iter().position(|d| d.is_none()){
+					return Some(ParserState::Error(format!("Syntax error from function_parameter: parameter '{}' of {} needs a default value since it follows one that has a default", self.function_parameters[first_default + missing], self.function_name.token_value)));
+				}
+			}
+			self.model.add_function(self.function_name.token_value.clone(), self.function_entry_flag, self.function_parameters.clone(), self.function_parameter_defaults.clone(), &self.cli.cl_args);
 		}else{
-			self.function_parameters.push(self.token.token_value.clone());
+			//	nothing can follow the rest parameter
+			if let Some(last) = self.function_parameters.last(){
+				if last.starts_with("..."){
+					return Some(ParserState::Error(format!("Syntax error from function_parameter: the rest parameter '{}' of {} must be the last one", last, self.function_name.token_value)));
+				}
+			}
+
+			if self.rest_parameter{
+				if self.token.token_type != TokenType::ID{
+					return Some(ParserState::Error(format!("Syntax error from function_parameter: expecting a name following '...'.  Saw '{}'", self.token.token_value)));
+				}
+				self.function_parameters.push(format!("...{}", self.token.token_value));
+			}else{
+				self.function_parameters.push(self.token.token_value.clone());
+			}
+			self.function_parameter_defaults.push(None);
 		}
 
 		None
 	}
 
+	//	<function> :: fn | entry | entry fn <id> (<parameter>, ...<id>)  The rest parameter is
+	//	kept as "...<id>" (like a struct parameter is kept as <id>:<struct>)
+	fn function_parameter_rest(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: function_parameter_rest \"{}\"", self.token.token_value)}
+
+		if self.rest_parameter{
+			return Some(ParserState::Error(format!("Syntax error from function_parameter_rest: {} can only have one rest parameter", self.function_name.token_value)));
+		}
+		self.rest_parameter = true;
+		None
+	}
+
+	//	<function> :: fn | entry | entry fn <id> (<parameter> = [-]
+	fn function_parameter_default(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: function_parameter_default \"{}\"", self.token.token_value)}
+
+		match self.token.token_type{
+			TokenType::ASG_EQ if self.function_parameters.len() > 0 => self.negative_default = false,
+			TokenType::SUB if !self.negative_default => self.negative_default = true,
+			_ => return Some(ParserState::Error(format!("Syntax error from function_parameter_default: a default value is written <parameter> = <literal>.  Saw '{}'", self.token.token_value))),
+		}
+		None
+	}
+
+	//	<function> :: fn | entry | entry fn <id> (<parameter> = <literal>
+	//	Like a lit, the default is a single number, string, bool or lit name
+	fn function_parameter_default_value(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: function_parameter_default_value \"{}\"", self.token.token_value)}
+
+		let mut default = self.token.clone();
+		match default.token_type{
+			TokenType::INTEGER | TokenType::FLOAT | TokenType::DECIMAL => {
+				if self.negative_default{
+					default.token_value = format!("-{}", default.token_value);
+				}
+			},
+			TokenType::STRING | TokenType::BOOL | TokenType::ID if !self.negative_default => {},
+			_ => return Some(ParserState::Error(format!("Syntax error from function_parameter_default_value: the default value of '{}' must be a literal.  Saw '{}'", self.function_parameters.last().unwrap(), self.token.token_value))),
+		}
+
+		if self.function_parameters.last().unwrap().find(':') != None{
+			return Some(ParserState::Error(format!("Syntax error from function_parameter_default_value: struct parameter '{}' can't have a default value", self.function_parameters.last().unwrap())));
+		}
+		if self.function_parameters.last().unwrap().starts_with("..."){
+			return Some(ParserState::Error(format!("Syntax error from function_parameter_default_value: rest parameter '{}' can't have a default value", self.function_parameters.last().unwrap())));
+		}
+
+		self.negative_default = false;
+		*self.function_parameter_defaults.last_mut().unwrap() = Some(default);
+		None
+	}
+
 	//	A funciton can be declared with not parameters by simply leaving off
 	//	the parameter block "(...)";  Here we've seen:  entry/fn foo{
 	fn function_no_parameters(&mut self) -> Option<ParserState>{
 		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: function_no_parameters \"{}\" parser_context:{}", self.token.token_value,self.parser_context.last().unwrap())}
-		self.model.add_function(self.function_name.token_value.clone(), self.function_entry_flag, self.function_parameters.clone(), &self.cli.cl_args);
+		self.model.add_function(self.function_name.token_value.clone(), self.function_entry_flag, self.function_parameters.clone(), self.function_parameter_defaults.clone(), &self.cli.cl_args);
 		self.begin_block();
 		None
 	}
@@ -870,6 +1055,25 @@ impl <'a> Parser<'a>{
 		None
 	}
 
+	//	use <struct>; in a struct body.  The members of the used struct become members of
+	//	this one (see Program::add_struct_embedding)
+	fn struct_use(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: struct_use \"{}\"", self.token.token_value)}
+
+		if self.token.token_type != TokenType::ID {
+			return Some(ParserState::Error(format!("Syntax Error:  Expecting to see a struct name following use. Found:{}",self.token.token_value)));
+		}
+		if !self.look_ahead_test_token(TokenType::SEMI){
+			let local_token = self.next_token();
+			return Some(ParserState::Error(format!("Syntax Error:  Expecting to see a ';' following use {}. Found:{}", self.token.token_value, local_token.token_value)));
+		}
+		//	eat the ';'
+		self.next_token();
+
+		self.model.add_struct_embedding(&self.token.token_value, *self.struct_ix.last().unwrap());
+		None
+	}
+
 	fn struct_member_init(&mut self) -> Option<ParserState>{
 		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: struct_member_init \"{}\"", self.token.token_value)}
 		self.parser_context.push(ParserContext::StructMember);
@@ -878,17 +1082,15 @@ impl <'a> Parser<'a>{
 	}
 
 	//	we get to here after we've seen <target>=new. This had better be an id.  If it is, we
-	//	look ahead to make sure we see a ";" because the grammar here is precise and simple:
+	//	look ahead to make sure we see a ";" or a "(" because the grammar here is precise and simple:
 	//
 	//		<target> = new <struct name>;
+	//		<target> = new <struct name>(<arguments>);
 	//
-	//	as noted elsewhere, someday we may want to do:
+	//	The second form instantiates the struct and then calls its init method with the
+	//	arguments, exactly as if it were followed by the statement:
 	//
-	//		<target> = new <string name>(<member initialization list>);
-	//		<<member initialization list> ::= <init item> [,<member initialization list>]
-	//		<init item> ::= <member name>:<initial value>
-	//
-	//	but at the moment that's a bridge too far
+	//		<target>:init(<arguments>);
 	//
 	//	set the ID type to Struct and add the asssignment statement
 	fn struct_instantiate(&mut self) -> Option<ParserState>{
@@ -900,12 +1102,13 @@ impl <'a> Parser<'a>{
 		if self.token.token_type != TokenType::ID {
 			return Some(ParserState::Error(format!("Syntax Error:  Expecting to see an <ID> following the assignment operator. Found:{}",self.token.token_type)));
 		}
-		if !self.look_ahead_test_token(TokenType::SEMI){
+		let has_arguments = self.look_ahead_test_token(TokenType::LPAREN);
+		if !has_arguments && !self.look_ahead_test_token(TokenType::SEMI){
 			let local_token = self.next_token();
-			return Some(ParserState::Error(format!("Syntax Error:  Expecting to see a ';' following struct name. Found:{}",local_token.token_value)));
+			return Some(ParserState::Error(format!("Syntax Error:  Expecting to see a ';' or '(' following struct name. Found:{}",local_token.token_value)));
 		}
-		//	eat the ';'
-		self.next_token();
+		//	eat the ';' or '('
+		let next_token = self.next_token();
 
 		self.token.token_type = TokenType::STRUCT;
 		self.token.token_category = TokenCategory::StructDeclaration;
@@ -914,8 +1117,22 @@ impl <'a> Parser<'a>{
 
 		//	if we've seen <id> = new <id> then we know we're instantiating a
 		//	struct and there's nothing left to do.
-		self.model.add_struct_instantiate(&self.assignment_target, &self.token);
-		None
+		self.model.add_struct_instantiate(&self.assignment_target, &self.token, has_arguments);
+		if !has_arguments{
+			return None;
+		}
+
+		//	Otherwise the rest of the statement is the call of the init method
+		let mut init_call = self.assignment_target.clone();
+		init_call.token_type = TokenType::QUALIFIED_ID;
+		init_call.token_category = TokenCategory::Factor;
+		init_call.token_value = format!("{}:init", self.assignment_target.token_value);
+		self.statement_factor = init_call;
+		self.token = next_token;
+		match self.function_call_statement(){
+			None => Some(ParserState::Expression),
+			state => state,
+		}
 	}
 
 
@@ -936,9 +1153,45 @@ impl <'a> Parser<'a>{
 		self.statement_factor = self.token.clone();
 		//  initialize some things
 		self.assignment_target_index_expression.clear();
+		self.assignment_targets.clear();
 		None
 	}
 
+	//	We've seen "<factor>," at the start of a statement so it's the first of the targets
+	//	of a destructuring assignment (x, y = ...).  Only variables and struct members
+	//	can be targets
+	fn destructuring_target(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: destructuring_target \"{}\" target={}", self.token.token_value, self.statement_factor.token_value)}
+		if let Some(error) = self.check_destructuring_target(){
+			return Some(error);
+		}
+		self.assignment_targets.push(self.statement_factor.clone());
+		None
+	}
+
+	fn destructuring_next_target(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: destructuring_next_target \"{}\"", self.token.token_value)}
+		self.statement_factor = self.token.clone();
+		self.assignment_target_index_expression.clear();
+		None
+	}
+
+	fn check_destructuring_target(&self) -> Option<ParserState>{
+		match self.statement_factor.token_type{
+			TokenType::ID | TokenType::QUALIFIED_ID if self.assignment_target_index_expression.len() == 0 => None,
+			_ => Some(ParserState::Error(format!("Syntax Error: Each target of a destructuring assignment must be an ID or ID:QUAL. We found: {}", self.statement_factor.token_value))),
+		}
+	}
+
+	//	The values of return a, b; or x, y = a, b; are gathered into an array literal.  The
+	//	'[' was inserted when the first comma was seen so close it here
+	fn close_value_list(&mut self){
+		if self.value_list{
+			self.value_list = false;
+			self.infix_expression.push(Token::new2(TokenType::RBRACKET, "]".to_string(), self.token.line_number, self.token.line_text.clone(), TokenCategory::RBracket));
+		}
+	}
+
 	fn do_infix_to_postfix(&mut self){
 		//	get the reference to i_to_p conversion functions
 		let mut itop = InfixToPostfix::new(self.cli);
@@ -958,10 +1211,12 @@ impl <'a> Parser<'a>{
 		match parser_context{
 			ParserContext::Assignment => {
 				//eprintln!(".... dispatch_model_adder\nassignment_target ({})\nstatement_factor ({})", self.assignment_target, self.statement_factor);
+				self.close_value_list();
 				self.do_infix_to_postfix();
-				self.model.add_assignment_statement(self.assignment_target.clone(), self.assignment_operator.clone(), &self.assignment_target_index_expression, &self.postfix_expression.clone());
+				self.model.add_assignment_statement(self.assignment_target.clone(), &self.assignment_targets, self.assignment_operator.clone(), &self.assignment_target_index_expression, &self.postfix_expression.clone());
 			},
 			ParserContext::Simple => {
+				self.close_value_list();
 				self.do_infix_to_postfix();
 				self.model.add_simple_statement(self.simple.clone(),  self.postfix_expression.clone());
 			},
@@ -969,7 +1224,12 @@ impl <'a> Parser<'a>{
 				//	If we've decided that the factor of the assignment statement is, in fact, a function name
 				//	send it to the model
 				self.do_infix_to_postfix();
-				self.model.add_function_call_statement (self.statement_factor.clone(), self.postfix_expression.clone(), (self.statement_list_item_counter + 1) as usize);
+				let mut function_name = self.statement_factor.clone();
+				function_name.arg_names = self.take_argument_names();
+				self.model.add_function_call_statement (function_name, self.postfix_expression.clone(), (self.statement_list_item_counter + 1) as usize);
+
+				//	rparen leaves the statement's paren counter alone, so we're done with it here
+				self.function_call_paren_counters.pop();
 			},
 
 			ParserContext::CollectionSource => {
@@ -982,6 +1242,23 @@ impl <'a> Parser<'a>{
 				self.model.add_struct_member(&self.struct_member_name, &self.postfix_expression, *self.struct_ix.last().unwrap());
 			}
 
+			ParserContext::Global => {
+				self.do_infix_to_postfix();
+				self.model.add_global_variable(self.global_id.clone(), self.postfix_expression.clone());
+			}
+
+			ParserContext::Until => {
+				self.do_infix_to_postfix();
+				self.model.add_until_statement(self.postfix_expression.clone());
+			}
+
+			//	we've seen for <init>; <condition>; -- hang on to the condition
+			//	until we've seen the step
+			ParserContext::ForCondition => {
+				self.do_infix_to_postfix();
+				self.for_condition = self.postfix_expression.clone();
+			}
+
 			_=>{
 				abend!(format!("from dispatch_model_adder.  Unknown parser_context: {}", parser_context));
 			}
@@ -1014,6 +1291,63 @@ impl <'a> Parser<'a>{
 			return Some(ParserState::StructBody);
 		}
 
+		if parser_context == ParserContext::Global{
+			return Some(ParserState::Program);
+		}
+
+		//	The condition of a for is done.  Next is the step, which is an
+		//	assignment, unless there's no step at all
+		if parser_context == ParserContext::ForCondition{
+			if self.look_ahead_test_token(TokenType::LBRACE){
+				return Some(ParserState::ExpressionTerm);
+			}
+			return Some(ParserState::Statement);
+		}
+
+		//	The init of a for is done (it's been added as a normal assignment).  Next
+		//	comes the condition
+		if *self.parser_context.last().unwrap() == ParserContext::For{
+			return self.for_condition_begin();
+		}
+
+		None
+	}
+
+	//	Start collecting the condition of a for.  An empty condition is the same as "true"
+	fn for_condition_begin(&mut self) -> Option<ParserState>{
+		self.parser_context.push(ParserContext::ForCondition);
+		if self.look_ahead_test_token(TokenType::SEMI){
+			self.infix_expression.push(Token::new2(TokenType::BOOL, "true".to_string(), self.token.line_number, self.token.line_text.clone(), TokenCategory::Factor));
+			return Some(ParserState::ExpressionTerm);
+		}
+		Some(ParserState::Expression)
+	}
+
+	//	We get here when we see <factor>++ or <factor>-- at the start of a statement.  That's
+	//	only allowed as the step of a for (e.g. for i=0; i<10; i++ {...}) where it's
+	//	converted to i += 1
+	fn for_step_inc_dec(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: for_step_inc_dec \"{}\" context={:?}", self.token.token_value, self.parser_context)}
+
+		if *self.parser_context.last().unwrap() != ParserContext::For{
+			return Some(ParserState::Error(format!("Syntax Error: {}{} is not allowed as a statement.  Use {} {}= 1", self.statement_factor.token_value, self.token.token_value, self.statement_factor.token_value, &self.token.token_value[0..1])));
+		}
+
+		let mut op = self.token.clone();
+		op.token_category = TokenCategory::AssignmentOp;
+		if self.token.token_type == TokenType::PP{
+			op.token_type = TokenType::ASG_ADD_EQ;
+			op.token_value = "+=".to_string();
+		}else{
+			op.token_type = TokenType::ASG_SUB_EQ;
+			op.token_value = "-=".to_string();
+		}
+
+		self.parser_context.push(ParserContext::Assignment);
+		self.assignment_target = self.statement_factor.clone();
+		self.assignment_operator = op;
+		self.infix_expression.clear();
+		self.infix_expression.push(Token::new2(TokenType::INTEGER, "1".to_string(), self.token.line_number, self.token.line_text.clone(), TokenCategory::Factor));
 		None
 	}
 
@@ -1031,6 +1365,17 @@ impl <'a> Parser<'a>{
 			_ => return Some(ParserState::Error(format!("Syntax Error: Target of an assignment must be an ID or ID:QUAL or ID[<index>] or ID:QUAL[<index>]. We found: {} context={:?}", self.statement_factor.token_type, self.parser_context))),
 		}
 
+		//	the last target of a destructuring assignment
+		if self.assignment_targets.len() > 0{
+			if let Some(error) = self.check_destructuring_target(){
+				return Some(error);
+			}
+			if self.token.token_type != TokenType::ASG_EQ || self.look_ahead_test_token(TokenType::NEW){
+				return Some(ParserState::Error(format!("Syntax Error: A destructuring assignment must use '=' and can't instantiate a struct. We found: {}", self.token.token_value)));
+			}
+			self.assignment_targets.push(self.statement_factor.clone());
+		}
+
 		//  Set the context
 		self.parser_context.push(ParserContext::Assignment);
 
@@ -1070,9 +1415,25 @@ impl <'a> Parser<'a>{
 		//	create a function call specific paren counter and set it
 		//	to 1.
 		self.function_call_paren_counters.push(1);
+		self.argument_names.push(vec!(String::new()));
 
 		//	clear the expression list
 		self.infix_expression.clear();
+
+		//	a method call's first argument is the instance
+		if self.statement_factor.token_type == TokenType::QUALIFIED_ID{
+			let method_call = self.statement_factor.clone();
+			if let Some(error) = self.push_method_instance(&method_call){
+				return Some(error);
+			}
+			if self.look_ahead_test_token(TokenType::RPAREN){
+				self.statement_list_item_counter = 0;
+				return Some(ParserState::ExpressionTerm);
+			}
+			self.push_method_argument_separator();
+			self.statement_list_item_counter = 1;
+			return None;
+		}
 		
 		//	look ahead 1 token and if we see a  ")" then there are no
 		//	arguments in this call and when that's the case, we initialize
@@ -1134,7 +1495,7 @@ impl <'a> Parser<'a>{
 					indexed_id.token_category = TokenCategory::IndexedId;	
 				}
 				self.infix_expression.push(indexed_id.clone());
-			}else if self.token.token_value.find(':') != None{
+			}else if self.token.token_value.replace("::", "").find(':') != None{
 				let mut struct_ref = self.token.clone();
 				struct_ref.token_type = TokenType::QUALIFIED_ID;
 				self.infix_expression.push(struct_ref.clone());
@@ -1142,6 +1503,12 @@ impl <'a> Parser<'a>{
 				//	NOT a function call, OR indexed id save the input token (some kind fo factor)
 				self.infix_expression.push(self.token.clone());
 			}
+		}else if self.token.token_type == TokenType::QUALIFIED_ID && self.look_ahead_test_token(TokenType::LPAREN){
+			//	a method call (inst:method(...))
+			return Some(ParserState::FunctionCallArg);
+		}else if (self.token.token_type == TokenType::WILDCARD || self.token.token_type == TokenType::REGEX)
+				&& *self.parser_context.last().unwrap() != ParserContext::When{
+			return Some(ParserState::Error(format!("Syntax error from expression_factor, line {}: '{}' may only be used as a when pattern", self.token.line_number, self.token.token_value)));
 		}else{
 			//  Not an ID at all
 			self.infix_expression.push(self.token.clone());
@@ -1149,6 +1516,66 @@ impl <'a> Parser<'a>{
 		None
 	}
 
+	//	when <low>..<high> {...}.  Save the low bound; the high bound is the rest of the pattern
+	fn when_range (&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: when_range \"{}\" context={:?}", self.token.token_value, self.parser_context)}
+
+		if *self.parser_context.last().unwrap() != ParserContext::When || self.when_guard{
+			return Some(ParserState::Error(format!("Syntax error from when_range, line {}: a range ('..') may only be used as a when pattern", self.token.line_number)));
+		}
+		if self.when_range.is_some(){
+			return Some(ParserState::Error(format!("Syntax error from when_range, line {}: a when pattern can only have one '..'", self.token.line_number)));
+		}
+
+		self.do_infix_to_postfix();
+		self.when_range_low = self.postfix_expression.clone();
+		self.when_range = Some(self.token.clone());
+		self.infix_expression.clear();
+		None
+	}
+
+	//	when <patterns> if <guard> {...}.  The IF ends the last pattern
+	fn when_guard (&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: when_guard \"{}\" context={:?}", self.token.token_value, self.parser_context)}
+
+		//	anywhere else a verb following an expression is a plain syntax error
+		if *self.parser_context.last().unwrap() != ParserContext::When || self.token.token_type != TokenType::IF || self.when_guard{
+			let key = ParserStateTransitionKey::new(ParserState::ExpressionTerm, self.token.token_category);
+			return Some(ParserState::Error(self.syntax_error_text(&key, self.token.clone())));
+		}
+
+		if let Some(error) = self.when_pattern_end(){
+			return Some(error);
+		}
+		self.when_guard = true;
+		None
+	}
+
+	//	Add the pattern just parsed to the when's pattern list.  A range becomes
+	//	<low> RANGE <high>.  A '_' or a regex has to be the whole pattern.
+	fn when_pattern_end (&mut self) -> Option<ParserState>{
+		self.do_infix_to_postfix();
+		self.infix_expression.clear();
+		let mut pattern = self.postfix_expression.clone();
+
+		let is_special = |t : &Token| t.token_type == TokenType::WILDCARD || t.token_type == TokenType::REGEX;
+		if pattern.is_empty() || (self.when_range.is_some() && self.when_range_low.is_empty()){
+			return Some(ParserState::Error(format!("Syntax error from when_pattern_end, line {}: missing when pattern", self.token.line_number)));
+		}
+		if (pattern.len() > 1 || self.when_range.is_some()) && (pattern.iter().any(is_special) || self.when_range_low.iter().any(is_special)){
+			return Some(ParserState::Error(format!("Syntax error from when_pattern_end, line {}: '_' and regexes can't be part of an expression or a range", self.token.line_number)));
+		}
+
+		if let Some(range) = self.when_range.take(){
+			let mut low = std::mem::take(&mut self.when_range_low);
+			low.push(range);
+			low.append(&mut pattern);
+			pattern = low;
+		}
+		self.when_patterns.push(pattern);
+		None
+	}
+
 	//	We've seen ID and this is the "(" following which indicates that the ID
 	//	is a function and this is a function call.  And, we have not added the
 	//	the function call to the infix expression yet. We do that here.
@@ -1186,9 +1613,12 @@ impl <'a> Parser<'a>{
 		//	Add the Argument delimiter
 		self.infix_expression.push(lparen_arg_token.clone());
 
+		//	A method call always has at least one argument, the instance
+		let is_method_call = function_call_token.token_value.replace("::", "").find(':') != None;
+
 		//	now, we're currently looking at the LPAREN that begins the function call's
 		//  argument list, look ahead to see if, perhaps, there are no arguments.
-		if self.look_ahead_test_token(TokenType::RPAREN){
+		if !is_method_call && self.look_ahead_test_token(TokenType::RPAREN){
 			//	if see an RPAREN, we have to assume that this function call
 			//  has no arguments.
 
@@ -1209,6 +1639,7 @@ impl <'a> Parser<'a>{
 
 		//	Create a comma counter for this function call
 		self.comma_counters.push(0);
+		self.argument_names.push(vec!(String::new()));
 
 		//	and add the location of this call to the locations vector
 		self.function_call_locations.push(loc);
@@ -1224,10 +1655,96 @@ impl <'a> Parser<'a>{
 
 		self.parser_context.push(ParserContext::FunctionCallTerm);
 
+		if is_method_call{
+			if let Some(error) = self.push_method_instance(&function_call_token){
+				return Some(error);
+			}
+			if self.look_ahead_test_token(TokenType::RPAREN){
+				return Some(ParserState::ExpressionTerm);
+			}
+			self.push_method_argument_separator();
+			*self.comma_counters.last_mut().unwrap() += 1;
+		}
+
 		//	return a non-standard state
 		Some(ParserState::Expression)
 	}
 
+	//	inst:method(...) passes a reference to inst as the first argument, which is the
+	//	method's self parameter.  The instance has to be a variable holding a struct
+	fn push_method_instance(&mut self, method_call : &Token) -> Option<ParserState>{
+		let (instance, method) = method_call.token_value.split_once(':').unwrap();
+		if method.replace("::", "").find(':') != None{
+			return Some(ParserState::Error(format!("Syntax error from push_method_instance: {} can't be called.  A method can only be called as <struct variable>:<method>(...)", method_call.token_value)));
+		}
+
+		let mut instance_address = method_call.clone();
+		instance_address.token_type = TokenType::IDADDR;
+		instance_address.token_category = TokenCategory::Factor;
+		instance_address.token_value = instance.to_string();
+		instance_address.arg_names.clear();
+		self.infix_expression.push(instance_address);
+		None
+	}
+
+	//	The ',' between the instance of a method call and the arguments that follow it
+	fn push_method_argument_separator(&mut self){
+		let mut separator = self.token.clone();
+		separator.token_type = TokenType::ARG_SEPARATOR;
+		separator.token_category = TokenCategory::ArgSeparator;
+		separator.token_value = ",".to_string();
+		self.infix_expression.push(separator);
+
+		if let Some(names) = self.argument_names.last_mut(){
+			names.push(String::new());
+		}
+	}
+
+	//	name: at the start of a function call argument.  The name isn't part of the
+	//	expression, it's remembered with the call and matched against the called
+	//	function's parameters when code is generated
+	fn named_argument(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: named_argument \"{}\" context={:?}", self.token.token_value, self.parser_context)}
+
+		if !self.is_argument_start(){
+			return Some(ParserState::Error(format!("Syntax error from named_argument: '{}:' can only name a function call argument", self.token.token_value)));
+		}
+
+		let names = self.argument_names.last_mut().unwrap();
+		if names.iter().any(|name| *name == self.token.token_value){
+			return Some(ParserState::Error(format!("Syntax error from named_argument: argument '{}' is named more than once", self.token.token_value)));
+		}
+		*names.last_mut().unwrap() = self.token.token_value.clone();
+		None
+	}
+
+	//	...<expression> at the start of a function call argument.  The array is spread
+	//	into arguments when the call is made.  Like a name, the spread is remembered with
+	//	the call (as an argument named "...")
+	fn spread_argument(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: spread_argument \"{}\" context={:?}", self.token.token_value, self.parser_context)}
+
+		if !self.is_argument_start(){
+			return Some(ParserState::Error("Syntax error from spread_argument: '...' can only precede a function call argument".to_string()));
+		}
+
+		*self.argument_names.last_mut().unwrap().last_mut().unwrap() = self.token.token_value.clone();
+		None
+	}
+
+	//	Are we looking at the beginning of an argument of a function call?
+	fn is_argument_start(&self) -> bool{
+		let context = self.parser_context.last().unwrap();
+		if *context != ParserContext::FunctionCallTerm && *context != ParserContext::FunctionCallStatement{
+			return false;
+		}
+
+		match self.infix_expression.last(){
+			None => *context == ParserContext::FunctionCallStatement,
+			Some(t) => t.token_type == TokenType::LPAREN_ARG || t.token_type == TokenType::ARG_SEPARATOR,
+		}
+	}
+
 	//	we get here because we saw a COMMA.  Increment the general
 	//	comma counter (which counts commas across the entire expression)
 	//
@@ -1259,14 +1776,33 @@ impl <'a> Parser<'a>{
 				self.comma_counters[last as usize] += 1;
 			}
 
+			if let Some(names) = self.argument_names.last_mut(){
+				names.push(String::new());
+			}
+
 			//	Since we're in a FunctionCallTerm or FunctionCallStatement context, convert the
 			//  Comma to an ARG_SEPARATOR
 			token.token_type = TokenType::ARG_SEPARATOR;
 			token.token_category = TokenCategory::ArgSeparator;
+		}else if (*context == ParserContext::Simple && self.simple.token_type == TokenType::RETURN)
+				|| (*context == ParserContext::Assignment && self.assignment_targets.len() > 0){
+			//	return a, b; or x, y = a, b;  The values become an array literal
+			if !self.value_list{
+				self.value_list = true;
+				self.infix_expression.insert(0, Token::new2(TokenType::LBRACKET, "[".to_string(), self.token.line_number, self.token.line_text.clone(), TokenCategory::LBracket));
+			}
+			token.token_type = TokenType::LIST_SEPARATOR;
+			token.token_category = TokenCategory::ListSeparator;
 		}else if *context == ParserContext::CollectionSource{
 			//  just accept this contet
 		}else if *context == ParserContext::IndexedTarget{
 			//	and this context as well?????
+		}else if *context == ParserContext::When{
+			//	when a, b, c {...} the comma ends a pattern
+			if self.when_guard{
+				return Some(ParserState::Error(format!("Syntax error from expression_list_item, line {}: the guard must follow the last when pattern", self.token.line_number)));
+			}
+			return self.when_pattern_end();
 		}else{
 			panic!("from expression_list_item:  unknown parser context: {}", context);
 		}
@@ -1303,11 +1839,42 @@ impl <'a> Parser<'a>{
 		None
 	}
 
+	//	global <id> [= <expression>];  The initializer, if there is one, is an
+	//	ordinary expression that's collected like a struct member's initializer
+	fn global_id(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: global_id \"{}\"", self.token.token_value)}
+
+		if self.token.token_type != TokenType::ID{
+			return Some(ParserState::Error(format!("Syntax error from global_id, line {}: Expected a variable name following GLOBAL. Saw '{}' instead", line!(), self.token.token_value)));
+		}
+		self.global_id = self.token.clone();
+		self.infix_expression.clear();
+		None
+	}
+
+	fn global_init(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: global_init \"{}\"", self.token.token_value)}
+
+		if self.token.token_type != TokenType::ASG_EQ{
+			return Some(ParserState::Error(format!("Syntax error from global_init, line {}: A global is initialized with '=', not '{}'", line!(), self.token.token_value)));
+		}
+		self.parser_context.push(ParserContext::Global);
+		self.infix_expression.clear();
+		None
+	}
+
+	//	global <id>; -- there's no initializer so the global starts out uninitialized
+	fn global_add(&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: global_add \"{}\"", self.global_id.token_value)}
+		self.model.add_global_variable(self.global_id.clone(), Vec::new());
+		None
+	}
+
 	fn verb (&mut self) -> Option<ParserState>{
 		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: verb \"{}\" context:{:?} braces={}", self.token.token_value, self.parser_context, self.brace_counter);}
 		self.infix_expression.clear();
 		match self.token.token_type{
-			TokenType::PRINT | TokenType::EPRINT | TokenType::PRINTLN | TokenType::EPRINTLN | TokenType::RETURN  =>  {
+			TokenType::PRINT | TokenType::EPRINT | TokenType::PRINTLN | TokenType::EPRINTLN | TokenType::RETURN | TokenType::THROW  =>  {
 				self.parser_context.push(ParserContext::Simple);
 				self.simple = self.token.clone();
 			},
@@ -1321,12 +1888,46 @@ impl <'a> Parser<'a>{
 			},
 			TokenType::IF => self.parser_context.push(ParserContext::If),
 			TokenType::WHILE => self.parser_context.push(ParserContext::While),
+
+			//	do {...} until <condition>;  the "{" must follow immediately
+			TokenType::DO => {
+				if !self.look_ahead_test_token(TokenType::LBRACE){
+					let token = self.next_token();
+					return Some(ParserState::Error(format!("Syntax error from verb, line {}: Missing '{}' following DO. Saw '{}' instead", line!(), '{', token.token_value)));
+				}
+				self.parser_context.push(ParserContext::Do);
+				return Some(ParserState::ExpressionTerm);
+			},
+
+			//	try {...} catch <id> {...};  like do, the "{" must follow immediately
+			TokenType::TRY => {
+				if !self.look_ahead_test_token(TokenType::LBRACE){
+					let token = self.next_token();
+					return Some(ParserState::Error(format!("Syntax error from verb, line {}: Missing '{}' following TRY. Saw '{}' instead", line!(), '{', token.token_value)));
+				}
+				self.parser_context.push(ParserContext::Try);
+				return Some(ParserState::ExpressionTerm);
+			},
+
+			//	for <init>; <condition>; <step> {...}.  The init is just an assignment
+			//	statement so go parse it as one (unless there isn't one)
+			TokenType::FOR => {
+				self.parser_context.push(ParserContext::For);
+				if self.look_ahead_test_token(TokenType::SEMI){
+					self.next_token();
+					return self.for_condition_begin();
+				}
+				return Some(ParserState::Statement);
+			},
 			TokenType::EVAL => {
 				self.new_eval_brace_counter();
 				self.parser_context.push(ParserContext::Eval);
 			},
 			TokenType::WHEN => if let ParserContext::Eval = self.parser_context.last().unwrap(){
 				self.parser_context.push(ParserContext::When);
+				self.when_patterns.clear();
+				self.when_range = None;
+				self.when_guard = false;
 			}else{
 				return Some(ParserState::Error(format!("Syntax Error:  from Parser.verb: 'WHEN' may only appear as an arm to EVALUATE")));
 			},
@@ -1420,9 +2021,96 @@ impl <'a> Parser<'a>{
 		None
 	}
 	
+	//	A keyword at the start of a statement must be either the UNTIL that ends
+	//	a do, the CATCH that ends a try or an ELSEIF
+	fn keyword (&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: keyword \"{}\"", self.token.token_value)}
+
+		match self.token.token_type{
+			TokenType::UNTIL => self.keyword_until(),
+			TokenType::CATCH => self.keyword_catch(),
+			TokenType::ELSEIF => self.keyword_elseif(),
+			_ => Some(ParserState::Error(format!("Syntax error from keyword, line {}: '{}' is not allowed here", line!(), self.token.token_value))),
+		}
+	}
+
+	//	When rbrace saw the end of the do block it set the context to Until.
+	fn keyword_until (&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: keyword_until \"{}\"", self.token.token_value)}
+
+		if self.token.token_type != TokenType::UNTIL || *self.parser_context.last().unwrap() != ParserContext::Until{
+			return Some(ParserState::Error(format!("Syntax error from keyword_until, line {}: '{}' is not allowed here", line!(), self.token.token_value)));
+		}
+
+		self.infix_expression.clear();
+		None
+	}
+
+	//	When rbrace saw the end of the try block it set the context to Catch.  The
+	//	name of the variable that receives the error is optional:  catch {...}
+	fn keyword_catch (&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: keyword_catch \"{}\"", self.token.token_value)}
+
+		if *self.parser_context.last().unwrap() != ParserContext::Catch{
+			return Some(ParserState::Error(format!("Syntax error from keyword_catch, line {}: CATCH must follow the end of a TRY block", line!())));
+		}
+
+		self.catch_variable = Token::new();
+		if !self.look_ahead_test_token(TokenType::LBRACE){
+			let token = self.next_token();
+			if token.token_type != TokenType::ID{
+				return Some(ParserState::Error(format!("Syntax error from keyword_catch, line {}: Expected a variable name following CATCH. Saw '{}' instead", line!(), token.token_value)));
+			}
+			self.catch_variable = token;
+
+			if !self.look_ahead_test_token(TokenType::LBRACE){
+				let token = self.next_token();
+				return Some(ParserState::Error(format!("Syntax error from keyword_catch, line {}: Missing '{}' following CATCH {}. Saw '{}' instead", line!(), '{', self.catch_variable.token_value, token.token_value)));
+			}
+		}
+
+		self.infix_expression.clear();
+		Some(ParserState::ExpressionTerm)
+	}
+
+	//	An ELSEIF is like an ELSE except that it's followed by a condition.  It
+	//	doesn't consume the top of the if_model_context stack because there may
+	//	be more elseif arms or an else after it.  When rbrace saw the end of the
+	//	IF (or ELSEIF) block it set the context to ElseIf.  The condition is
+	//	added to the model when we see the "{"
+	fn keyword_elseif (&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: keyword_elseif \"{}\"", self.token.token_value)}
+
+		if self.if_model_context.is_empty() || *self.parser_context.last().unwrap() != ParserContext::ElseIf{
+			return Some(ParserState::Error(format!("Syntax error from keyword_elseif, line {}: ELSEIF must follow the end of an IF or ELSEIF block", line!())));
+		}
+
+		self.infix_expression.clear();
+		None
+	}
+
 	fn keyword_loop (&mut self) -> Option<ParserState>{
 		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: keyword_loop \"{}\"", self.token.token_value)}
 		self.model.add_loop_statement();
+
+		//	the loop's "}" closes this context rather than one the loop is in (e.g. a do or a try)
+		self.parser_context.push(ParserContext::Loop);
+		None
+	}
+
+	//	outer: foreach row rows {...}.  The label names the loop for break outer; and continue outer;
+	fn loop_label (&mut self) -> Option<ParserState>{
+		if self.cli.is_debug_bit(TRACE_PARSER_STATES) {eprintln!("    Action: loop_label \"{}\"", self.token.token_value)}
+
+		let label = self.token.clone();
+		let next = self.next_token();
+		self.tokenizer.push_back();
+		match next.token_type{
+			TokenType::WHILE | TokenType::FOR | TokenType::FOREACH | TokenType::LOOP | TokenType::DO => {},
+			_ => return Some(ParserState::Error(format!("Syntax error from loop_label, line {}: the label '{}' must be followed by while, for, foreach, loop or do. Saw '{}' instead", label.line_number, label.token_value, next.token_value))),
+		}
+
+		self.model.add_loop_label(label);
 		None
 	}
 
@@ -1670,11 +2358,40 @@ impl <'a> Parser<'a>{
 				self.if_model_context.push(self.model.add_if_statement(self.postfix_expression.clone()));
 			},
 
+			ParserContext::ElseIf => {
+				self.do_infix_to_postfix();
+				self.model.add_elseif_statement(*self.if_model_context.last().unwrap(), self.postfix_expression.clone());
+			},
+
 			ParserContext::While => {
 				self.do_infix_to_postfix();
 				self.model.add_while_statement(self.postfix_expression.clone());
 			},
 
+			//	for <init>; <condition>; <step> {
+			ParserContext::Assignment => {
+				let step_context = self.parser_context.pop().unwrap();
+				if *self.parser_context.last().unwrap() != ParserContext::For{
+					abend!(format!("from Action lbrace:  I don't know what to do with this context: {}", step_context));
+				}
+				if self.assignment_targets.len() > 0{
+					return Some(ParserState::Error(format!("Syntax Error: The step of a for can't be a destructuring assignment")));
+				}
+				self.do_infix_to_postfix();
+				self.model.add_for_statement(self.for_condition.clone(), Some((self.assignment_target.clone(), self.assignment_operator.clone(), self.assignment_target_index_expression.clone(), self.postfix_expression.clone())));
+				*self.parser_context.last_mut().unwrap() = ParserContext::ForBlock;
+			},
+
+			ParserContext::Do => self.model.add_do_statement(),
+			ParserContext::Try => self.model.add_try_statement(),
+			ParserContext::Catch => self.model.add_catch_statement(self.catch_variable.clone()),
+
+			//	for <init>; <condition>; {
+			ParserContext::For => {
+				self.model.add_for_statement(self.for_condition.clone(), None);
+				*self.parser_context.last_mut().unwrap() = ParserContext::ForBlock;
+			},
+
 			ParserContext::Foreach => {
 				self.do_infix_to_postfix();
 				for t in &self.postfix_expression{
@@ -1691,8 +2408,18 @@ impl <'a> Parser<'a>{
 
 			ParserContext::When => {
 				self.increment_eval_brace_counter();
-				self.do_infix_to_postfix();
-				self.model.add_when_statement(self.postfix_expression.clone(), *self.eval_model_context.last().unwrap());
+				let mut guard = Vec::new();
+				if self.when_guard{
+					self.do_infix_to_postfix();
+					if self.postfix_expression.iter().any(|t| t.token_type == TokenType::WILDCARD || t.token_type == TokenType::REGEX){
+						return Some(ParserState::Error(format!("Syntax error from lbrace, line {}: '_' and regexes are when patterns, they can't be used in a guard", self.token.line_number)));
+					}
+					guard = self.postfix_expression.clone();
+				}else if let Some(error) = self.when_pattern_end(){
+					return Some(error);
+				}
+				let patterns = std::mem::take(&mut self.when_patterns);
+				self.model.add_when_statement(patterns, guard, *self.eval_model_context.last().unwrap());
 			},
 
 			_=> abend!(format!("from Action lbrace:  I don't know what to do with this context: {}", self.parser_context.last().unwrap())),
@@ -1719,20 +2446,45 @@ impl <'a> Parser<'a>{
 				self.decrement_eval_brace_counter();
 			},
 
-			ParserContext::If => {
+			ParserContext::If | ParserContext::ElseIf => {
 				//	When we're in an IF context and we see the "}" we can see either
-				//	and ELSE or something else as the next token.  If we don't see
-				//	an ELSE, we don't care about this IF any more so we can remove
+				//	and ELSE, an ELSEIF or something else as the next token.  If we don't see
+				//	an ELSE or ELSEIF, we don't care about this IF any more so we can remove
 				//	its self_model_context from the stack (assuming there is one)
 
 				//	see also the comments above "fn keyword_else"
 				let if_ctx_len = self.if_model_context.len();
-				if !self.look_ahead_test_token(TokenType::ELSE) && if_ctx_len > 0{
+				if !self.look_ahead_test_multiple(&[TokenType::ELSE, TokenType::ELSEIF].to_vec()) && if_ctx_len > 0{
 					if if_ctx_len > 0 {
 						self.if_model_context.pop();
 					}					
 				}
 				self.parser_context.pop();
+
+				//	keyword_elseif uses this to make sure the elseif follows an if block
+				if self.look_ahead_test_token(TokenType::ELSEIF){
+					self.parser_context.push(ParserContext::ElseIf);
+				}
+			},
+
+			//	The end of a do block must be followed by an until
+			ParserContext::Do => {
+				if !self.look_ahead_test_token(TokenType::UNTIL){
+					let token = self.next_token();
+					return Some(ParserState::Error(format!("Syntax error from rbrace, line {}: Missing UNTIL following the do block. Saw '{}' instead", line!(), token.token_value)));
+				}
+				self.parser_context.pop();
+				self.parser_context.push(ParserContext::Until);
+			},
+
+			//	The end of a try block must be followed by a catch
+			ParserContext::Try => {
+				if !self.look_ahead_test_token(TokenType::CATCH){
+					let token = self.next_token();
+					return Some(ParserState::Error(format!("Syntax error from rbrace, line {}: Missing CATCH following the try block. Saw '{}' instead", line!(), token.token_value)));
+				}
+				self.parser_context.pop();
+				self.parser_context.push(ParserContext::Catch);
 			},
 
 			ParserContext::Struct => {
@@ -1805,6 +2557,9 @@ impl <'a> Parser<'a>{
 
 				//	assume we're going to see a "{" so increment the brace counter here. 
 				self.brace_counter += 1;
+
+				//	the foreach's "}" closes this context rather than one the foreach is in
+				self.parser_context.push(ParserContext::Foreach);
 			},		
 			TokenType::LBRACKET | TokenType::LBRACE => abend!(format!("From foreach_source:  Foreach <target> <collection literal> not implemented yet.")),
 			_=> return Some(ParserState::Error("From foreach_source:  Expecting an ID, '[', or '{'".to_string())),
@@ -1830,6 +2585,17 @@ impl <'a> Parser<'a>{
 		let loc = self.function_call_locations.pop().unwrap();
 		let count = self.comma_counters.pop().unwrap() + 1;
 		self.infix_expression[loc].token_type = TokenType::FUNCTION_CALL(count as usize);
+		self.infix_expression[loc].arg_names = self.take_argument_names();
+	}
+
+	//	The names of the arguments of the function call that just ended.  The list is
+	//	left empty if they're all positional
+	fn take_argument_names(&mut self) -> Vec<String>{
+		let names = self.argument_names.pop().unwrap();
+		if names.iter().all(|name| name.is_empty()){
+			return Vec::new();
+		}
+		names
 	}
 
 
@@ -1913,8 +2679,8 @@ impl <'a> Parser<'a>{
 			binary_op.token_type = TokenType::UPLUS;
 		}else if self.token.token_type == TokenType::SUB{
 			binary_op.token_type = TokenType::UMINUS;
-		}else if self.token.token_type == TokenType::DAMNIT{
-			// don't do anything for damnit because it's already a unary op
+		}else if self.token.token_type == TokenType::DAMNIT || self.token.token_type == TokenType::BITWISE_NOT{
+			// don't do anything for damnit or '~' because they're already unary ops
 		}else{
 			panic! ("from binary_op_to_unary_op.  Houston we have a problem.  Trying to convert an illegal binary op to unary {}", self.token.token_type);
 		}
@@ -12,11 +12,24 @@ pub enum StructMemberType{
 	Substruct(Substruct),
 }
 
+impl StructMemberType{
+	pub fn name(&self) -> &str{
+		match self{
+			StructMemberType::Field(field) => &field.name,
+			StructMemberType::Substruct(substruct) => &substruct.name,
+		}
+	}
+}
+
 
 #[derive(Clone)]
 pub struct Struct{
 	pub name : String,
 	pub members : MemberList,
+
+	//	the methods declared in the struct and the name of the function that
+	//	implements each of them (see Tokenizer::lift_methods)
+	pub methods : Vec<(String, String)>,
 }
 
 impl Struct{
@@ -24,6 +37,7 @@ impl Struct{
 		Struct{
 			name : name.to_string(),
 			members : MemberList::new(),
+			methods : Vec::new(),
 		}
 	}
 
@@ -35,6 +49,9 @@ impl Struct{
 				StructMemberType::Substruct (child)  => println!("    child: {}",child.name),
 			}
 		}
+		for (method, function_name) in &self.methods{
+			println!("    method: {} ({})", method, function_name);
+		}
 	}
 }
 
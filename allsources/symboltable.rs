@@ -34,18 +34,22 @@ use names::*;
 pub enum SymbolTableEntryType{
 	NormalSymbolEntry(NormalSymbolEntry),
 	StructMemberEntry(StructMemberEntry),
+	StructMethodEntry(StructMethodEntry),
 	StructEntry(StructEntry),
 	StructChildEntry(StructChildEntry),
 	LiteralEntry(LiteralEntry),
+	GlobalEntry(GlobalEntry),
 }
 impl fmt::Display for SymbolTableEntryType{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self{
 			SymbolTableEntryType::NormalSymbolEntry(_) => write!(f,"NormalSymbolEntry"),
 			SymbolTableEntryType::StructMemberEntry(_) => write!(f,"StructMemberEntry"),
+			SymbolTableEntryType::StructMethodEntry(_) => write!(f,"StructMethodEntry"),
 			SymbolTableEntryType::StructEntry(_) => write!(f,"StructEntry"),
 			SymbolTableEntryType::StructChildEntry(_) => write!(f,"StructChildEntry"),
 			SymbolTableEntryType::LiteralEntry(_) => write!(f,"LiteralEntry"),
+			SymbolTableEntryType::GlobalEntry(_) => write!(f,"GlobalEntry"),
 		}
 	}
 }
@@ -153,6 +157,28 @@ impl fmt::Display for StructMemberEntry{
 	}
 }
 
+//	A method of a struct instance (inst:method) and the function that implements it
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+pub struct StructMethodEntry{
+	pub function_name : String,
+	pub interner : usize,
+}
+
+impl StructMethodEntry{
+	pub fn new(function_name : &String, interner : usize) -> StructMethodEntry{
+		StructMethodEntry{
+			function_name : function_name.clone(),
+			interner : interner,
+		}
+	}
+}
+
+impl fmt::Display for StructMethodEntry{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "function: {} interner: {}", self.function_name, self.interner)
+	}
+}
+
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
 pub struct StructChildEntry{
 	id : usize,
@@ -177,6 +203,29 @@ impl fmt::Display for StructChildEntry{
 	}
 }
 
+//	A global variable lives in the global operand frame.  Its index is its
+//	address in that frame's only block
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+pub struct GlobalEntry{
+	pub index : usize,
+	pub interner : usize,
+}
+
+impl GlobalEntry{
+	pub fn new(index : usize, interner : usize) -> GlobalEntry{
+		GlobalEntry{
+			index : index,
+			interner : interner,
+		}
+	}
+}
+
+impl fmt::Display for GlobalEntry{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "index:{} interner: {}", self.index, self.interner)
+	}
+}
+
 //	*********************************************************************
 //	******  Literals
 //	*********************************************************************
@@ -303,15 +352,29 @@ impl fmt::Display for LiteralEntry{
 *****************************************/
 
 struct GlobalSymbols{
+	//	the index of the next global variable
+	current_index : usize,
 	globals : HashMap<String, SymbolTableEntryType>,
 }
 
 impl GlobalSymbols{
 	fn new() -> GlobalSymbols{
 		GlobalSymbols{
+			current_index : 0,
 			globals : HashMap::new(),
 		}
 	}
+
+	fn dump_table(&self){
+		eprintln!("Globals");
+		for symbol in self.globals.keys(){
+			match self.globals.get(symbol).unwrap(){
+				SymbolTableEntryType::GlobalEntry(g) => eprintln!("Global-\"{}\" {}",symbol, g),
+				SymbolTableEntryType::LiteralEntry(l) => eprintln!("LiteralEntry-\"{}\" {}",symbol, l),
+				e => eprintln!("{}-\"{}\"",e, symbol),
+			}
+		}
+	}
 }
 
 struct SymbolTableBlock{
@@ -343,9 +406,11 @@ impl SymbolTableBlock{
 				match self.table.get(symbol).unwrap(){
 					SymbolTableEntryType::NormalSymbolEntry(n) => eprintln!("Normal-\"{}\" {}",symbol, n),
 					SymbolTableEntryType::StructMemberEntry(m) => eprintln!("StructMemberEntry-\"{}\" {}",symbol, m),
+					SymbolTableEntryType::StructMethodEntry(m) => eprintln!("StructMethodEntry-\"{}\" {}",symbol, m),
 					SymbolTableEntryType::StructEntry(s) => eprintln!("StructEntry-\"{}\" {}",symbol,s),
 					SymbolTableEntryType::StructChildEntry(s) => eprintln!("StructChildEntry-\"{}\" {}",symbol,s),
 					SymbolTableEntryType::LiteralEntry(l) => eprintln!("LiteralEntry-\"{}\" {}",symbol,l),
+					SymbolTableEntryType::GlobalEntry(g) => eprintln!("Global-\"{}\" {}",symbol,g),
 				}
 			}	
 		}
@@ -402,10 +467,12 @@ impl SymbolTableFrame{
 				Some(r) => {
 					match r{
 						SymbolTableEntryType::StructMemberEntry(_) => println!("======= {} is Struct Member",symbol),
+						SymbolTableEntryType::StructMethodEntry(_) => println!("======= {} is Struct Method",symbol),
 						SymbolTableEntryType::NormalSymbolEntry(_) => println!("======= {} is Normal",symbol),
 						SymbolTableEntryType::StructEntry(_) => println!("======= {} is Struct",symbol),
 						SymbolTableEntryType::StructChildEntry(_) => println!("======= {} is StructChild",symbol),
 						SymbolTableEntryType::LiteralEntry(_) => println!("======= {} is Literal",symbol),
+						SymbolTableEntryType::GlobalEntry(_) => println!("======= {} is Global",symbol),
 					}
 					return;
 				}
@@ -551,6 +618,25 @@ impl SymbolTableFrame{
 		struct_member_entry
 	}
 
+	//	Add a method of a struct instance (inst:method).  Like a member it takes no space in
+	//	the operand stack, it just remembers which function implements the method
+	pub fn add_struct_method(&mut self, method_ref : String, function_name : &String, interner : usize) -> StructMethodEntry{
+		if self.table.len() == 0{
+			abend!(format!("FROM SymbolTable.add_struct_method ({}): trying to get current block but tables is empty", line!()-1));
+		}
+
+		//	get the current block
+		let symbol_table_block = self.table.last_mut().unwrap();
+
+		if symbol_table_block.table.contains_key(&method_ref){
+			abend!(format!("From add_struct_method: Duplicate struct method {}", method_ref));
+		}
+
+		let struct_method_entry = StructMethodEntry::new(function_name, interner);
+		symbol_table_block.table.insert(method_ref, SymbolTableEntryType::StructMethodEntry(struct_method_entry.clone()));
+		struct_method_entry
+	}
+
 	//	Add a substruct member.
 	pub fn _add_struct_substruct(&mut self, member_ref : String, parent_index : usize, member_index : usize, child_index : usize, interner : usize) -> StructChildEntry{
 		if self.table.len() == 0{
@@ -768,6 +854,16 @@ impl <'a> SymbolTable <'a>{
 		frame.add_struct_member(member_ref, struct_number, member_index, interner)
 	}
 
+	//	Add a method of a struct instance, e.g. "p:shift" implemented by "point.shift"
+	pub fn add_struct_method(&mut self, method_ref : String, function_name : &String) -> StructMethodEntry{
+		//	get the element of the function symbol list
+		let frame : &mut SymbolTableFrame = self.tables.last_mut().unwrap(); 
+
+		let interner = self.names.borrow_mut().add(&method_ref);
+
+		frame.add_struct_method(method_ref, function_name, interner)
+	}
+
 	//  Fetch the address of a normal entry.  Panic if the symbol is a StructMemberEntry
 	pub fn get_normal_address(&mut self, key : &String) -> NormalSymbolEntry{
 		//	get the element of the function symbol list
@@ -800,6 +896,9 @@ impl <'a> SymbolTable <'a>{
 	}
 
 	fn symbol_table_dump_absolutely(&self){
+		if self.globals.globals.len() > 0{
+			self.globals.dump_table();
+		}
 		for table in &self.tables{
 			if table.table.len() > 0{
 				table.dump_table();
@@ -815,4 +914,24 @@ impl <'a> SymbolTable <'a>{
 	pub fn add_global_symbol(&mut self, symbol : &String, entry : &SymbolTableEntryType){
 		self.globals.globals.insert(symbol.clone(), entry.clone());
 	}
+
+	//	Look up a global (a variable or a lit) ignoring any function's symbols
+	pub fn get_global_symbol_entry(&self, symbol : &String) -> Option<SymbolTableEntryType>{
+		self.globals.globals.get(symbol).cloned()
+	}
+
+	//	Add a global variable and return its entry.  Globals share a name space with
+	//	global literals so a duplicate of either is an error
+	pub fn add_global_variable(&mut self, symbol : &String) -> GlobalEntry{
+		if self.globals.globals.contains_key(symbol){
+			abend!(format!("from SymbolTable.add_global_variable:  duplicates are not allowed.  Symbol={}", symbol));
+		}
+
+		let interner = self.names.borrow_mut().add(symbol);
+		let global_entry = GlobalEntry::new(self.globals.current_index, interner);
+		self.globals.globals.insert(symbol.clone(), SymbolTableEntryType::GlobalEntry(global_entry.clone()));
+		self.globals.current_index += 1;
+
+		global_entry
+	}
 }
@@ -8,6 +8,7 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::env;
 use std::io::BufReader;
+use std::path::{Path, PathBuf};
 use std::fmt;
 use std::collections::HashSet;
 use std::collections::HashMap;
@@ -32,6 +33,11 @@ pub enum TokenCategory{
 	FunctionDeclaration,	//  "fn" or "entry"
 	Factor,					// Id, Number, Boolean, String
 	Literal,				// Keyword Litreal
+	Global,					// Keyword global
+	Use,					// Keyword use (struct embedding)
+	NamedArgument,			// name: in a function call's argument list
+	Ellipsis,				// ... of a rest parameter or a spread argument
+	Range,					// .. of a when pattern (when 1..10)
 	FunctionCall,
 	IndexedId,
 	IndexedQualifiedId,		// may not need this
@@ -83,6 +89,11 @@ impl fmt::Display for TokenCategory {
 			TokenCategory::StructDeclaration	=> write!(f, "StructDeclaration"),
 			TokenCategory::Factor				=> write!(f, "Factor"),
 			TokenCategory::Literal				=> write!(f, "Literal"),
+			TokenCategory::Global				=> write!(f, "Global"),
+			TokenCategory::Use					=> write!(f, "Use"),
+			TokenCategory::NamedArgument		=> write!(f, "NamedArgument"),
+			TokenCategory::Ellipsis				=> write!(f, "Ellipsis"),
+			TokenCategory::Range				=> write!(f, "Range"),
 			TokenCategory::FunctionCall			=> write!(f, "FunctionCall"),
 			TokenCategory::IndexedId			=> write!(f, "IndexedId"),
 			TokenCategory::IndexedQualifiedId	=> write!(f, "IndexedQualifiedId"),
@@ -136,6 +147,7 @@ pub enum TokenType{
 	STRING,						// quoted with either ' or "
 	INTEGER,
 	FLOAT,
+	DECIMAL,					// 12.34d
 	BOOL,						// "true" or "false"
 	EOF,						// end of input file
 	KEYWORD_OR_ID,
@@ -146,6 +158,9 @@ pub enum TokenType{
 
 	ELSE, 
 	DO,
+	TRY,
+	CATCH,
+	THROW,
 
 	IF,
 	ELSEIF,
@@ -167,10 +182,13 @@ pub enum TokenType{
 	ASSIGNMENT,
 	ENTRY,
 	INCLUDE,
+	IMPORT,
 	BREAK,
 	CONTINUE,
 	RETURN,
-	IN,
+	IN,				// membership (x in collection)
+	NOT,			// only valid as the first half of "not in"
+	NOT_IN,			// "not in" combined into a single operator
 	TRUE,
 	FALSE,
 
@@ -193,6 +211,10 @@ pub enum TokenType{
 
 	BITWISE_OR,
 	BITWISE_AND,
+	BITWISE_XOR,		// '^'
+	BITWISE_NOT,		// '~'
+	SHIFT_LEFT,			// '<<'
+	SHIFT_RIGHT,		// '>>'
 
 	PP,
 	MM,
@@ -210,12 +232,18 @@ pub enum TokenType{
 	ASG_APPEND_EQ,		// '.='
 	ASG_OR_EQ,			// '|='
 	ASG_AND_EQ,			// '&-'
+	ASG_XOR_EQ,			// '^='
+	ASG_POWER_EQ,		// '**='
+	ASG_SHIFT_LEFT_EQ,	// '<<='
+	ASG_SHIFT_RIGHT_EQ,	// '>>='
 	ASG_EQ,				// '='
 
 	ADD,
 	SUB,
 	MUL,
+	POWER,				// '**'
 	DIV,
+	INT_DIV,			// integer division (a div b)
 	MOD,
 	CONCAT,
 
@@ -230,6 +258,8 @@ pub enum TokenType{
 	COMMA,
 
 	LITERAL,
+	GLOBAL,
+	USE,
 
 	//  Synthetic Tokens.  These are created by transforming a "normal" token
 	//	into a special token used in specific contexts
@@ -254,6 +284,18 @@ pub enum TokenType{
 	INDEX_EXPRESSION,
 	ARGUMENT_EXPRESSION,
 	NEW_COLLECTION,			// when x=[] is encountered this is the expression token
+	LAND_TEST,				// marks the end of the left operand of '&&' in a postfix expression
+	LOR_TEST,				// marks the end of the left operand of '||' in a postfix expression
+	QUESTION,				// cond ? a : b
+	COLON,
+	TERNARY_TEST,			// marks the end of the condition of '?' in a postfix expression
+	TERNARY_ELSE,			// marks the end of the first choice of '?' in a postfix expression
+	FUNCTION_LITERAL,		// fn(...){...} in an expression;  the value is the name of the lifted function
+	NAMED_ARG,				// name: in front of a function call argument;  the value is the name without the ':'
+	ELLIPSIS,				// fn f(a, ...rest) and f(...array)
+	RANGE,					// when <low>..<high>
+	REGEX,					// when /<regex>/;  the value is the regex without the '/'s
+	WILDCARD,				// when _ (matches anything)
 }
 
 impl fmt::Display for TokenType {
@@ -270,11 +312,15 @@ impl fmt::Display for TokenType {
 			TokenType::STRING  => write!(f, "STRING"), 
 			TokenType::INTEGER  => write!(f, "INTEGER"), 
 			TokenType::FLOAT  => write!(f, "FLOAT"), 
+			TokenType::DECIMAL  => write!(f, "DECIMAL"), 
 			TokenType::BOOL  => write!(f, "BOOL"),
 			TokenType::LINE_COMMENT  => write!(f, "LINE_COMMENT"),
 			TokenType::BLOCK_COMMENT  => write!(f, "BLOCK_COMMENT"),
 			TokenType::ELSE  => write!(f, "ELSE"), 
 			TokenType::DO  => write!(f, "DO"),
+			TokenType::TRY  => write!(f, "TRY"),
+			TokenType::CATCH  => write!(f, "CATCH"),
+			TokenType::THROW  => write!(f, "THROW"),
 			TokenType::IF  => write!(f, "IF"),
 			TokenType::ELSEIF  => write!(f, "ELSEIF"),
 			TokenType::UNTIL  => write!(f, "UNTIL"),
@@ -300,10 +346,13 @@ impl fmt::Display for TokenType {
 
 			TokenType::ENTRY  => write!(f, "ENTRY"),
 			TokenType::INCLUDE  => write!(f, "INCLUDE"),
+			TokenType::IMPORT  => write!(f, "IMPORT"),
 			TokenType::BREAK  => write!(f, "BREAK"),
 			TokenType::CONTINUE  => write!(f, "CONTINUE"),
 			TokenType::RETURN  => write!(f, "RETURN"),
 			TokenType::IN  => write!(f, "IN"),
+			TokenType::NOT  => write!(f, "NOT"),
+			TokenType::NOT_IN  => write!(f, "NOT_IN"),
 			TokenType::TRUE  => write!(f, "TRUE"),
 			TokenType::FALSE  => write!(f, "FALSE"),
 
@@ -319,6 +368,10 @@ impl fmt::Display for TokenType {
 
 			TokenType::BITWISE_OR  => write!(f, "BITWISE_OR"),
 			TokenType::BITWISE_AND  => write!(f, "BITWISE_AND"),
+			TokenType::BITWISE_XOR  => write!(f, "BITWISE_XOR"),
+			TokenType::BITWISE_NOT  => write!(f, "BITWISE_NOT"),
+			TokenType::SHIFT_LEFT  => write!(f, "SHIFT_LEFT"),
+			TokenType::SHIFT_RIGHT  => write!(f, "SHIFT_RIGHT"),
 	   
 			TokenType::PP  => write!(f, "PP"),
 			TokenType::MM  => write!(f, "MM"),
@@ -335,12 +388,18 @@ impl fmt::Display for TokenType {
 			TokenType::ASG_APPEND_EQ  => write!(f, "ASG_APPEND_EQ"),
 			TokenType::ASG_OR_EQ  => write!(f, "ASG_OR_EQ"),
 			TokenType::ASG_AND_EQ  => write!(f, "ASG_AND_EQ"),
+			TokenType::ASG_XOR_EQ  => write!(f, "ASG_XOR_EQ"),
+			TokenType::ASG_POWER_EQ  => write!(f, "ASG_POWER_EQ"),
+			TokenType::ASG_SHIFT_LEFT_EQ  => write!(f, "ASG_SHIFT_LEFT_EQ"),
+			TokenType::ASG_SHIFT_RIGHT_EQ  => write!(f, "ASG_SHIFT_RIGHT_EQ"),
 			TokenType::ASG_EQ  => write!(f, "ASG_EQ"),
 
 			TokenType::ADD  => write!(f, "ADD"),
 			TokenType::SUB  => write!(f, "SUB"),
 			TokenType::DIV  => write!(f, "DIV"),
+			TokenType::INT_DIV  => write!(f, "INT_DIV"),
 			TokenType::MUL  => write!(f, "MUL"),
+			TokenType::POWER  => write!(f, "POWER"),
 			TokenType::MOD  => write!(f, "MOD"),
 			TokenType::CONCAT  => write!(f, "CONCAT"),
 
@@ -356,6 +415,8 @@ impl fmt::Display for TokenType {
 			TokenType::COMMA  => write!(f, "COMMA"),
 
 			TokenType::LITERAL  => write!(f, "LITERAL"),	   
+			TokenType::GLOBAL  => write!(f, "GLOBAL"),
+			TokenType::USE  => write!(f, "USE"),
 
 			TokenType::LBRACKET  => write!(f, "LBRACKET"),
 			TokenType::RBRACKET  => write!(f, "RBACKET"),
@@ -378,6 +439,18 @@ impl fmt::Display for TokenType {
 			TokenType::INDEX_EXPRESSION => write!(f, "INDEX_EXPRESSION"),
 			TokenType::ARGUMENT_EXPRESSION => write!(f, "ARGUMENT_EXPRESSION"),
 			TokenType::NEW_COLLECTION => write!(f, "NEW_COLLECTION"),
+			TokenType::LAND_TEST => write!(f, "LAND_TEST"),
+			TokenType::LOR_TEST => write!(f, "LOR_TEST"),
+			TokenType::QUESTION => write!(f, "QUESTION"),
+			TokenType::COLON => write!(f, "COLON"),
+			TokenType::TERNARY_TEST => write!(f, "TERNARY_TEST"),
+			TokenType::TERNARY_ELSE => write!(f, "TERNARY_ELSE"),
+			TokenType::FUNCTION_LITERAL => write!(f, "FUNCTION_LITERAL"),
+			TokenType::NAMED_ARG => write!(f, "NAMED_ARG"),
+			TokenType::ELLIPSIS => write!(f, "ELLIPSIS"),
+			TokenType::RANGE => write!(f, "RANGE"),
+			TokenType::REGEX => write!(f, "REGEX"),
+			TokenType::WILDCARD => write!(f, "WILDCARD"),
 		}
     }
 }
@@ -392,6 +465,8 @@ enum TokenizerStates{
 	ERR,
 	SINGLE_Q,
 	DOUBLE_Q,
+	TRIPLE_Q,			// """ multi-line string """
+	RAW_Q,				// r"string with no escapes"
 	NUMBER,
 	REAL_NUMBER,
 	KEYWORD_OR_ID,
@@ -410,6 +485,8 @@ impl fmt::Display for TokenizerStates {
            TokenizerStates::ERR => write!(f, "TokenizerStates::ERR"),
            TokenizerStates::SINGLE_Q => write!(f, "TokenizerStates::SINGLE_Q"),
            TokenizerStates::DOUBLE_Q => write!(f, "TokenizerStates::DOUBLE_Q"),
+           TokenizerStates::TRIPLE_Q => write!(f, "TokenizerStates::TRIPLE_Q"),
+           TokenizerStates::RAW_Q => write!(f, "TokenizerStates::RAW_Q"),
            TokenizerStates::NUMBER => write!(f, "TokenizerStates::NUMBER"),
            TokenizerStates::REAL_NUMBER => write!(f, "TokenizerStates::REAL_NUMBER"),
            TokenizerStates::KEYWORD_OR_ID => write!(f, "TokenizerStates::KEYWORD_OR_ID"),
@@ -428,6 +505,8 @@ pub struct Token{
 	pub line_number : i32,
 	pub line_text : String,
 	pub token_category : TokenCategory,
+	pub file_name : String,			// the imported file the token came from (empty for the main source)
+	pub arg_names : Vec<String>,	// for a function call, the names of named arguments ("" when positional)
 }
 
 impl Token{
@@ -438,6 +517,8 @@ impl Token{
 			line_number:0,
 			line_text:String::new(),
 			token_category : TokenCategory::Unknown,
+			file_name:String::new(),
+			arg_names:Vec::new(),
 		}
 	}
 
@@ -448,6 +529,8 @@ impl Token{
 			line_number:line_number,
 			line_text:text,	
 			token_category:cat,
+			file_name:String::new(),
+			arg_names:Vec::new(),
 		}
 	}
 
@@ -457,7 +540,18 @@ impl Token{
 			token_value:val.to_string(),
 			line_number:99999,
 			line_text:"make_string".to_string(),	
-			token_category:TokenCategory::Internal
+			token_category:TokenCategory::Internal,
+			file_name:String::new(),
+			arg_names:Vec::new(),
+		}
+	}
+
+	//	Where the token came from for error messages (e.g. "line 10" or "line 10 of geometry.cpl")
+	pub fn location(&self) -> String{
+		if self.file_name.is_empty(){
+			format!("line {}", self.line_number)
+		}else{
+			format!("line {} of {}", self.line_number, self.file_name)
 		}
 	}
 }
@@ -479,7 +573,7 @@ pub fn token_list_text(tokens : &Vec<Token>) -> String{
 			display.push_str(" ");
 		}
 
-		if t.token_type == TokenType::INTEGER || t.token_type == TokenType::FLOAT || t.token_type == TokenType::STRING ||t.token_type==TokenType::BOOL{
+		if t.token_type == TokenType::INTEGER || t.token_type == TokenType::FLOAT || t.token_type == TokenType::DECIMAL || t.token_type == TokenType::STRING ||t.token_type==TokenType::BOOL{
 			display.push_str(&format!("\"{}\"",t.token_value));
 		}else{
 			display.push_str(&format!("{}<{}>",t.token_value, t.token_type));
@@ -492,6 +586,7 @@ pub fn token_list_text(tokens : &Vec<Token>) -> String{
 pub struct Tokenizer<'a>{
     tokenizer_state : TokenizerStates,
     reader : Vec<BufReader<File>>,
+	reader_paths : Vec<String>,			// the file each reader is reading
 	include_pending : bool,
 	line : String,
     line_index : usize,
@@ -503,15 +598,47 @@ pub struct Tokenizer<'a>{
 	raw_tokens : Vec<Token>,
 	raw_index : usize,
 
+	//	the pieces of the string being collected that precede each ${expression}:
+	//	the literal text and the text of the expression
+	interpolations : Vec<(String, String)>,
+
+	//	function literals lifted out of expressions:  the name of the function
+	//	that was created and the names it might capture from the enclosing function
+	pub function_literals : HashMap<String, Vec<String>>,
+
+	//	imported files (canonical path) and the namespace each was compiled into,
+	//	the chain of files being imported (to detect cycles) and the tokens of the
+	//	imported files, which are placed ahead of the main source
+	imported : HashMap<String, String>,
+	import_stack : Vec<String>,
+	import_tokens : Vec<Token>,
+
 	pub categories : HashMap<TokenType,TokenCategory>,
 }
 
 impl<'a> Tokenizer<'_>{
     pub fn new (pcli : & 'a CLI<'a>) -> Tokenizer<'a>{
+		let mut izer = Tokenizer::open(pcli, pcli.source());
+
+		//	Replace the import statements with the imported files
+		let source = std::fs::canonicalize(pcli.source()).map(|p| p.to_string_lossy().to_string()).unwrap_or(pcli.source().to_string());
+		izer.import_stack.push(source);
+		let tokens = std::mem::take(&mut izer.raw_tokens);
+		let tokens = izer.resolve_imports(tokens);
+		izer.raw_tokens = std::mem::take(&mut izer.import_tokens);
+		izer.raw_tokens.extend(tokens);
+
+		izer.lift_function_literals();
+		izer
+	}
+
+	//	Tokenize a single file
+	fn open (pcli : & 'a CLI<'a>, path : &str) -> Tokenizer<'a>{
 
       	let mut izer = Tokenizer{
             tokenizer_state : TokenizerStates::BASE,
 			reader : Vec::new(),
+			reader_paths : Vec::new(),
 			include_pending : false,
 			line : String::new(),
             line_index : 0,
@@ -522,6 +649,11 @@ impl<'a> Tokenizer<'_>{
 			cli : pcli,
 			raw_tokens : Vec::new(),
 			raw_index : 0,
+			interpolations : Vec::new(),
+			function_literals : HashMap::new(),
+			imported : HashMap::new(),
+			import_stack : Vec::new(),
+			import_tokens : Vec::new(),
 
 			categories : HashMap::from (
 				[
@@ -531,6 +663,8 @@ impl<'a> Tokenizer<'_>{
 					(TokenType::ENTRY,TokenCategory::FunctionDeclaration),
 
 					(TokenType::DO,TokenCategory::Verb),
+					(TokenType::TRY,TokenCategory::Verb),
+					(TokenType::THROW,TokenCategory::Verb),
 					(TokenType::IF,TokenCategory::Verb),
 					(TokenType::FOR,TokenCategory::Verb),
 					(TokenType::WHILE,TokenCategory::Verb),
@@ -547,7 +681,10 @@ impl<'a> Tokenizer<'_>{
 
 					(TokenType::RETURN,TokenCategory::Verb),
 					(TokenType::INCLUDE,TokenCategory::Verb),
+					(TokenType::IMPORT,TokenCategory::Misc),
 					(TokenType::LITERAL,TokenCategory::Literal),
+					(TokenType::GLOBAL,TokenCategory::Global),
+					(TokenType::USE,TokenCategory::Use),
 
 					//	Need to recognize special case verbs
 					(TokenType::ELSE,TokenCategory::Else), 
@@ -560,9 +697,15 @@ impl<'a> Tokenizer<'_>{
 
 					(TokenType::ID,TokenCategory::Factor),
 					(TokenType::QUALIFIED_ID,TokenCategory::Factor),
+					(TokenType::NAMED_ARG,TokenCategory::NamedArgument),
+					(TokenType::ELLIPSIS,TokenCategory::Ellipsis),
+					(TokenType::RANGE,TokenCategory::Range),
+					(TokenType::REGEX,TokenCategory::Factor),
+					(TokenType::WILDCARD,TokenCategory::Factor),
 					(TokenType::STRING,TokenCategory::Factor),			// quoted with either ' or "
 					(TokenType::INTEGER,TokenCategory::Factor),
 					(TokenType::FLOAT,TokenCategory::Factor),
+					(TokenType::DECIMAL,TokenCategory::Factor),
 					(TokenType::BOOL,TokenCategory::Factor),
 
 					(TokenType::FUNCTION_CALL(0),TokenCategory::FunctionCall),
@@ -573,8 +716,11 @@ impl<'a> Tokenizer<'_>{
 					
 					(TokenType::ELSEIF,TokenCategory::Keyword),
 					(TokenType::UNTIL,TokenCategory::Keyword),
+					(TokenType::CATCH,TokenCategory::Keyword),
 
-					(TokenType::IN,TokenCategory::Keyword),
+					(TokenType::IN,TokenCategory::BinaryOp),
+					(TokenType::NOT_IN,TokenCategory::BinaryOp),
+					(TokenType::NOT,TokenCategory::Misc),
 					(TokenType::TRUE,TokenCategory::Factor),
 					(TokenType::FALSE,TokenCategory::Factor),
 
@@ -590,6 +736,9 @@ impl<'a> Tokenizer<'_>{
 				
 					(TokenType::BITWISE_OR,TokenCategory::BinaryOp),
 					(TokenType::BITWISE_AND,TokenCategory::BinaryOp),
+					(TokenType::BITWISE_XOR,TokenCategory::BinaryOp),
+					(TokenType::SHIFT_LEFT,TokenCategory::BinaryOp),
+					(TokenType::SHIFT_RIGHT,TokenCategory::BinaryOp),
 				
 					(TokenType::PP,TokenCategory::IncDec),
 					(TokenType::MM,TokenCategory::IncDec),
@@ -598,12 +747,15 @@ impl<'a> Tokenizer<'_>{
 					(TokenType::UPLUS,TokenCategory::UnaryOp),
 					(TokenType::LENGTH_OF,TokenCategory::UnaryOp),
 					(TokenType::DAMNIT,TokenCategory::UnaryOp),
+					(TokenType::BITWISE_NOT,TokenCategory::UnaryOp),
 
 					(TokenType::ADD,TokenCategory::BinaryOp),
 					(TokenType::SUB,TokenCategory::BinaryOp),
 				
 					(TokenType::MUL,TokenCategory::BinaryOp),
+					(TokenType::POWER,TokenCategory::BinaryOp),
 					(TokenType::DIV,TokenCategory::BinaryOp),
+					(TokenType::INT_DIV,TokenCategory::BinaryOp),
 					(TokenType::MOD,TokenCategory::BinaryOp),
 					(TokenType::CONCAT,TokenCategory::BinaryOp),
 
@@ -639,6 +791,10 @@ impl<'a> Tokenizer<'_>{
 					(TokenType::ASG_APPEND_EQ,TokenCategory::AssignmentOp),
 					(TokenType::ASG_OR_EQ,TokenCategory::AssignmentOp),
 					(TokenType::ASG_AND_EQ,TokenCategory::AssignmentOp),
+					(TokenType::ASG_XOR_EQ,TokenCategory::AssignmentOp),
+					(TokenType::ASG_POWER_EQ,TokenCategory::AssignmentOp),
+					(TokenType::ASG_SHIFT_LEFT_EQ,TokenCategory::AssignmentOp),
+					(TokenType::ASG_SHIFT_RIGHT_EQ,TokenCategory::AssignmentOp),
 					(TokenType::ASG_EQ,TokenCategory::AssignmentOp),
 
 					(TokenType::NONE,TokenCategory::Misc),			// Used for initialization only
@@ -654,19 +810,28 @@ impl<'a> Tokenizer<'_>{
 					(TokenType::INDEX_EXPRESSION,TokenCategory::IndexExpression),
 					(TokenType::ARGUMENT_EXPRESSION,TokenCategory::ArgumentExpression),	
 					(TokenType::NEW_COLLECTION,TokenCategory::Unknown),	
+					(TokenType::LAND_TEST,TokenCategory::Misc),
+					(TokenType::LOR_TEST,TokenCategory::Misc),
+					(TokenType::QUESTION,TokenCategory::BinaryOp),
+					(TokenType::COLON,TokenCategory::BinaryOp),
+					(TokenType::TERNARY_TEST,TokenCategory::Misc),
+					(TokenType::TERNARY_ELSE,TokenCategory::Misc),
+					(TokenType::FUNCTION_LITERAL,TokenCategory::Factor),
 				]
 			),
 		};
 
-		let file = File::open(pcli.source());
+		let file = File::open(path);
 		match file{
-			Err	(m) => {println!("       Can't open File : {}",pcli.source()); std::process::exit(1);},
+			Err	(m) => {println!("       Can't open File : {}",path); std::process::exit(1);},
 			Ok(f) => izer.reader.push(BufReader::new(f)),
 		}
+		izer.reader_paths.push(path.to_string());
 
 
 		//	Build the raw_tokens list (so that next token and push_back work)
 		izer.tokenize();
+		izer.lift_methods();
 		izer
     }
 
@@ -679,8 +844,16 @@ impl<'a> Tokenizer<'_>{
 		println!("get_category:  can't find token {}",token_type);
 		TokenCategory::Unknown
 	}
+	//	A name from an imported module (e.g. geo::area) isn't qualified unless it also has
+	//	a single ':' (e.g. p:geo::point)
+	//	A name with a single trailing ':' (e.g. f(start: 1)) names a function argument
+	fn is_named_argument (&self) -> bool{
+		let value = &self.token.token_value;
+		value.len() > 1 && value.ends_with(':') && value[..value.len()-1].find(':') == None
+	}
+
 	fn is_qualified_id (&self) -> bool{
-		if self.token.token_value.find(':') != None{
+		if self.token.token_value.replace("::", "").find(':') != None{
 			return true;
 		}else{
 			return false;
@@ -694,6 +867,9 @@ impl<'a> Tokenizer<'_>{
 		match self.token.token_value.as_str(){
 			"else"			=> TokenType::ELSE, 
 			"do"			=> TokenType::DO,
+			"try"			=> TokenType::TRY,
+			"catch"			=> TokenType::CATCH,
+			"throw"			=> TokenType::THROW,
 			"if"			=> TokenType::IF,
 			"elseif"		=> TokenType::ELSEIF,
 			"until"			=> TokenType::UNTIL,
@@ -711,10 +887,13 @@ impl<'a> Tokenizer<'_>{
 			"new"			=> TokenType::NEW,
 			"entry"			=> TokenType::ENTRY,
 			"include"		=> TokenType::INCLUDE,
+			"import"		=> TokenType::IMPORT,
 			"break"			=> TokenType::BREAK,
 			"continue"		=> TokenType::CONTINUE,
 			"return"		=> TokenType::RETURN,
 			"in"			=> TokenType::IN,
+			"div"			=> TokenType::INT_DIV,
+			"not"			=> TokenType::NOT,
 			"true"			=> TokenType::BOOL,
 			"false"			=> TokenType::BOOL,
 			"print"			=> TokenType::PRINT,
@@ -725,9 +904,17 @@ impl<'a> Tokenizer<'_>{
 
 			"literal"		=> TokenType::LITERAL,
 			"lit"			=> TokenType::LITERAL,
+			"global"		=> TokenType::GLOBAL,
+			"use"			=> TokenType::USE,
+
+			//	a ':' by itself is part of cond ? a : b
+			":"				=> TokenType::COLON,
 			
 			//	Nope, it's an ID.  It might be a qualified ID (e.g. foo:bar)
-			_ 				=> 	if self.is_qualified_id(){
+			_ 				=> 	if self.is_named_argument(){
+									self.token.token_value.pop();
+									TokenType::NAMED_ARG
+								}else if self.is_qualified_id(){
 									TokenType::QUALIFIED_ID
 								}else{
 									TokenType::ID
@@ -804,13 +991,508 @@ impl<'a> Tokenizer<'_>{
 		let mut token = self.machine();
 		while token.token_type != TokenType::EOF{
 			//println!(".....{} \"{}\"", token.token_type, token.token_value);
-			self.raw_tokens.push(token);
+			self.push_raw_token(token);
 			token = self.machine();
 		}
 		//  End the list with the EOF
 		self.raw_tokens.push(token);
 	}
 
+	fn push_raw_token(&mut self, token : Token){
+		//	"not in" is two words but a single operator.  Fold the "in" into
+		//	the "not" that precedes it
+		if token.token_type == TokenType::IN{
+			if let Some(last) = self.raw_tokens.last_mut(){
+				if last.token_type == TokenType::NOT{
+					last.token_type = TokenType::NOT_IN;
+					last.token_value = "not in".to_string();
+					last.token_category = TokenCategory::BinaryOp;
+					return;
+				}
+			}
+		}
+
+		if token.token_type == TokenType::STRING && !self.interpolations.is_empty(){
+			self.push_interpolated_string(token);
+			return;
+		}
+		self.raw_tokens.push(token);
+	}
+
+	//	A string with ${expression} in it becomes a concatenation.  For example
+	//
+	//		"Total: ${total} for ${name:first}"
+	//
+	//	becomes
+	//
+	//		("Total: " . (total) . " for " . (name:first) . "")
+	//
+	//	The text of each expression is fed back through the tokenizer so it can be
+	//	any expression, including another interpolated string
+	fn push_interpolated_string(&mut self, token : Token){
+		let interpolations = std::mem::take(&mut self.interpolations);
+		let make = |izer : &Self, token_type : TokenType, value : &str| Token{
+			token_type,
+			token_value : value.to_string(),
+			token_category : izer.get_category(token_type),
+			..token.clone()
+		};
+
+		self.raw_tokens.push(make(self, TokenType::LPAREN, "("));
+		for (i, (literal, expression)) in interpolations.iter().enumerate(){
+			//	the first piece is kept, even if empty, so "${n}" is a string even if n is a number
+			if i == 0 || !literal.is_empty(){
+				if i > 0{
+					self.raw_tokens.push(make(self, TokenType::CONCAT, "."));
+				}
+				self.raw_tokens.push(make(self, TokenType::STRING, literal));
+			}
+			self.raw_tokens.push(make(self, TokenType::CONCAT, "."));
+			self.raw_tokens.push(make(self, TokenType::LPAREN, "("));
+
+			//	a trailing ';' ends the expression's last token and then is dropped
+			let pending = std::mem::take(&mut self.last_char);
+			self.last_char = format!("{};", expression).chars().rev().collect();
+			loop{
+				let expression_token = self.machine();
+				if expression_token.token_type == TokenType::SEMI && self.last_char.is_empty(){
+					break;
+				}
+				self.push_raw_token(expression_token);
+			}
+			self.last_char = pending;
+
+			self.raw_tokens.push(make(self, TokenType::RPAREN, ")"));
+		}
+		if !token.token_value.is_empty(){
+			self.raw_tokens.push(make(self, TokenType::CONCAT, "."));
+			self.raw_tokens.push(make(self, TokenType::STRING, &token.token_value));
+		}
+		self.raw_tokens.push(make(self, TokenType::RPAREN, ")"));
+	}
+
+	//	Collect the text of a ${expression} up to its closing '}'.  Braces inside
+	//	quotes don't count
+	fn interpolated_expression(&mut self) -> String{
+		let mut expression = String::new();
+		let mut depth = 1;
+		let mut quote : Option<char> = None;
+		loop{
+			let c = match self.next_char(){
+				Some(c) if c != '\n' => c,
+				_ => abend!(format!("Missing '}}' after '${{{}' in a string at line {}", expression, self.line_number)),
+			};
+			match (quote, c){
+				(Some(q), _) if c == q	=> quote = None,
+				(Some(_), _)			=> {},
+				(None, '"' | '\'')		=> quote = Some(c),
+				(None, '{')				=> depth += 1,
+				(None, '}')				=> {
+					depth -= 1;
+					if depth == 0{
+						break;
+					}
+				},
+				_						=> {},
+			}
+			expression.push(c);
+		}
+
+		if expression.trim().is_empty(){
+			abend!(format!("Empty '${{}}' in a string at line {}", self.line_number));
+		}
+		expression
+	}
+
+
+	//	Locate an include or import file.  Unless the path is absolute, look relative to the
+	//	file doing the including, then in the -I directories, then in the CPL_PATH directories
+	//	and, last, relative to the working directory
+	fn find_source_file(&self, path : &str, including_file : &str, what : &str, location : &str) -> String{
+		let mut search_list : Vec<PathBuf> = Vec::new();
+		if Path::new(path).is_absolute(){
+			search_list.push(PathBuf::from(path));
+		}else{
+			if let Some(dir) = Path::new(including_file).parent(){
+				search_list.push(dir.join(path));
+			}
+			for dir in self.cli.include_dirs(){
+				search_list.push(Path::new(dir).join(path));
+			}
+			if let Some(cpl_path) = env::var_os("CPL_PATH"){
+				for dir in env::split_paths(&cpl_path){
+					search_list.push(dir.join(path));
+				}
+			}
+			search_list.push(PathBuf::from(path));
+		}
+		//	an including file with no directory gives the same path as the last entry
+		let mut seen : HashSet<PathBuf> = HashSet::new();
+		search_list.retain(|candidate| seen.insert(candidate.clone()));
+
+		for candidate in &search_list{
+			if candidate.is_file(){
+				if self.cli.is_debug_bit(TRACE_TOKENIZER) {println!("Tokenizer.find_source_file {} found {}", path, candidate.display());}
+				return candidate.to_string_lossy().to_string();
+			}
+		}
+
+		let mut error_text = format!("Can't find {} file {} at {}.  Looked for:", what, path, location);
+		for candidate in &search_list{
+			error_text.push_str(&format!("\n    {}", candidate.display()));
+		}
+		abend!(error_text);
+	}
+
+	//	import "<file>" as <name>;
+	//
+	//	The file is compiled once no matter how many times (or by how many files) it's
+	//	imported.  Its functions and structs are renamed into a namespace (e.g. area
+	//	becomes geo::area) and its tokens are placed ahead of the main source.  References
+	//	to <name>::<something> in the importing file are then pointed at that namespace.
+	fn resolve_imports(&mut self, tokens : Vec<Token>) -> Vec<Token>{
+		let mut aliases : HashMap<String, String> = HashMap::new();
+		let mut rtn : Vec<Token> = Vec::new();
+		let mut depth = 0;
+		let mut i = 0;
+		while i < tokens.len(){
+			match tokens[i].token_type{
+				TokenType::LBRACE => depth += 1,
+				TokenType::RBRACE => depth -= 1,
+				TokenType::IMPORT => {
+					let location = tokens[i].location();
+					if depth > 0{
+						abend!(format!("import at {} must be outside of any function or struct", location));
+					}
+					if i + 4 >= tokens.len()
+						|| tokens[i+1].token_type != TokenType::STRING
+						|| tokens[i+2].token_type != TokenType::ID || tokens[i+2].token_value != "as"
+						|| tokens[i+3].token_type != TokenType::ID
+						|| tokens[i+4].token_type != TokenType::SEMI{
+						abend!(format!("Expecting import \"<file>\" as <name>; at {}", location));
+					}
+
+					let alias = tokens[i+3].token_value.clone();
+					if aliases.contains_key(&alias){
+						abend!(format!("{} at {} is already the name of an import", alias, location));
+					}
+					let importing_file = if tokens[i].file_name.is_empty() {self.cli.source().clone()} else {tokens[i].file_name.clone()};
+					let path = self.find_source_file(&tokens[i+1].token_value, &importing_file, "import", &location);
+					let namespace = self.import_module(&path, &alias, &location);
+					aliases.insert(alias, namespace);
+					i += 5;
+					continue;
+				},
+				_ => {},
+			}
+			rtn.push(tokens[i].clone());
+			i += 1;
+		}
+
+		//	the name is the part in front of the "::" (which may follow a struct variable e.g. p:geo::point)
+		for t in rtn.iter_mut(){
+			if t.token_type != TokenType::ID && t.token_type != TokenType::QUALIFIED_ID{
+				continue;
+			}
+			if let Some(ix) = t.token_value.find("::"){
+				let start = t.token_value[..ix].rfind(':').map(|s| s + 1).unwrap_or(0);
+				match aliases.get(&t.token_value[start..ix]){
+					Some(namespace) => t.token_value = format!("{}{}{}", &t.token_value[..start], namespace, &t.token_value[ix..]),
+					None => abend!(format!("{} at {}: {} hasn't been imported", t.token_value, t.location(), &t.token_value[start..ix])),
+				}
+			}
+		}
+		rtn
+	}
+
+	//	Tokenize an imported file (and, in turn, its imports) into its namespace and
+	//	return the namespace
+	fn import_module(&mut self, path : &str, alias : &str, location : &str) -> String{
+		let canonical = match std::fs::canonicalize(path){
+			Ok(p) => p.to_string_lossy().to_string(),
+			Err(_) => abend!(format!("Can't open import file {} at {}", path, location)),
+		};
+
+		if self.import_stack.contains(&canonical){
+			abend!(format!("Import cycle: {} at {} is already being imported", path, location));
+		}
+
+		if let Some(namespace) = self.imported.get(&canonical){
+			return namespace.clone();
+		}
+
+		//	names are per importing file, so two files may use the same name for different imports
+		let mut namespace = alias.to_string();
+		let mut n = 1;
+		while self.imported.values().any(|ns| *ns == namespace){
+			n += 1;
+			namespace = format!("{}{}", alias, n);
+		}
+		self.imported.insert(canonical.clone(), namespace.clone());
+
+		if self.cli.is_debug_bit(TRACE_TOKENIZER) {println!("Tokenizer.import_module {} as {}", path, namespace);}
+
+		self.import_stack.push(canonical);
+		let mut tokens = Tokenizer::open(self.cli, path).raw_tokens;
+		tokens.pop();			// EOF
+		for t in tokens.iter_mut(){
+			t.file_name = path.to_string();
+		}
+		let tokens = self.resolve_imports(tokens);
+		let tokens = self.namespace_declarations(tokens, &namespace);
+		self.import_tokens.extend(tokens);
+		self.import_stack.pop();
+
+		namespace
+	}
+
+	//	Rename the functions and structs declared by an imported file, and the references
+	//	to them, into its namespace
+	fn namespace_declarations(&self, mut tokens : Vec<Token>, namespace : &str) -> Vec<Token>{
+		let mut names : HashSet<String> = HashSet::new();
+		let mut structs : HashSet<String> = HashSet::new();
+		let mut depth = 0;
+		for i in 0..tokens.len(){
+			match tokens[i].token_type{
+				TokenType::LBRACE => depth += 1,
+				TokenType::RBRACE => depth -= 1,
+				TokenType::FN if depth == 0 && i + 1 < tokens.len() && tokens[i+1].token_type == TokenType::ID => {
+					names.insert(tokens[i+1].token_value.clone());
+				},
+				TokenType::STRUCT if depth == 0 && i + 1 < tokens.len() && tokens[i+1].token_type == TokenType::ID => {
+					names.insert(tokens[i+1].token_value.clone());
+					structs.insert(tokens[i+1].token_value.clone());
+				},
+				TokenType::ENTRY if depth == 0 => {
+					abend!(format!("{} at {}: an imported file can't have an entry function", tokens[i].token_value, tokens[i].location()));
+				},
+				_ => {},
+			}
+		}
+
+		//	struct members aren't renamed (although functions called by their initializers and
+		//	used structs are), but struct types of parameters (e.g. p:point) are
+		let mut depth = 0;
+		let mut in_struct = false;
+		let mut in_parameters = false;
+		for i in 0..tokens.len(){
+			match tokens[i].token_type{
+				TokenType::LBRACE => depth += 1,
+				TokenType::RBRACE => {
+					depth -= 1;
+					if depth == 0{
+						in_struct = false;
+					}
+				},
+				TokenType::STRUCT if depth == 0 => in_struct = true,
+				TokenType::LPAREN if depth == 0 && i >= 2 && tokens[i-2].token_type == TokenType::FN => in_parameters = true,
+				TokenType::RPAREN => in_parameters = false,
+				TokenType::ID if (!in_struct || depth == 0 || tokens[i-1].token_type == TokenType::USE || (i + 1 < tokens.len() && tokens[i+1].token_type == TokenType::LPAREN)) && names.contains(&tokens[i].token_value) => {
+					tokens[i].token_value = format!("{}::{}", namespace, tokens[i].token_value);
+				},
+				TokenType::QUALIFIED_ID if in_parameters => {
+					let parts : Vec<String> = tokens[i].token_value.splitn(2, ':').map(|p| p.to_string()).collect();
+					if structs.contains(&parts[1]){
+						tokens[i].token_value = format!("{}:{}::{}", parts[0], namespace, parts[1]);
+					}
+				},
+				_ => {},
+			}
+		}
+		tokens
+	}
+
+	//	A method (a fn declared in the body of a top-level struct) is moved to the end of the
+	//	file as a normal function named <struct>.<method> whose first parameter, self, is
+	//	the instance the method is called on.  So, this:
+	//
+	//		struct point{ x=0; fn shift(dx){ self:x += dx; } }
+	//
+	//	becomes:
+	//
+	//		struct point{ x=0; }
+	//		fn point.shift(self:point, dx){ self:x += dx; }
+	//
+	//	Since a name can't contain a '.', the function can only be called as a method
+	//	(p:shift(1)) which passes &p as self.
+	fn lift_methods(&mut self){
+		let mut lifted : Vec<Token> = Vec::new();
+		let mut depth = 0;
+		let mut struct_name = String::new();
+		let mut i = 0;
+		while i < self.raw_tokens.len(){
+			match self.raw_tokens[i].token_type{
+				TokenType::LBRACE => depth += 1,
+				TokenType::RBRACE => {
+					depth -= 1;
+					if depth == 0{
+						struct_name.clear();
+					}
+				},
+				TokenType::STRUCT if depth == 0 && self.raw_tokens[i+1].token_type == TokenType::ID => {
+					struct_name = self.raw_tokens[i+1].token_value.clone();
+				},
+				TokenType::FN if !struct_name.is_empty() && self.raw_tokens[i+1].token_type == TokenType::ID => {
+					if depth > 1{
+						abend!(format!("Method {} at line {}: methods can only be declared in a top-level struct", self.raw_tokens[i+1].token_value, self.raw_tokens[i].line_number));
+					}
+					if self.raw_tokens[i+2].token_type != TokenType::LPAREN{
+						abend!(format!("Method {} at line {}: expecting '(' after the name", self.raw_tokens[i+1].token_value, self.raw_tokens[i].line_number));
+					}
+
+					//	the body ends at the "}" that balances the "{" after the parameters
+					let mut end = i + 2;
+					while self.raw_tokens[end].token_type != TokenType::LBRACE{
+						if self.raw_tokens[end].token_type == TokenType::EOF{
+							abend!(format!("Method {} at line {} has no body", self.raw_tokens[i+1].token_value, self.raw_tokens[i].line_number));
+						}
+						end += 1;
+					}
+					let mut body_depth = 0;
+					loop{
+						match self.raw_tokens[end].token_type{
+							TokenType::LBRACE => body_depth += 1,
+							TokenType::RBRACE => body_depth -= 1,
+							TokenType::EOF => abend!(format!("Method {} at line {} is missing its closing '}}'", self.raw_tokens[i+1].token_value, self.raw_tokens[i].line_number)),
+							_ => {},
+						}
+						if body_depth == 0 {break}
+						end += 1;
+					}
+
+					let mut method : Vec<Token> = self.raw_tokens.drain(i..=end).collect();
+					method[1].token_value = format!("{}.{}", struct_name, method[1].token_value);
+
+					//	self goes in front of the declared parameters
+					let mut self_parameter = method[1].clone();
+					self_parameter.token_type = TokenType::QUALIFIED_ID;
+					self_parameter.token_category = *self.categories.get(&TokenType::QUALIFIED_ID).unwrap();
+					self_parameter.token_value = format!("self:{}", struct_name);
+					if method[3].token_type != TokenType::RPAREN{
+						let mut comma = method[2].clone();
+						comma.token_type = TokenType::COMMA;
+						comma.token_category = *self.categories.get(&TokenType::COMMA).unwrap();
+						comma.token_value = ",".to_string();
+						method.insert(3, comma);
+					}
+					method.insert(3, self_parameter);
+
+					lifted.extend(method);
+					continue;
+				},
+				_ => {},
+			}
+			i += 1;
+		}
+
+		let eof = self.raw_tokens.len() - 1;
+		self.raw_tokens.splice(eof..eof, lifted);
+	}
+
+	//	A function literal (fn(a,b){...}) inside a function is moved to the end of the
+	//	program as a normal function declaration with a generated name, and replaced by a
+	//	FUNCTION_LITERAL token carrying that name.  So, this:
+	//
+	//		entry main{ f = fn(a){return a+n;}; }
+	//
+	//	becomes:
+	//
+	//		entry main{ f = <FUNCTION_LITERAL __fn0>; }
+	//		fn __fn0(a){return a+n;}
+	//
+	//	Any ID in the body that isn't a parameter is a candidate for capture (n above).  Code
+	//	generation decides which candidates actually are variables of the enclosing function.
+	//	A literal inside a literal is lifted on a later pass since it's still inside braces
+	//	after its enclosing literal has been moved.
+	fn lift_function_literals(&mut self){
+		loop{
+			//	find the first fn followed by "(" that's inside a block
+			let mut depth = 0;
+			let mut start = None;
+			for (i, t) in self.raw_tokens.iter().enumerate(){
+				match t.token_type{
+					TokenType::LBRACE => depth += 1,
+					TokenType::RBRACE => depth -= 1,
+					TokenType::FN if depth > 0 && self.raw_tokens[i+1].token_type == TokenType::LPAREN => {
+						start = Some(i);
+						break;
+					},
+					_ => {},
+				}
+			}
+
+			let start = match start{
+				Some(s) => s,
+				None => return,
+			};
+
+			//	the parameters end at ")" and the body at the "}" that balances the "{" after it
+			//	(skipping a default value and the ... of a rest parameter)
+			let mut parameters : Vec<String> = Vec::new();
+			let mut end = start + 2;
+			while self.raw_tokens[end].token_type != TokenType::RPAREN{
+				match self.raw_tokens[end].token_type{
+					TokenType::ID => parameters.push(self.raw_tokens[end].token_value.clone()),
+					TokenType::COMMA | TokenType::ELLIPSIS => {},
+					TokenType::ASG_EQ => {
+						while self.raw_tokens[end+1].token_type != TokenType::COMMA && self.raw_tokens[end+1].token_type != TokenType::RPAREN{
+							end += 1;
+						}
+					},
+					_ => abend!(format!("Function literal at line {}: expecting a parameter name, found {}", self.raw_tokens[end].line_number, self.raw_tokens[end].token_value)),
+				}
+				end += 1;
+			}
+			end += 1;
+			if self.raw_tokens[end].token_type != TokenType::LBRACE{
+				abend!(format!("Function literal at line {}: expecting '{{' after the parameters", self.raw_tokens[end].line_number));
+			}
+
+			depth = 0;
+			loop{
+				match self.raw_tokens[end].token_type{
+					TokenType::LBRACE => depth += 1,
+					TokenType::RBRACE => depth -= 1,
+					TokenType::EOF => abend!(format!("Function literal at line {} is missing its closing '}}'", self.raw_tokens[start].line_number)),
+					_ => {},
+				}
+				if depth == 0 {break}
+				end += 1;
+			}
+
+			let name = format!("__fn{}", self.function_literals.len());
+
+			let mut candidates : Vec<String> = Vec::new();
+			for t in &self.raw_tokens[start..end]{
+				if t.token_type == TokenType::ID && !parameters.contains(&t.token_value) && !candidates.contains(&t.token_value){
+					candidates.push(t.token_value.clone());
+				}
+			}
+			self.function_literals.insert(name.clone(), candidates);
+
+			//	replace the literal with a token naming the function
+			let mut literal = self.raw_tokens[start].clone();
+			literal.token_type = TokenType::FUNCTION_LITERAL;
+			literal.token_category = TokenCategory::Factor;
+			literal.token_value = name.clone();
+			let mut lifted : Vec<Token> = self.raw_tokens.splice(start..=end, vec![literal]).collect();
+
+			//	and give the lifted declaration its name
+			let mut name_token = lifted[0].clone();
+			name_token.token_type = TokenType::ID;
+			name_token.token_category = TokenCategory::Factor;
+			name_token.token_value = name;
+			lifted.insert(1, name_token);
+
+			//	fn(x){...}(2) isn't compiled as a call (the (2) is taken as a separate value) so it's an error
+			if self.raw_tokens[start+1].token_type == TokenType::LPAREN{
+				abend!(format!("Function literal at line {}: a literal can't be called where it's written.  Assign it to a variable and call that", self.raw_tokens[start].line_number));
+			}
+
+			let eof = self.raw_tokens.len() - 1;
+			self.raw_tokens.splice(eof..eof, lifted);
+		}
+	}
 
 	//	retrieves the next token from the raw_tokens list and increments the index
 	pub fn next_token(&mut self) -> Token{
@@ -854,15 +1536,17 @@ impl<'a> Tokenizer<'_>{
 				},
 				TokenizerStates::EOT			=> {
 					self.tokenizer_state = TokenizerStates::BASE;
-					self.token.line_number = self.line_number;
-					self.token.line_text = self.line.trim().to_string();
 
 					if self.include_pending {
-						let file = File::open(&self.token.token_value);
+						let location = format!("line {} of {}", self.line_number, self.reader_paths.last().unwrap());
+						let including_file = self.reader_paths.last().unwrap().clone();
+						let path = self.find_source_file(&self.token.token_value, &including_file, "include", &location);
+						let file = File::open(&path);
 						match file{
-							Err	(m) => {println!("       Can't open INCLUDE File : {}",self.token.token_value); std::process::exit(1);},
+							Err	(m) => {println!("       Can't open INCLUDE File : {}",path); std::process::exit(1);},
 							Ok(f) => self.reader.push(BufReader::new(f)),
 						}
+						self.reader_paths.push(path);
 
 						self.include_pending = false;
 						self.tokenizer_state = TokenizerStates::BASE;
@@ -896,6 +1580,7 @@ impl<'a> Tokenizer<'_>{
 			if next == None{
 				if self.reader.len() > 1{
 					self.reader.pop();
+					self.reader_paths.pop();
 					self.tokenizer_state = TokenizerStates::BASE;
 					continue;
 				}else{
@@ -921,6 +1606,8 @@ impl<'a> Tokenizer<'_>{
 				TokenizerStates::BLOCK_COMMENT			=> self.state_BLOCK_COMMENT(c),
 				TokenizerStates::SINGLE_Q				=> self.state_SINGLE_Q(c),
 				TokenizerStates::DOUBLE_Q				=> self.state_DOUBLE_Q(c),
+				TokenizerStates::TRIPLE_Q				=> self.state_TRIPLE_Q(c),
+				TokenizerStates::RAW_Q					=> self.state_RAW_Q(c),
 				_ => {}
 			}
 		}
@@ -950,7 +1637,9 @@ impl<'a> Tokenizer<'_>{
 				self.last_no_white_space(c);
 				self.tokenizer_state = TokenizerStates::BOT;
 				self.token = Token::new();
-				//  remember the line that the first character of the token is on
+				//  remember the line that the first character of the token is on (a string
+				//	can end on a later line)
+				self.token.line_number = self.line_number;
 				self.token.line_text = self.line.trim().to_string();
 			}
 		}
@@ -974,7 +1663,20 @@ impl<'a> Tokenizer<'_>{
 			':'				=> self.tokenizer_state = TokenizerStates::KEYWORD_OR_ID,
 			'0'..='9' 		=> self.tokenizer_state =TokenizerStates::NUMBER,
 
-			'"' 			=> {self.token.token_value.clear(); self.tokenizer_state =TokenizerStates::DOUBLE_Q},
+			'"' 			=> {
+				self.token.token_value.clear();
+				self.tokenizer_state =TokenizerStates::DOUBLE_Q;
+
+				//	"" is an empty string and """ starts a multi-line string
+				if self.op_look_ahead(c,'"'){
+					if self.op_look_ahead(c,'"'){
+						self.tokenizer_state = TokenizerStates::TRIPLE_Q;
+					}else{
+						self.tokenizer_state = TokenizerStates::EOT;
+						self.token.token_type = TokenType::STRING;
+					}
+				}
+			},
 			'\''			=> {self.token.token_value.clear(); self.tokenizer_state =TokenizerStates::SINGLE_Q},
 			_ =>{
 				//	If we're not looking at a potential ID, Number or String then push back
@@ -1005,12 +1707,28 @@ impl<'a> Tokenizer<'_>{
 			'>'		=>	if self.op_look_ahead(c,'='){
 							self.token.token_value = ">=".to_string();
 							self.token.token_type= TokenType::GE;
+						}else if self.op_look_ahead(c,'>'){
+							if self.op_look_ahead(c,'='){
+								self.token.token_value = ">>=".to_string();
+								self.token.token_type= TokenType::ASG_SHIFT_RIGHT_EQ;
+							}else{
+								self.token.token_value = ">>".to_string();
+								self.token.token_type= TokenType::SHIFT_RIGHT;
+							}
 						}else{
 							self.token.token_type= TokenType::GT;
 						},
 			'<'		=>	if self.op_look_ahead(c,'='){
 							self.token.token_value = "<=".to_string();
 							self.token.token_type= TokenType::LE;
+						}else if self.op_look_ahead(c,'<'){
+							if self.op_look_ahead(c,'='){
+								self.token.token_value = "<<=".to_string();
+								self.token.token_type= TokenType::ASG_SHIFT_LEFT_EQ;
+							}else{
+								self.token.token_value = "<<".to_string();
+								self.token.token_type= TokenType::SHIFT_LEFT;
+							}
 						}else{
 							self.token.token_type= TokenType::LT;
 						},
@@ -1085,7 +1803,9 @@ impl<'a> Tokenizer<'_>{
 								self.tokenizer_state = TokenizerStates::BLOCK_COMMENT;
 								self.token.token_value.pop();
 							}else{
-								if self.op_look_ahead(c,'='){
+								if self.regex_allowed(){
+									self.regex();
+								}else if self.op_look_ahead(c,'='){
 									self.token.token_type= TokenType::ASG_DIV_EQ;
 								}else{
 									self.token.token_value = "/".to_string();
@@ -1096,9 +1816,24 @@ impl<'a> Tokenizer<'_>{
 			'*'		=>	if self.op_look_ahead(c,'='){
 							self.token.token_value = "*=".to_string();
 							self.token.token_type= TokenType::ASG_MUL_EQ;
+						}else if self.op_look_ahead(c,'*'){
+							if self.op_look_ahead(c,'='){
+								self.token.token_value = "**=".to_string();
+								self.token.token_type= TokenType::ASG_POWER_EQ;
+							}else{
+								self.token.token_value = "**".to_string();
+								self.token.token_type= TokenType::POWER;
+							}
 						}else{
 							self.token.token_type= TokenType::MUL;
 						},
+			'^'		=>	if self.op_look_ahead(c,'='){
+							self.token.token_value = "^=".to_string();
+							self.token.token_type= TokenType::ASG_XOR_EQ;
+						}else{
+							self.token.token_type= TokenType::BITWISE_XOR;
+						},
+			'~'		=> self.token.token_type= TokenType::BITWISE_NOT,
 			'%'		=>	if self.op_look_ahead(c,'='){
 							self.token.token_value = "%=".to_string();
 							self.token.token_type= TokenType::ASG_MOD_EQ;
@@ -1108,6 +1843,14 @@ impl<'a> Tokenizer<'_>{
 			'.'		=>	if self.op_look_ahead(c,'='){
 							self.token.token_value = ".=".to_string();
 							self.token.token_type= TokenType::ASG_APPEND_EQ;
+						}else if self.op_look_ahead(c,'.'){
+							if self.op_look_ahead(c,'.'){
+								self.token.token_value = "...".to_string();
+								self.token.token_type= TokenType::ELLIPSIS;
+							}else{
+								self.token.token_value = "..".to_string();
+								self.token.token_type= TokenType::RANGE;
+							}
 						}else{
 							self.token.token_type= TokenType::CONCAT;
 						},
@@ -1121,6 +1864,8 @@ impl<'a> Tokenizer<'_>{
 			';'		=> self.token.token_type= TokenType::SEMI,
 			','		=> self.token.token_type= TokenType::COMMA,
 			'#'		=> self.token.token_type= TokenType::LENGTH_OF,
+			'?'		=> self.token.token_type= TokenType::QUESTION,
+			'_'		=> self.token.token_type= TokenType::WILDCARD,
 			_		=> {println!("Tokenizer state_OPERATOR unknown character: <{}>",c);}       
         }
 
@@ -1131,6 +1876,59 @@ impl<'a> Tokenizer<'_>{
 
 
 
+	//	A '/' that begins a when pattern (when /^ERR/ or when "x", /^ERR/) is the start
+	//	of a regex rather than a divide.  Everywhere else it's a divide
+	fn regex_allowed(&self) -> bool{
+		let mut tokens = self.raw_tokens.iter().rev().filter(|t| t.token_category != TokenCategory::Comment);
+		match tokens.next(){
+			Some(t) if t.token_type == TokenType::WHEN => return true,
+			Some(t) if t.token_type == TokenType::COMMA => {},
+			_ => return false,
+		}
+
+		//	after a ',' it's a regex only if the ',' separates when patterns
+		let mut depth = 0;
+		for t in tokens{
+			match t.token_type{
+				TokenType::RPAREN | TokenType::RBRACKET => depth += 1,
+				TokenType::LPAREN | TokenType::LBRACKET => {
+					if depth == 0{
+						return false;
+					}
+					depth -= 1;
+				},
+				TokenType::WHEN if depth == 0 => return true,
+				TokenType::SEMI | TokenType::LBRACE | TokenType::RBRACE => return false,
+				_ => {},
+			}
+		}
+		false
+	}
+
+	//	Collect a regex up to the closing '/'.  A '/' in the regex is written \/
+	fn regex(&mut self){
+		self.token.token_value.clear();
+		loop{
+			match self.next_char(){
+				None | Some('\n') | Some('\r') => {
+					self.error_text = "Syntax err:  the regex has no closing '/'".to_string();
+					self.tokenizer_state = TokenizerStates::ERR;
+					return;
+				},
+				Some('/') => break,
+				Some('\\') => {
+					match self.next_char(){
+						Some('/') => self.token.token_value.push('/'),
+						Some(c) => {self.token.token_value.push('\\'); self.token.token_value.push(c)},
+						None => {},
+					}
+				},
+				Some(c) => self.token.token_value.push(c),
+			}
+		}
+		self.token.token_type = TokenType::REGEX;
+	}
+
 	//	the first character seen was a letter, so we know we are collecting either
 	//	an ID (i.e. something that isn't a keyword) or a keyword (some verb we know about).
 	//	keywords and ids can contain upper or lower case characters or digits.  Any other
@@ -1139,6 +1937,12 @@ impl<'a> Tokenizer<'_>{
     fn state_KEYWORD_OR_ID(&mut self, c : char){
         match c{
 			'a'..='z' |  'A'..='Z' | '0'..='9' | '_' | ':'  =>  self.value_no_white_space(c),
+
+			//	r"..." is a raw string
+			'"' if self.token.token_value == "r"	=> {
+				self.token.token_value.clear();
+				self.tokenizer_state = TokenizerStates::RAW_Q;
+			},
             _ =>   {
 						//  Save the current character as it isn't part of the current token
 						//  and set the state to EOT (end of token)
@@ -1149,11 +1953,25 @@ impl<'a> Tokenizer<'_>{
         }
     }
 
+	//	A 'd' right after a number makes it a decimal (12.34d) unless it begins a word
+	fn decimal_suffix(&mut self) -> bool{
+		match self.next_char(){
+			Some(look_ahead_char) => {
+				self.last_char.push(look_ahead_char);
+				!(look_ahead_char.is_alphanumeric() || look_ahead_char == '_')
+			},
+			None => true,
+		}
+	}
+
 	//	The first character found was a digit, now collect the rest of the number
 	fn state_NUMBER(&mut self, c : char){
         match c{
 			'0'..='9'  =>  {self.value_no_white_space(c)},		//  number is still an integer
+			//	1..10 is a range so the number ends at the first '.'
+			'.' if self.op_look_ahead(c,'.') => {self.last_char.push('.'); self.last_char.push('.'); self.tokenizer_state = TokenizerStates::EOT; self.token.token_type= TokenType::INTEGER},
 			'.'		   =>  {self.value_no_white_space(c); self.tokenizer_state = TokenizerStates::REAL_NUMBER}
+			'd' if self.decimal_suffix() => {self.tokenizer_state = TokenizerStates::EOT; self.token.token_type= TokenType::DECIMAL},
 			'\n' | '\r'| '\t' | ' ' =>  {},
             _ 		   =>  {self.last_no_white_space(c); self.tokenizer_state = TokenizerStates::EOT; self.token.token_type= TokenType::INTEGER},
         }
@@ -1165,6 +1983,7 @@ impl<'a> Tokenizer<'_>{
         match c{
 			'0'..='9'  =>  {self.value_no_white_space(c)},		//  this will be the fraction part of the float
 			'.'		   =>  {self.error_text = "Syntax err:  extra '.' found".to_string(); self.tokenizer_state = TokenizerStates::ERR}
+			'd' if self.decimal_suffix() => {self.tokenizer_state = TokenizerStates::EOT; self.token.token_type= TokenType::DECIMAL},
 			'\n' | '\r'| '\t' | ' ' => {},
 			_ 		   =>  {self.last_no_white_space(c); self.tokenizer_state = TokenizerStates::EOT; self.token.token_type= TokenType::FLOAT},
         }
@@ -1210,7 +2029,90 @@ impl<'a> Tokenizer<'_>{
         }
     }
 
-	//	We are collecting a string defined by '"" characters.
+	//	We are collecting a raw string, r"...", where a '\\' is just a '\\'.  Handy for
+	//	regular expressions
+	fn state_RAW_Q(&mut self, c : char){
+		match c{
+			'"'			=> 	{
+								self.tokenizer_state = TokenizerStates::EOT;
+								self.token.token_type= TokenType::STRING;
+							},
+			_ 		   	=>  self.token.token_value.push(c),
+		}
+	}
+
+	//	We are collecting a string defined by """ characters.  It's the same as a '"'
+	//	string except that it can have '"' in it and, when it's done, the indentation
+	//	common to its lines is removed
+	fn state_TRIPLE_Q(&mut self, c : char){
+		if c != '"'{
+			self.state_DOUBLE_Q(c);
+			return;
+		}
+
+		if !self.op_look_ahead(c,'"'){
+			self.token.token_value.push('"');
+		}else if !self.op_look_ahead(c,'"'){
+			self.token.token_value.push_str("\"\"");
+		}else{
+			self.tokenizer_state = TokenizerStates::EOT;
+			self.token.token_type= TokenType::STRING;
+			self.strip_common_indentation();
+		}
+	}
+
+	//	Remove the indentation shared by the lines of a """ string.  A first line that's
+	//	empty (i.e. the text starts on the line after the """) is dropped, as is the
+	//	white space in front of a closing """ on its own line.  Text on the same line as
+	//	the opening """ is left alone.  For example
+	//
+	//		html = """
+	//			<p>
+	//				hello
+	//			</p>
+	//			""";
+	//
+	//	is "<p>\n\thello\n</p>\n".  Each ${expression} is stood in for by a '\0' so it
+	//	stays on its line
+	fn strip_common_indentation(&mut self){
+		let mut text = String::new();
+		for (literal, _) in &self.interpolations{
+			text.push_str(literal);
+			text.push('\0');
+		}
+		text.push_str(&self.token.token_value);
+
+		let mut lines : Vec<&str> = text.split('\n').collect();
+		let mut first = 1;
+		if lines.len() > 1 && lines[0].trim().is_empty(){
+			lines.remove(0);
+			first = 0;
+		}
+		let last = lines.len() - 1;
+		if last > 0 && lines[last].trim().is_empty(){
+			lines[last] = "";
+		}
+
+		let indentation = lines.iter()
+			.skip(first)
+			.filter(|l| !l.trim().is_empty())
+			.map(|l| l.chars().take_while(|c| *c == ' ' || *c == '\t').count())
+			.min()
+			.unwrap_or(0);
+		let lines : Vec<String> = lines.iter().enumerate()
+			.map(|(i, l)| if i < first {l.to_string()} else {l.chars().skip(indentation).collect()})
+			.collect();
+		let text = lines.join("\n");
+
+		let mut pieces : Vec<&str> = text.split('\0').collect();
+		self.token.token_value = pieces.pop().unwrap().to_string();
+		for (i, piece) in pieces.iter().enumerate(){
+			self.interpolations[i].0 = piece.to_string();
+		}
+	}
+
+	//	We are collecting a string defined by '"" characters.  ${expression} is
+	//	interpolated and $$ is a '$'
 	fn state_DOUBLE_Q(&mut self, c : char){
 		//eprintln!("======== '{}'",c);
         match c{
@@ -1232,6 +2134,19 @@ impl<'a> Tokenizer<'_>{
 
 								}
 							},
+			'$'			=>	match self.next_char(){
+								Some('$')	=> self.token.token_value.push('$'),
+								Some('{')	=> {
+									let expression = self.interpolated_expression();
+									let literal = std::mem::take(&mut self.token.token_value);
+									self.interpolations.push((literal, expression));
+								},
+								Some(lahead)	=> {
+									self.token.token_value.push(c);
+									self.last_char.push(lahead);
+								},
+								None		=> self.token.token_value.push(c),
+							},
 			_ 		   	=>  self.token.token_value.push(c),
         }
     }